@@ -0,0 +1,18 @@
+use std::env;
+
+// poll_only: no way to block, as on wasm32-unknown-unknown without std
+// futex: the futex feature on a target the futex module has syscall numbers for
+fn main() {
+    println!("cargo::rustc-check-cfg=cfg(poll_only)");
+    println!("cargo::rustc-check-cfg=cfg(loom)");
+    println!("cargo::rustc-check-cfg=cfg(futex)");
+    let arch = env::var("CARGO_CFG_TARGET_ARCH").unwrap_or_default();
+    let os = env::var("CARGO_CFG_TARGET_OS").unwrap_or_default();
+    let std = env::var_os("CARGO_FEATURE_STD").is_some();
+    if arch == "wasm32" && os == "unknown" && !std {
+        println!("cargo::rustc-cfg=poll_only");
+    }
+    if env::var_os("CARGO_FEATURE_FUTEX").is_some() && os == "linux" && (arch == "x86_64" || arch == "aarch64") {
+        println!("cargo::rustc-cfg=futex");
+    }
+}
@@ -0,0 +1,54 @@
+#ifndef TRANSMITTER_H
+#define TRANSMITTER_H
+
+/* Generated by cbindgen from src/ffi.rs, do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+#define TRANSMITTER_READY 0
+
+#define TRANSMITTER_DROPPED 1
+
+#define TRANSMITTER_TIMEOUT 2
+
+typedef struct TransmitterGet TransmitterGet;
+
+typedef struct TransmitterSet TransmitterSet;
+
+/**
+ * # Safety
+ * `set` and `get` must be valid for writes.
+ */
+void transmitter_oneshot_new(struct TransmitterSet **set, struct TransmitterGet **get);
+
+/**
+ * # Safety
+ * `set` must come from `transmitter_oneshot_new` and is consumed. `free_value`, if not
+ * NULL, frees a delivered `value` that is never received; on false `value` stays the caller's.
+ */
+bool transmitter_oneshot_set(struct TransmitterSet *set, void *value, void (*free_value)(void*));
+
+/**
+ * # Safety
+ * `get` must come from `transmitter_oneshot_new` and is consumed, `value` must be valid for writes.
+ */
+int32_t transmitter_oneshot_wait(struct TransmitterGet *get, void **value);
+
+/**
+ * # Safety
+ * As for `transmitter_oneshot_wait`, except that `get` stays valid on a timeout.
+ */
+int32_t transmitter_oneshot_wait_timeout(struct TransmitterGet *get, uint64_t timeout_ms, void **value);
+
+/**
+ * # Safety
+ * `set` and `get` may each be NULL, otherwise they must come from `transmitter_oneshot_new`
+ * and not be used afterwards. A value still in the channel is freed with the last handle.
+ */
+void transmitter_oneshot_free(struct TransmitterSet *set, struct TransmitterGet *get);
+
+#endif  /* TRANSMITTER_H */
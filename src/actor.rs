@@ -0,0 +1,96 @@
+use std::fmt::{self,Debug};
+use std::thread;
+
+use crate::mpsc::{self,Sender};
+use crate::{oneshot,OneGet,OneSet};
+
+// the mailbox of an actor, it stops once every Address is dropped
+pub struct Address<M>(Sender<M>);
+impl<M> Address<M> {
+    // the message comes back if the actor has stopped
+    pub fn tell(&self, msg: M) -> Result<(),M> {
+        self.0.send(msg)
+    }
+    // builds the message around a reply oneshot, a stopped actor closes the OneGet
+    pub fn ask<R,F>(&self, f: F) -> OneGet<R>
+    where F: FnOnce(OneSet<R>) -> M
+    {
+        let (tx,rx) = oneshot();
+        let _ = self.0.send(f(tx));
+        rx
+    }
+    pub fn is_alive(&self) -> bool {
+        self.0.is_needed()
+    }
+}
+impl<M> Clone for Address<M> {
+    fn clone(&self) -> Address<M> {
+        Address(self.0.clone())
+    }
+}
+impl<M> Debug for Address<M> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Address")
+    }
+}
+
+// runs the handler on its own thread, one message at a time; a panic in it drops the
+// queued messages while unwinding, so pending asks see RecvError::SenderPanicked
+pub fn spawn_actor<M,F>(mut handler: F) -> Address<M>
+where M: Send + 'static, F: FnMut(M) + Send + 'static
+{
+    let (tx,rx) = mpsc::channel();
+    thread::spawn(move || {
+        for msg in rx {
+            handler(msg);
+        }
+    });
+    Address(tx)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::spawn_actor;
+    use crate::{OneSet,RecvError};
+
+    enum Counter {
+        Add(u32),
+        Get(OneSet<u32>),
+        Fail,
+    }
+
+    fn counter() -> super::Address<Counter> {
+        let mut total = 0;
+        spawn_actor(move |msg| match msg {
+            Counter::Add(n) => total += n,
+            Counter::Get(reply) => {
+                let _ = reply.set(total);
+            },
+            Counter::Fail => panic!("counter failed"),
+        })
+    }
+
+    #[test]
+    fn test_tell_ask() {
+        let addr = counter();
+        for i in 1 ..= 4 {
+            addr.tell(Counter::Add(i)).ok().unwrap();
+        }
+        assert_eq!(addr.ask(Counter::Get).wait(),Some(10));
+        assert_eq!(addr.clone().ask(Counter::Get).wait(),Some(10));
+    }
+
+    #[test]
+    fn test_stopped() {
+        let addr = counter();
+        addr.tell(Counter::Fail).ok().unwrap();
+        let reply = addr.ask(Counter::Get);
+        assert!(matches!(reply.recv(),Err(RecvError::SenderPanicked | RecvError::Disconnected)));
+        while addr.is_alive() {
+            std::thread::yield_now();
+        }
+        assert!(addr.tell(Counter::Add(1)).is_err());
+        assert_eq!(addr.ask(Counter::Get).wait(),None);
+    }
+}
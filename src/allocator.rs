@@ -0,0 +1,169 @@
+use core::fmt::{self,Debug};
+use core::mem::ManuallyDrop;
+use core::ptr::{self,NonNull};
+#[cfg(feature = "std")]
+use std::time::Duration;
+
+use allocator_api2::alloc::{handle_alloc_error,Allocator,Layout};
+
+#[cfg(not(poll_only))]
+use crate::RecvError;
+use crate::sync::{AtomicU8,Ordering};
+use crate::{InnerOne,State};
+
+// the channel state and the allocator it lives in; the last handle to go takes the
+// allocator out and frees the block with it
+struct Block<T,A: Allocator> {
+    inner: InnerOne<T>,
+    handles: AtomicU8,
+    alloc: ManuallyDrop<A>,
+}
+
+struct Handle<T,A: Allocator>(NonNull<Block<T,A>>);
+impl<T,A: Allocator> Handle<T,A> {
+    fn inner(&self) -> &InnerOne<T> {
+        unsafe { &self.0.as_ref().inner }
+    }
+    fn release(&self) {
+        if unsafe { self.0.as_ref() }.handles.fetch_sub(1,Ordering::AcqRel) == 1 {
+            unsafe {
+                let block = self.0.as_ptr();
+                let alloc = ManuallyDrop::take(&mut (*block).alloc);
+                ptr::drop_in_place(block);
+                alloc.deallocate(self.0.cast(),Layout::new::<Block<T,A>>());
+            }
+        }
+    }
+}
+// the allocator ends up on whichever thread drops the last handle
+unsafe impl<T: Send,A: Allocator + Send> Send for Handle<T,A> {}
+unsafe impl<T: Send,A: Allocator + Send> Sync for Handle<T,A> {}
+
+pub struct AllocGet<T,A: Allocator>(Handle<T,A>);
+impl<T,A: Allocator> AllocGet<T,A> {
+    pub fn is_ready(&self) -> bool {
+        self.state() == State::Ready
+    }
+    pub fn is_closed(&self) -> bool {
+        self.state() == State::SenderDropped
+    }
+    pub fn state(&self) -> State {
+        self.0.inner().receiver_state()
+    }
+    #[cfg(not(poll_only))]
+    pub fn wait(self) -> Option<T> {
+        self.0.inner().wait()
+    }
+    #[cfg(not(poll_only))]
+    pub fn recv(self) -> Result<T,RecvError> {
+        self.0.inner().wait().ok_or_else(|| self.0.inner().closed_error())
+    }
+    #[cfg(feature = "std")]
+    pub fn wait_timeout(self, timeout: Duration) -> Result<Option<T>,AllocGet<T,A>> {
+        match self.0.inner().wait_timeout(timeout) {
+            Some(res) => Ok(res),
+            None => Err(self),
+        }
+    }
+    pub fn try_take(&mut self) -> Option<Option<T>> {
+        self.0.inner().try_take()
+    }
+}
+impl<T,A: Allocator> Debug for AllocGet<T,A> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "AllocGet")
+    }
+}
+impl<T,A: Allocator> Drop for AllocGet<T,A> {
+    fn drop(&mut self) {
+        self.0.inner().drop_receiver();
+        self.0.release();
+    }
+}
+
+pub struct AllocSet<T,A: Allocator>(Handle<T,A>,bool);
+impl<T,A: Allocator> AllocSet<T,A> {
+    pub fn is_needed(&self) -> bool {
+        self.0.inner().is_needed()
+    }
+    pub fn state(&self) -> State {
+        self.0.inner().sender_state()
+    }
+    pub fn set(mut self, t: T) -> Result<(),T> {
+        self.1 = true;
+        self.0.inner().send(t)
+    }
+}
+impl<T,A: Allocator> Debug for AllocSet<T,A> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "AllocSet")
+    }
+}
+impl<T,A: Allocator> Drop for AllocSet<T,A> {
+    fn drop(&mut self) {
+        if !self.1 {
+            self.0.inner().drop_sender();
+        }
+        self.0.release();
+    }
+}
+
+// the channel state is allocated in alloc, e.g. an arena or a bumpalo Bump by reference
+pub fn oneshot_alloc<T,A: Allocator>(alloc: A) -> (AllocSet<T,A>,AllocGet<T,A>) {
+    let layout = Layout::new::<Block<T,A>>();
+    let block = match alloc.allocate(layout) {
+        Ok(block) => block.cast::<Block<T,A>>(),
+        Err(_) => handle_alloc_error(layout),
+    };
+    unsafe { block.as_ptr().write(Block { inner: InnerOne::new(), handles: AtomicU8::new(2), alloc: ManuallyDrop::new(alloc) }) };
+    (AllocSet(Handle(block),false),AllocGet(Handle(block)))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::oneshot_alloc;
+    use allocator_api2::alloc::{AllocError,Allocator,Global,Layout};
+    use core::ptr::NonNull;
+    use std::sync::atomic::{AtomicUsize,Ordering};
+    use std::thread;
+
+    // counts the blocks allocated through it that are still live
+    struct Counting<'a>(&'a AtomicUsize);
+    unsafe impl Allocator for Counting<'_> {
+        fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>,AllocError> {
+            self.0.fetch_add(1,Ordering::Relaxed);
+            Global.allocate(layout)
+        }
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            self.0.fetch_sub(1,Ordering::Relaxed);
+            Global.deallocate(ptr,layout)
+        }
+    }
+
+    #[test]
+    fn test_alloc_wait() {
+        static LIVE: AtomicUsize = AtomicUsize::new(0);
+        let (tx,rx) = oneshot_alloc(Counting(&LIVE));
+        assert_eq!(LIVE.load(Ordering::Relaxed),1);
+        let h = thread::spawn(move || tx.set(String::from("done")).unwrap());
+        assert_eq!(rx.wait().as_deref(),Some("done"));
+        h.join().unwrap();
+        assert_eq!(LIVE.load(Ordering::Relaxed),0);
+    }
+
+    #[test]
+    fn test_alloc_unreceived() {
+        let live = AtomicUsize::new(0);
+        let (tx,rx) = oneshot_alloc(Counting(&live));
+        tx.set(vec![1,2]).unwrap();
+        assert!(rx.is_ready());
+        drop(rx);
+        assert_eq!(live.load(Ordering::Relaxed),0);
+        let (tx,rx) = oneshot_alloc::<u8,_>(Counting(&live));
+        drop(tx);
+        assert!(rx.is_closed());
+        drop(rx);
+        assert_eq!(live.load(Ordering::Relaxed),0);
+    }
+}
@@ -0,0 +1,109 @@
+use core::fmt::{self,Debug};
+#[cfg(feature = "std")]
+use std::time::{Duration,Instant};
+
+use crate::sync::{Condvar,Mutex};
+
+struct Round {
+    arrived: usize,
+    generation: u64,
+}
+
+// reusable: the round restarts as soon as the last thread arrives
+pub struct Barrier {
+    n: usize,
+    round: Mutex<Round>,
+    cond: Condvar,
+}
+impl Barrier {
+    pub const fn new(n: usize) -> Barrier {
+        Barrier {
+            n,
+            round: Mutex::new(Round { arrived: 0, generation: 0 }),
+            cond: Condvar::new(),
+        }
+    }
+    // true for the thread completing the round
+    pub fn wait(&self) -> bool {
+        let mut round = self.round.lock();
+        let generation = round.generation;
+        if self.arrive(&mut round) {
+            return true;
+        }
+        while round.generation == generation {
+            round = self.cond.wait(round);
+        }
+        false
+    }
+    // None if the round didn't complete in time, the arrival is withdrawn then
+    #[cfg(feature = "std")]
+    pub fn wait_timeout(&self, timeout: Duration) -> Option<bool> {
+        let deadline = Instant::now().checked_add(timeout);
+        let mut round = self.round.lock();
+        let generation = round.generation;
+        if self.arrive(&mut round) {
+            return Some(true);
+        }
+        while round.generation == generation {
+            let (guard,timed_out) = self.cond.wait_deadline(round,deadline);
+            round = guard;
+            if timed_out && round.generation == generation {
+                round.arrived -= 1;
+                return None;
+            }
+        }
+        Some(false)
+    }
+    pub fn generation(&self) -> u64 {
+        self.round.lock().generation
+    }
+    fn arrive(&self, round: &mut Round) -> bool {
+        round.arrived += 1;
+        if round.arrived < self.n {
+            return false;
+        }
+        round.arrived = 0;
+        round.generation += 1;
+        self.cond.notify_all();
+        true
+    }
+}
+impl Debug for Barrier {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Barrier({})", self.n)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::Barrier;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_rounds() {
+        let barrier = Barrier::new(4);
+        let leaders = thread::scope(|s| {
+            let hs = (0 .. 4).map(|_| s.spawn(|| {
+                (0 .. 3).filter(|_| barrier.wait()).count()
+            })).collect::<Vec<_>>();
+            hs.into_iter().map(|h| h.join().unwrap()).sum::<usize>()
+        });
+        assert_eq!(leaders,3);
+        assert_eq!(barrier.generation(),3);
+    }
+
+    #[test]
+    fn test_wait_timeout() {
+        let barrier = Barrier::new(2);
+        assert_eq!(barrier.wait_timeout(Duration::from_millis(10)),None);
+        thread::scope(|s| {
+            let h = s.spawn(|| barrier.wait_timeout(Duration::from_secs(5)));
+            thread::sleep(Duration::from_millis(20));
+            let leader = barrier.wait();
+            assert_eq!(h.join().unwrap(),Some(!leader));
+        });
+        assert_eq!(barrier.generation(),1);
+    }
+}
@@ -0,0 +1,236 @@
+use alloc::collections::VecDeque;
+use core::error::Error;
+use core::fmt::{self,Debug};
+
+use crate::sync::{Arc,Condvar,Mutex};
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum RecvError {
+    Lagged(u64),
+    Disconnected,
+}
+impl fmt::Display for RecvError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RecvError::Lagged(n) => write!(f, "receiver lagged behind, {} values skipped", n),
+            RecvError::Disconnected => write!(f, "all senders dropped"),
+        }
+    }
+}
+impl Error for RecvError {}
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum TryRecvError {
+    Empty,
+    Lagged(u64),
+    Disconnected,
+}
+impl fmt::Display for TryRecvError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TryRecvError::Empty => write!(f, "no value ready yet"),
+            TryRecvError::Lagged(n) => write!(f, "receiver lagged behind, {} values skipped", n),
+            TryRecvError::Disconnected => write!(f, "all senders dropped"),
+        }
+    }
+}
+impl Error for TryRecvError {}
+
+struct Shared<T> {
+    // buffer[i] has sequence number first + i
+    buffer: VecDeque<T>,
+    first: u64,
+    senders: usize,
+    receivers: usize,
+}
+impl<T> Shared<T> {
+    fn next(&self) -> u64 {
+        self.first + self.buffer.len() as u64
+    }
+    fn take(&self, next: &mut u64) -> Option<Result<T,u64>> where T: Clone {
+        if *next < self.first {
+            let lagged = self.first - *next;
+            *next = self.first;
+            return Some(Err(lagged));
+        }
+        let t = self.buffer.get((*next - self.first) as usize)?.clone();
+        *next += 1;
+        Some(Ok(t))
+    }
+}
+
+struct Inner<T> {
+    shared: Mutex<Shared<T>>,
+    cond: Condvar,
+    capacity: usize,
+}
+
+pub struct Sender<T>(Arc<Inner<T>>);
+impl<T> Sender<T> {
+    pub fn send(&self, t: T) -> Result<(),T> {
+        let mut lock = self.0.shared.lock();
+        if lock.receivers == 0 {
+            return Err(t);
+        }
+        lock.buffer.push_back(t);
+        let overflow = match lock.buffer.len() > self.0.capacity {
+            true => {
+                lock.first += 1;
+                lock.buffer.pop_front()
+            },
+            false => None,
+        };
+        drop(lock);
+        self.0.cond.notify_all();
+        drop(overflow);
+        Ok(())
+    }
+    pub fn subscribe(&self) -> Receiver<T> {
+        let mut lock = self.0.shared.lock();
+        lock.receivers += 1;
+        Receiver {
+            inner: self.0.clone(),
+            next: lock.next(),
+        }
+    }
+    pub fn receiver_count(&self) -> usize {
+        self.0.shared.lock().receivers
+    }
+}
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Sender<T> {
+        self.0.shared.lock().senders += 1;
+        Sender(self.0.clone())
+    }
+}
+impl<T> Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Sender")
+    }
+}
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut lock = self.0.shared.lock();
+        lock.senders -= 1;
+        if lock.senders == 0 {
+            self.0.cond.notify_all();
+        }
+    }
+}
+
+pub struct Receiver<T> {
+    inner: Arc<Inner<T>>,
+    next: u64,
+}
+impl<T: Clone> Receiver<T> {
+    pub fn recv(&mut self) -> Result<T,RecvError> {
+        let mut lock = self.inner.shared.lock();
+        loop {
+            match lock.take(&mut self.next) {
+                Some(Ok(t)) => return Ok(t),
+                Some(Err(lagged)) => return Err(RecvError::Lagged(lagged)),
+                None if lock.senders == 0 => return Err(RecvError::Disconnected),
+                None => lock = self.inner.cond.wait(lock),
+            }
+        }
+    }
+    pub fn try_recv(&mut self) -> Result<T,TryRecvError> {
+        let lock = self.inner.shared.lock();
+        match lock.take(&mut self.next) {
+            Some(Ok(t)) => Ok(t),
+            Some(Err(lagged)) => Err(TryRecvError::Lagged(lagged)),
+            None if lock.senders == 0 => Err(TryRecvError::Disconnected),
+            None => Err(TryRecvError::Empty),
+        }
+    }
+}
+impl<T> Clone for Receiver<T> {
+    fn clone(&self) -> Receiver<T> {
+        self.inner.shared.lock().receivers += 1;
+        Receiver {
+            inner: self.inner.clone(),
+            next: self.next,
+        }
+    }
+}
+impl<T> Debug for Receiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Receiver")
+    }
+}
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.inner.shared.lock().receivers -= 1;
+    }
+}
+
+pub fn channel<T>(capacity: usize) -> (Sender<T>,Receiver<T>) {
+    assert!(capacity > 0, "broadcast capacity must be positive");
+    let r = Arc::new(Inner {
+        shared: Mutex::new(Shared {
+            buffer: VecDeque::with_capacity(capacity),
+            first: 0,
+            senders: 1,
+            receivers: 1,
+        }),
+        cond: Condvar::new(),
+        capacity,
+    });
+    (Sender(r.clone()),Receiver { inner: r, next: 0 })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{channel,RecvError,TryRecvError};
+    use std::thread;
+
+    #[test]
+    fn test_every_receiver_gets_value() {
+        let (tx,rx) = channel(16);
+        let hs = (0 .. 4).map(|_| {
+            let mut rx = rx.clone();
+            thread::spawn(move || {
+                let mut got = Vec::new();
+                while let Ok(v) = rx.recv() {
+                    got.push(v);
+                }
+                got
+            })
+        }).collect::<Vec<_>>();
+        drop(rx);
+        for i in 0 .. 10 {
+            tx.send(i).unwrap();
+        }
+        drop(tx);
+        for h in hs {
+            assert_eq!(h.join().unwrap(),(0 .. 10).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn test_lagged() {
+        let (tx,mut rx) = channel(2);
+        for i in 0 .. 5 {
+            tx.send(i).unwrap();
+        }
+        assert_eq!(rx.recv(),Err(RecvError::Lagged(3)));
+        assert_eq!(rx.recv(),Ok(3));
+        assert_eq!(rx.try_recv(),Ok(4));
+        assert_eq!(rx.try_recv(),Err(TryRecvError::Empty));
+        drop(tx);
+        assert_eq!(rx.try_recv(),Err(TryRecvError::Disconnected));
+    }
+
+    #[test]
+    fn test_subscribe() {
+        let (tx,rx) = channel(4);
+        tx.send(1).unwrap();
+        let mut late = tx.subscribe();
+        tx.send(2).unwrap();
+        assert_eq!(late.recv(),Ok(2));
+        drop(rx);
+        drop(late);
+        assert_eq!(tx.send(3),Err(3));
+    }
+}
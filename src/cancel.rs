@@ -0,0 +1,169 @@
+use std::collections::BTreeMap;
+use std::fmt::{self,Debug};
+use std::sync::Weak;
+use std::task::Waker;
+use std::time::{Duration,Instant};
+
+use crate::sync::{Arc,Condvar,Mutex};
+
+struct Tree {
+    cancelled: bool,
+    children: Vec<Weak<Inner>>,
+    wakers: BTreeMap<u64,Waker>,
+    next_id: u64,
+}
+
+struct Inner {
+    tree: Mutex<Tree>,
+    cond: Condvar,
+}
+impl Inner {
+    fn new(cancelled: bool) -> Arc<Inner> {
+        Arc::new(Inner {
+            tree: Mutex::new(Tree {
+                cancelled,
+                children: Vec::new(),
+                wakers: BTreeMap::new(),
+                next_id: 0,
+            }),
+            cond: Condvar::new(),
+        })
+    }
+    fn cancel(&self) {
+        let (children,wakers) = {
+            let mut tree = self.tree.lock();
+            if tree.cancelled {
+                return;
+            }
+            tree.cancelled = true;
+            self.cond.notify_all();
+            (std::mem::take(&mut tree.children),std::mem::take(&mut tree.wakers))
+        };
+        for waker in wakers.into_values() {
+            waker.wake();
+        }
+        for child in children {
+            if let Some(child) = child.upgrade() {
+                child.cancel();
+            }
+        }
+    }
+}
+
+// clones share the cancellation, children are cancelled with their parent but not the other way
+pub struct CancellationToken(Arc<Inner>);
+impl CancellationToken {
+    pub fn new() -> CancellationToken {
+        CancellationToken(Inner::new(false))
+    }
+    pub fn child(&self) -> CancellationToken {
+        let mut tree = self.0.tree.lock();
+        let child = Inner::new(tree.cancelled);
+        if !tree.cancelled {
+            tree.children.retain(|c| c.strong_count() > 0);
+            tree.children.push(Arc::downgrade(&child));
+        }
+        CancellationToken(child)
+    }
+    pub fn cancel(&self) {
+        self.0.cancel()
+    }
+    pub fn is_cancelled(&self) -> bool {
+        self.0.tree.lock().cancelled
+    }
+    pub fn wait_cancelled(&self) {
+        let mut tree = self.0.tree.lock();
+        while !tree.cancelled {
+            tree = self.0.cond.wait(tree);
+        }
+    }
+    pub fn wait_cancelled_timeout(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now().checked_add(timeout);
+        let mut tree = self.0.tree.lock();
+        while !tree.cancelled {
+            let (guard,timed_out) = self.0.cond.wait_deadline(tree,deadline);
+            if timed_out {
+                return false;
+            }
+            tree = guard;
+        }
+        true
+    }
+    // None if already cancelled, the waker is not kept then
+    pub(crate) fn watch(&self, waker: &Waker) -> Option<u64> {
+        let mut tree = self.0.tree.lock();
+        if tree.cancelled {
+            return None;
+        }
+        let id = tree.next_id;
+        tree.next_id += 1;
+        tree.wakers.insert(id,waker.clone());
+        Some(id)
+    }
+    pub(crate) fn unwatch(&self, id: u64) {
+        self.0.tree.lock().wakers.remove(&id);
+    }
+}
+impl Clone for CancellationToken {
+    fn clone(&self) -> CancellationToken {
+        CancellationToken(self.0.clone())
+    }
+}
+impl Default for CancellationToken {
+    fn default() -> CancellationToken {
+        CancellationToken::new()
+    }
+}
+impl Debug for CancellationToken {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "CancellationToken({})", self.is_cancelled())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::CancellationToken;
+    use crate::oneshot;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_children() {
+        let root = CancellationToken::new();
+        let child = root.child();
+        let grandchild = child.child();
+        let other = root.child();
+        child.cancel();
+        assert!(grandchild.is_cancelled());
+        assert!(!root.is_cancelled());
+        assert!(!other.wait_cancelled_timeout(Duration::from_millis(20)));
+        let h = thread::spawn(move || other.wait_cancelled());
+        root.cancel();
+        h.join().unwrap();
+        assert!(root.child().is_cancelled());
+    }
+
+    #[test]
+    fn test_wait_cancellable() {
+        let token = CancellationToken::new();
+        let (tx,rx) = oneshot::<u64>();
+        let t = token.clone();
+        let h = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            t.cancel();
+        });
+        let rx = rx.wait_cancellable(&token).unwrap_err();
+        h.join().unwrap();
+        tx.set(4).unwrap();
+        assert_eq!(rx.wait(),Some(4));
+        let (tx,rx) = oneshot();
+        let h = thread::spawn(move || tx.set(5).unwrap());
+        let token = CancellationToken::new().child();
+        assert_eq!(rx.wait_cancellable(&token).unwrap(),Some(5));
+        token.cancel();
+        let (_tx,rx) = oneshot::<u64>();
+        assert!(rx.wait_cancellable(&token).is_err());
+        h.join().unwrap();
+    }
+}
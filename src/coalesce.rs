@@ -0,0 +1,151 @@
+use std::cell::Cell;
+use std::fmt::{self,Debug};
+use std::thread;
+use std::time::{Duration,Instant};
+
+use crate::error::{RecvError,TryRecvError};
+use crate::iter::Receive;
+
+// drains what is ready, keeping the newest; Ok(None) if nothing was
+fn latest<R: Receive>(rx: &R) -> Result<Option<R::Item>,RecvError> {
+    let mut last = None;
+    loop {
+        match rx.try_recv() {
+            Ok(t) => last = Some(t),
+            Err(TryRecvError::Empty) => return Ok(last),
+            Err(TryRecvError::Disconnected) => return last.map(Some).ok_or(RecvError::Disconnected),
+            Err(TryRecvError::Malformed) => return Err(RecvError::Malformed),
+        }
+    }
+}
+
+// recv hands out the newest value once nothing new came for a whole quiet period
+pub struct Debounce<R> {
+    rx: R,
+    quiet: Duration,
+}
+impl<R: Receive> Debounce<R> {
+    pub fn new(rx: R, quiet: Duration) -> Debounce<R> {
+        Debounce { rx, quiet }
+    }
+    pub fn into_inner(self) -> R {
+        self.rx
+    }
+}
+impl<R: Receive> Receive for Debounce<R> {
+    type Item = R::Item;
+    fn recv(&self) -> Result<R::Item,RecvError> {
+        let mut last = self.rx.recv()?;
+        loop {
+            thread::sleep(self.quiet);
+            match latest(&self.rx) {
+                Ok(Some(t)) => last = t,
+                // a disconnect during the quiet period still delivers the value
+                Ok(None) | Err(_) => return Ok(last),
+            }
+        }
+    }
+    fn try_recv(&self) -> Result<R::Item,TryRecvError> {
+        match latest(&self.rx) {
+            Ok(Some(t)) => Ok(t),
+            Ok(None) => Err(TryRecvError::Empty),
+            Err(_) => Err(TryRecvError::Disconnected),
+        }
+    }
+}
+impl<R> Debug for Debounce<R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Debounce({:?})", self.quiet)
+    }
+}
+
+// recv returns at most once per period, the newest value of what came meanwhile
+pub struct Throttle<R> {
+    rx: R,
+    period: Duration,
+    last: Cell<Option<Instant>>,
+}
+impl<R: Receive> Throttle<R> {
+    pub fn new(rx: R, period: Duration) -> Throttle<R> {
+        Throttle { rx, period, last: Cell::new(None) }
+    }
+    pub fn into_inner(self) -> R {
+        self.rx
+    }
+    fn next_at(&self) -> Option<Instant> {
+        self.last.get().and_then(|last| last.checked_add(self.period))
+    }
+}
+impl<R: Receive> Receive for Throttle<R> {
+    type Item = R::Item;
+    fn recv(&self) -> Result<R::Item,RecvError> {
+        let mut last = self.rx.recv()?;
+        if let Some(left) = self.next_at().and_then(|at| at.checked_duration_since(Instant::now())) {
+            thread::sleep(left);
+            if let Ok(Some(t)) = latest(&self.rx) {
+                last = t;
+            }
+        }
+        self.last.set(Some(Instant::now()));
+        Ok(last)
+    }
+    fn try_recv(&self) -> Result<R::Item,TryRecvError> {
+        if self.next_at().is_some_and(|at| at > Instant::now()) {
+            return Err(TryRecvError::Empty);
+        }
+        match latest(&self.rx) {
+            Ok(Some(t)) => {
+                self.last.set(Some(Instant::now()));
+                Ok(t)
+            },
+            Ok(None) => Err(TryRecvError::Empty),
+            Err(_) => Err(TryRecvError::Disconnected),
+        }
+    }
+}
+impl<R> Debug for Throttle<R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Throttle({:?})", self.period)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::{mpsc,Receive};
+    use std::thread;
+    use std::time::{Duration,Instant};
+
+    #[test]
+    fn test_debounce() {
+        let (tx,rx) = mpsc::channel();
+        let rx = rx.debounce(Duration::from_millis(30));
+        let h = thread::spawn(move || {
+            for i in 0 .. 5 {
+                tx.send(i).unwrap();
+                thread::sleep(Duration::from_millis(5));
+            }
+            thread::sleep(Duration::from_millis(100));
+            tx.send(10).unwrap();
+        });
+        assert_eq!(rx.recv(),Ok(4));
+        assert_eq!(rx.recv(),Ok(10));
+        assert!(rx.recv().is_err());
+        h.join().unwrap();
+    }
+
+    #[test]
+    fn test_throttle() {
+        let (tx,rx) = mpsc::channel();
+        let rx = rx.throttle(Duration::from_millis(50));
+        for i in 0 .. 3 {
+            tx.send(i).unwrap();
+        }
+        let start = Instant::now();
+        assert_eq!(rx.recv(),Ok(0));
+        assert_eq!(rx.recv(),Ok(2));
+        assert!(start.elapsed() >= Duration::from_millis(50));
+        tx.send(3).unwrap();
+        assert!(rx.try_recv().is_err());
+    }
+}
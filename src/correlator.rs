@@ -0,0 +1,116 @@
+use core::fmt::{self,Debug};
+use core::hash::Hash;
+use std::collections::HashMap;
+use std::time::{Duration,Instant};
+
+use crate::sync::Mutex;
+use crate::{oneshot,OneGet,OneSet};
+
+struct Entry<T> {
+    // taken only by sweep, as the entry leaves the map
+    tx: Option<OneSet<T>>,
+    deadline: Option<Instant>,
+}
+
+pub struct Correlator<K,T>(Mutex<HashMap<K,Entry<T>>>);
+impl<K: Eq + Hash,T> Correlator<K,T> {
+    pub fn new() -> Correlator<K,T> {
+        Correlator(Mutex::new(HashMap::new()))
+    }
+    fn insert(&self, key: K, deadline: Option<Instant>) -> OneGet<T> {
+        let (tx,rx) = oneshot();
+        // an older request with the same key is disconnected
+        let old = self.0.lock().insert(key,Entry { tx: Some(tx), deadline });
+        drop(old);
+        rx
+    }
+    pub fn expect(&self, key: K) -> OneGet<T> {
+        self.insert(key,None)
+    }
+    pub fn expect_timeout(&self, key: K, timeout: Duration) -> OneGet<T> {
+        self.insert(key,Instant::now().checked_add(timeout))
+    }
+    pub fn complete(&self, key: &K, t: T) -> Result<(),T> {
+        let entry = self.0.lock().remove(key);
+        match entry.and_then(|entry| entry.tx) {
+            Some(tx) => tx.set(t),
+            None => Err(t),
+        }
+    }
+    pub fn cancel(&self, key: &K) -> bool {
+        let entry = self.0.lock().remove(key);
+        entry.is_some()
+    }
+    pub fn len(&self) -> usize {
+        self.0.lock().len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    // drops expired entries and the ones nobody waits for, returns how many
+    pub fn sweep(&self) -> usize {
+        let now = Instant::now();
+        let mut stale = Vec::new();
+        self.0.lock().retain(|_,e| {
+            let expired = e.deadline.is_some_and(|d| d <= now);
+            if expired || !e.tx.as_ref().is_some_and(|tx| tx.is_needed()) {
+                stale.push(e.tx.take());
+                return false;
+            }
+            true
+        });
+        // the setters are dropped outside the lock
+        stale.len()
+    }
+}
+impl<K: Eq + Hash,T> Default for Correlator<K,T> {
+    fn default() -> Correlator<K,T> {
+        Correlator::new()
+    }
+}
+impl<K,T> Debug for Correlator<K,T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Correlator")
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::Correlator;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_complete() {
+        let c = Correlator::new();
+        let rx1 = c.expect(1u64);
+        let rx2 = c.expect(2u64);
+        assert_eq!(c.len(),2);
+        thread::scope(|s| {
+            s.spawn(|| {
+                c.complete(&2,"two").unwrap();
+                c.complete(&1,"one").unwrap();
+            });
+        });
+        assert_eq!(rx1.wait(),Some("one"));
+        assert_eq!(rx2.wait(),Some("two"));
+        assert_eq!(c.complete(&1,"again"),Err("again"));
+        assert!(c.is_empty());
+    }
+
+    #[test]
+    fn test_sweep() {
+        let c = Correlator::new();
+        let rx1 = c.expect_timeout(1u64,Duration::from_millis(50));
+        let rx2 = c.expect(2);
+        let rx3 = c.expect(3);
+        drop(rx3);
+        thread::sleep(Duration::from_millis(100));
+        assert_eq!(c.sweep(),2);
+        assert_eq!(rx1.wait(),None);
+        c.complete(&2,2).unwrap();
+        assert_eq!(rx2.wait(),Some(2));
+        assert!(!c.cancel(&1));
+    }
+}
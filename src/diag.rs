@@ -0,0 +1,98 @@
+use std::backtrace::Backtrace;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicU64,Ordering};
+use std::thread::{self,Thread};
+use std::time::{Duration,Instant};
+
+use crate::sync::{Arc,Mutex};
+
+#[derive(Debug)]
+pub struct LongWait {
+    pub id: u64,
+    pub waited: Duration,
+    pub thread: Thread,
+    pub created: Arc<Backtrace>,
+}
+
+struct Info {
+    id: u64,
+    created: Arc<Backtrace>,
+}
+
+struct Diagnostics {
+    threshold: Duration,
+    hook: Box<dyn Fn(&LongWait) + Send + Sync>,
+    // keyed by the address of the channel state, removed when it is freed
+    channels: Mutex<HashMap<usize,Info>>,
+    next_id: AtomicU64,
+}
+
+static DIAGNOSTICS: OnceLock<Diagnostics> = OnceLock::new();
+
+// opt-in, once per process: oneshots created afterwards record their creation backtrace
+// and a wait on them running past the threshold calls the hook once
+pub fn set_long_wait_hook(threshold: Duration, hook: impl Fn(&LongWait) + Send + Sync + 'static) -> bool {
+    DIAGNOSTICS.set(Diagnostics {
+        threshold,
+        hook: Box::new(hook),
+        channels: Mutex::new(HashMap::new()),
+        next_id: AtomicU64::new(0),
+    }).is_ok()
+}
+
+pub(crate) fn threshold() -> Option<Duration> {
+    DIAGNOSTICS.get().map(|d| d.threshold)
+}
+
+pub(crate) fn created(addr: usize) {
+    if let Some(d) = DIAGNOSTICS.get() {
+        let id = d.next_id.fetch_add(1,Ordering::Relaxed);
+        let created = Arc::new(Backtrace::force_capture());
+        d.channels.lock().insert(addr,Info { id, created });
+    }
+}
+
+pub(crate) fn freed(addr: usize) {
+    if let Some(d) = DIAGNOSTICS.get() {
+        d.channels.lock().remove(&addr);
+    }
+}
+
+pub(crate) fn long_wait(addr: usize, since: Instant) {
+    if let Some(d) = DIAGNOSTICS.get() {
+        let info = d.channels.lock().get(&addr).map(|i| (i.id,i.created.clone()));
+        // channels made before the hook was installed aren't tracked
+        if let Some((id,created)) = info {
+            (d.hook)(&LongWait { id, waited: since.elapsed(), thread: thread::current(), created });
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::set_long_wait_hook;
+    use crate::oneshot;
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_long_wait() {
+        let (reports,reported) = mpsc::channel();
+        let reports = std::sync::Mutex::new(reports);
+        assert!(set_long_wait_hook(Duration::from_millis(50),move |w| {
+            let _ = reports.lock().unwrap().send((w.waited,w.thread.id()));
+        }));
+        let (tx,rx) = oneshot();
+        let h = thread::spawn(move || {
+            let id = thread::current().id();
+            (rx.wait(),id)
+        });
+        let (waited,thread) = reported.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert!(waited >= Duration::from_millis(50));
+        tx.set(1).unwrap();
+        assert_eq!(h.join().unwrap(),(Some(1),thread));
+    }
+}
@@ -0,0 +1,108 @@
+use alloc::boxed::Box;
+use core::any::Any;
+use core::fmt::{self,Debug};
+#[cfg(feature = "std")]
+use std::time::Duration;
+
+use crate::error::DowncastError;
+use crate::{oneshot,OneGet,OneSet};
+
+type Payload = Box<dyn Any + Send>;
+
+fn downcast<T: Any>(res: Option<Payload>) -> Result<T,DowncastError> {
+    match res {
+        Some(b) => b.downcast::<T>().map(|t| *t).map_err(DowncastError::WrongType),
+        None => Err(DowncastError::Disconnected),
+    }
+}
+
+// one type for every payload, so a single map can hold replies of many types
+pub struct DynOneSet(OneSet<Payload>);
+impl DynOneSet {
+    pub fn is_needed(&self) -> bool {
+        self.0.is_needed()
+    }
+    pub fn set<T: Any + Send>(self, t: T) -> Result<(),T> {
+        // only a T can come back
+        self.0.set(Box::new(t)).map_err(|b| *b.downcast::<T>().unwrap())
+    }
+}
+impl Debug for DynOneSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "DynOneSet")
+    }
+}
+
+// the payload type is checked when it is received
+pub struct DynOneGet(OneGet<Payload>);
+impl DynOneGet {
+    pub fn is_ready(&self) -> bool {
+        self.0.is_ready()
+    }
+    #[cfg(not(poll_only))]
+    pub fn wait<T: Any>(self) -> Result<T,DowncastError> {
+        downcast(self.0.wait())
+    }
+    #[cfg(feature = "std")]
+    pub fn wait_timeout<T: Any>(self, timeout: Duration) -> Result<Result<T,DowncastError>,DynOneGet> {
+        self.0.wait_timeout(timeout).map(downcast).map_err(DynOneGet)
+    }
+    pub fn try_get<T: Any>(self) -> Result<Result<T,DowncastError>,DynOneGet> {
+        self.0.try_get().map(downcast).map_err(DynOneGet)
+    }
+    // a OneGet of the expected type, a mismatch keeps the payload
+    pub fn typed<T: Any + Send>(self) -> OneGet<Result<T,Payload>> {
+        self.0.map(|b| b.downcast::<T>().map(|t| *t))
+    }
+}
+impl Debug for DynOneGet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "DynOneGet")
+    }
+}
+
+pub fn dyn_oneshot() -> (DynOneSet,DynOneGet) {
+    let (tx,rx) = oneshot();
+    (DynOneSet(tx),DynOneGet(rx))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{dyn_oneshot,DynOneSet};
+    use crate::DowncastError;
+    use std::collections::HashMap;
+    use std::thread;
+
+    #[test]
+    fn test_registry() {
+        let mut pending: HashMap<u32,DynOneSet> = HashMap::new();
+        let (tx,num) = dyn_oneshot();
+        pending.insert(1,tx);
+        let (tx,text) = dyn_oneshot();
+        pending.insert(2,tx);
+        let h = thread::spawn(move || {
+            pending.remove(&2).unwrap().set(String::from("two")).unwrap();
+            pending.remove(&1).unwrap().set(1u64).unwrap();
+        });
+        assert_eq!(text.wait::<String>().unwrap(),"two");
+        assert_eq!(num.wait::<u64>().unwrap(),1);
+        h.join().unwrap();
+    }
+
+    #[test]
+    fn test_mismatch() {
+        let (tx,rx) = dyn_oneshot();
+        tx.set(7u8).unwrap();
+        match rx.wait::<u16>() {
+            Err(DowncastError::WrongType(b)) => assert_eq!(b.downcast_ref::<u8>(),Some(&7)),
+            other => panic!("unexpected {:?}", other),
+        }
+        let (tx,rx) = dyn_oneshot();
+        drop(tx);
+        assert!(matches!(rx.wait::<u8>(),Err(DowncastError::Disconnected)));
+        let (tx,rx) = dyn_oneshot();
+        drop(rx);
+        assert_eq!(tx.set(3i32),Err(3));
+    }
+}
@@ -0,0 +1,154 @@
+#[cfg(feature = "alloc")]
+use alloc::boxed::Box;
+#[cfg(feature = "alloc")]
+use core::any::Any;
+use core::error::Error;
+use core::fmt;
+
+#[cfg(feature = "alloc")]
+use crate::OneGet;
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum RecvError {
+    Disconnected,
+    Timeout,
+    SenderPanicked,
+    // a value arrived over a wire that doesn't decode as the channel's type
+    Malformed,
+}
+impl fmt::Display for RecvError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RecvError::Disconnected => write!(f, "sender dropped without setting a value"),
+            RecvError::Timeout => write!(f, "no value was set before the deadline"),
+            RecvError::SenderPanicked => write!(f, "sender dropped by a panicking thread"),
+            RecvError::Malformed => write!(f, "received a value that doesn't decode"),
+        }
+    }
+}
+impl Error for RecvError {}
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum TryRecvError {
+    Empty,
+    Disconnected,
+    Malformed,
+}
+impl fmt::Display for TryRecvError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TryRecvError::Empty => write!(f, "no value ready yet"),
+            TryRecvError::Disconnected => write!(f, "all senders dropped"),
+            TryRecvError::Malformed => write!(f, "received a value that doesn't decode"),
+        }
+    }
+}
+impl Error for TryRecvError {}
+
+#[derive(Clone,Copy,PartialEq,Eq)]
+pub enum TrySendError<T> {
+    Full(T),
+    Disconnected(T),
+    // the value can never fit the channel, however much is drained
+    TooLarge(T),
+}
+impl<T> TrySendError<T> {
+    pub fn into_inner(self) -> T {
+        match self {
+            TrySendError::Full(t) | TrySendError::Disconnected(t) | TrySendError::TooLarge(t) => t,
+        }
+    }
+}
+impl<T> fmt::Debug for TrySendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TrySendError::Full(_) => write!(f, "Full(..)"),
+            TrySendError::Disconnected(_) => write!(f, "Disconnected(..)"),
+            TrySendError::TooLarge(_) => write!(f, "TooLarge(..)"),
+        }
+    }
+}
+impl<T> fmt::Display for TrySendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TrySendError::Full(_) => write!(f, "channel is full"),
+            TrySendError::Disconnected(_) => write!(f, "receiver dropped"),
+            TrySendError::TooLarge(_) => write!(f, "value too large for the channel"),
+        }
+    }
+}
+impl<T> Error for TrySendError<T> {}
+
+#[cfg(feature = "alloc")]
+pub enum RecvTimeoutError<T> {
+    Timeout { handle: OneGet<T> },
+    Disconnected,
+}
+#[cfg(feature = "alloc")]
+impl<T> RecvTimeoutError<T> {
+    pub fn is_timeout(&self) -> bool {
+        matches!(self, RecvTimeoutError::Timeout { .. })
+    }
+    pub fn into_handle(self) -> Option<OneGet<T>> {
+        match self {
+            RecvTimeoutError::Timeout { handle } => Some(handle),
+            RecvTimeoutError::Disconnected => None,
+        }
+    }
+}
+#[cfg(feature = "alloc")]
+impl<T> fmt::Debug for RecvTimeoutError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RecvTimeoutError::Timeout { .. } => write!(f, "Timeout {{ .. }}"),
+            RecvTimeoutError::Disconnected => write!(f, "Disconnected"),
+        }
+    }
+}
+#[cfg(feature = "alloc")]
+impl<T> fmt::Display for RecvTimeoutError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RecvTimeoutError::Timeout { .. } => write!(f, "timed out waiting for the value"),
+            RecvTimeoutError::Disconnected => write!(f, "sender dropped without setting a value"),
+        }
+    }
+}
+#[cfg(feature = "alloc")]
+impl<T> Error for RecvTimeoutError<T> {}
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub struct Elapsed;
+impl fmt::Display for Elapsed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "deadline elapsed before the value")
+    }
+}
+impl Error for Elapsed {}
+
+// the received payload comes back on a type mismatch
+#[cfg(feature = "alloc")]
+pub enum DowncastError {
+    Disconnected,
+    WrongType(Box<dyn Any + Send>),
+}
+#[cfg(feature = "alloc")]
+impl fmt::Debug for DowncastError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DowncastError::Disconnected => write!(f, "Disconnected"),
+            DowncastError::WrongType(_) => write!(f, "WrongType(..)"),
+        }
+    }
+}
+#[cfg(feature = "alloc")]
+impl fmt::Display for DowncastError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DowncastError::Disconnected => write!(f, "sender dropped without setting a value"),
+            DowncastError::WrongType(_) => write!(f, "value is not of the requested type"),
+        }
+    }
+}
+#[cfg(feature = "alloc")]
+impl Error for DowncastError {}
@@ -0,0 +1,104 @@
+use core::fmt::{self,Debug};
+#[cfg(feature = "std")]
+use std::time::{Duration,Instant};
+
+use crate::sync::{Condvar,Mutex};
+
+// stays set, releasing every waiter, until reset
+pub struct Event {
+    set: Mutex<bool>,
+    cond: Condvar,
+}
+impl Event {
+    pub const fn new(set: bool) -> Event {
+        Event {
+            set: Mutex::new(set),
+            cond: Condvar::new(),
+        }
+    }
+    pub fn set(&self) {
+        let mut set = self.set.lock();
+        if !*set {
+            *set = true;
+            self.cond.notify_all();
+        }
+    }
+    pub fn reset(&self) {
+        *self.set.lock() = false;
+    }
+    pub fn is_set(&self) -> bool {
+        *self.set.lock()
+    }
+    pub fn wait(&self) {
+        let mut set = self.set.lock();
+        while !*set {
+            set = self.cond.wait(set);
+        }
+    }
+    #[cfg(feature = "std")]
+    pub fn wait_timeout(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now().checked_add(timeout);
+        let mut set = self.set.lock();
+        while !*set {
+            let (guard,timed_out) = self.cond.wait_deadline(set,deadline);
+            if timed_out {
+                return false;
+            }
+            set = guard;
+        }
+        true
+    }
+}
+impl Default for Event {
+    fn default() -> Event {
+        Event::new(false)
+    }
+}
+impl Debug for Event {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Event({})", self.is_set())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::Event;
+    use std::sync::atomic::{AtomicUsize,Ordering};
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_set_reset() {
+        let event = Event::new(false);
+        let passed = AtomicUsize::new(0);
+        thread::scope(|s| {
+            for _ in 0 .. 3 {
+                s.spawn(|| {
+                    event.wait();
+                    passed.fetch_add(1,Ordering::SeqCst);
+                });
+            }
+            thread::sleep(Duration::from_millis(50));
+            assert_eq!(passed.load(Ordering::SeqCst),0);
+            event.set();
+        });
+        assert_eq!(passed.load(Ordering::SeqCst),3);
+        event.wait();
+        event.reset();
+        assert!(!event.is_set());
+    }
+
+    #[test]
+    fn test_wait_timeout() {
+        let event = Event::default();
+        assert!(!event.wait_timeout(Duration::from_millis(50)));
+        thread::scope(|s| {
+            s.spawn(|| {
+                thread::sleep(Duration::from_millis(50));
+                event.set();
+            });
+            assert!(event.wait_timeout(Duration::from_secs(5)));
+        });
+    }
+}
@@ -0,0 +1,91 @@
+use core::fmt::{self,Debug};
+use std::time::Duration;
+
+use crate::sync::Mutex;
+use crate::{oneshot,OneSet};
+
+struct Slot<T> {
+    next_id: u64,
+    // the first thread to arrive, waiting on the other end of its OneSet
+    waiting: Option<(u64,T,OneSet<T>)>,
+}
+
+// pairs up threads two by two, each gets the other's value
+pub struct Exchanger<T>(Mutex<Slot<T>>);
+impl<T> Exchanger<T> {
+    pub const fn new() -> Exchanger<T> {
+        Exchanger(Mutex::new(Slot { next_id: 0, waiting: None }))
+    }
+    // gives the value back if no partner came in time
+    pub fn exchange(&self, t: T, timeout: Duration) -> Result<T,T> {
+        let (id,rx) = {
+            let mut slot = self.0.lock();
+            if let Some((_,theirs,tx)) = slot.waiting.take() {
+                drop(slot);
+                // the partner only gives up after taking its entry back, so it is still there
+                let _ = tx.set(t);
+                return Ok(theirs);
+            }
+            let id = slot.next_id;
+            slot.next_id += 1;
+            let (tx,rx) = oneshot();
+            slot.waiting = Some((id,t,tx));
+            (id,rx)
+        };
+        let rx = match rx.wait_timeout(timeout) {
+            Ok(res) => return Ok(res.unwrap()),
+            Err(rx) => rx,
+        };
+        let mut slot = self.0.lock();
+        match slot.waiting.take_if(|(waiting,_,_)| *waiting == id) {
+            Some((_,t,_)) => Err(t),
+            None => {
+                drop(slot);
+                // taken by a partner that is setting its value right now
+                Ok(rx.wait().unwrap())
+            },
+        }
+    }
+}
+impl<T> Default for Exchanger<T> {
+    fn default() -> Exchanger<T> {
+        Exchanger::new()
+    }
+}
+impl<T> Debug for Exchanger<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Exchanger")
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::Exchanger;
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_exchange() {
+        let ex = Arc::new(Exchanger::new());
+        let hs = (0 .. 4).map(|i| {
+            let ex = ex.clone();
+            thread::spawn(move || ex.exchange(i,Duration::from_secs(5)).unwrap())
+        }).collect::<Vec<_>>();
+        let mut got = hs.into_iter().map(|h| h.join().unwrap()).collect::<Vec<_>>();
+        got.sort();
+        assert_eq!(got,vec![0,1,2,3]);
+    }
+
+    #[test]
+    fn test_timeout() {
+        let ex = Arc::new(Exchanger::new());
+        assert_eq!(ex.exchange(1,Duration::from_millis(10)),Err(1));
+        let ex2 = ex.clone();
+        let h = thread::spawn(move || ex2.exchange(2,Duration::from_secs(5)));
+        thread::sleep(Duration::from_millis(20));
+        assert_eq!(ex.exchange(3,Duration::from_secs(5)),Ok(2));
+        assert_eq!(h.join().unwrap(),Ok(3));
+    }
+}
@@ -0,0 +1,202 @@
+use core::ffi::c_void;
+use core::mem;
+use core::ptr;
+use std::time::Duration;
+
+use crate::{oneshot,OneGet,OneSet};
+
+pub type FreeFn = unsafe extern "C" fn(*mut c_void);
+
+// the payload is owned by whoever holds it; one nobody receives goes to its free
+// function once the last handle of its channel is freed
+#[derive(Debug)]
+pub struct Payload {
+    ptr: *mut c_void,
+    free: Option<FreeFn>,
+}
+unsafe impl Send for Payload {}
+impl Payload {
+    pub fn new(ptr: *mut c_void, free: Option<FreeFn>) -> Payload {
+        Payload { ptr, free }
+    }
+    pub fn as_ptr(&self) -> *mut c_void {
+        self.ptr
+    }
+    // the receiver's now, the free function is not called
+    pub fn into_raw(self) -> *mut c_void {
+        let ptr = self.ptr;
+        mem::forget(self);
+        ptr
+    }
+}
+impl Drop for Payload {
+    fn drop(&mut self) {
+        if let Some(free) = self.free {
+            if !self.ptr.is_null() {
+                unsafe { free(self.ptr) };
+            }
+        }
+    }
+}
+
+// opaque to C, only ever handled through pointers; not repr(C), so cbindgen declares
+// them without a body
+pub struct TransmitterSet {
+    _private: [u8; 0],
+}
+pub struct TransmitterGet {
+    _private: [u8; 0],
+}
+
+pub const TRANSMITTER_READY: i32 = 0;
+pub const TRANSMITTER_DROPPED: i32 = 1;
+pub const TRANSMITTER_TIMEOUT: i32 = 2;
+
+// hands a Rust-created half to C, which completes or frees it
+pub fn setter_into_c(tx: OneSet<Payload>) -> *mut TransmitterSet {
+    Box::into_raw(Box::new(tx)) as *mut TransmitterSet
+}
+pub fn getter_into_c(rx: OneGet<Payload>) -> *mut TransmitterGet {
+    Box::into_raw(Box::new(rx)) as *mut TransmitterGet
+}
+
+/// # Safety
+/// `set` and `get` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn transmitter_oneshot_new(set: *mut *mut TransmitterSet, get: *mut *mut TransmitterGet) {
+    let (tx,rx) = oneshot();
+    *set = setter_into_c(tx);
+    *get = getter_into_c(rx);
+}
+
+/// # Safety
+/// `set` must come from `transmitter_oneshot_new` and is consumed. `free_value`, if not
+/// NULL, frees a delivered `value` that is never received; on false `value` stays the caller's.
+#[no_mangle]
+pub unsafe extern "C" fn transmitter_oneshot_set(set: *mut TransmitterSet, value: *mut c_void, free_value: Option<unsafe extern "C" fn(*mut c_void)>) -> bool {
+    let tx = Box::from_raw(set as *mut OneSet<Payload>);
+    match tx.set(Payload::new(value,free_value)) {
+        Ok(()) => true,
+        Err(payload) => {
+            payload.into_raw();
+            false
+        },
+    }
+}
+
+/// # Safety
+/// `get` must come from `transmitter_oneshot_new` and is consumed, `value` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn transmitter_oneshot_wait(get: *mut TransmitterGet, value: *mut *mut c_void) -> i32 {
+    let rx = Box::from_raw(get as *mut OneGet<Payload>);
+    match rx.wait() {
+        Some(payload) => {
+            *value = payload.into_raw();
+            TRANSMITTER_READY
+        },
+        None => {
+            *value = ptr::null_mut();
+            TRANSMITTER_DROPPED
+        },
+    }
+}
+
+/// # Safety
+/// As for `transmitter_oneshot_wait`, except that `get` stays valid on a timeout.
+#[no_mangle]
+pub unsafe extern "C" fn transmitter_oneshot_wait_timeout(get: *mut TransmitterGet, timeout_ms: u64, value: *mut *mut c_void) -> i32 {
+    let rx = get as *mut OneGet<Payload>;
+    *value = ptr::null_mut();
+    let res = match (*rx).0.wait_timeout(Duration::from_millis(timeout_ms)) {
+        Some(res) => res,
+        None => return TRANSMITTER_TIMEOUT,
+    };
+    drop(Box::from_raw(rx));
+    match res {
+        Some(payload) => {
+            *value = payload.into_raw();
+            TRANSMITTER_READY
+        },
+        None => TRANSMITTER_DROPPED,
+    }
+}
+
+/// # Safety
+/// `set` and `get` may each be NULL, otherwise they must come from `transmitter_oneshot_new`
+/// and not be used afterwards. A value still in the channel is freed with the last handle.
+#[no_mangle]
+pub unsafe extern "C" fn transmitter_oneshot_free(set: *mut TransmitterSet, get: *mut TransmitterGet) {
+    if !set.is_null() {
+        drop(Box::from_raw(set as *mut OneSet<Payload>));
+    }
+    if !get.is_null() {
+        drop(Box::from_raw(get as *mut OneGet<Payload>));
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize,Ordering};
+    use std::thread;
+
+    #[test]
+    fn test_c_setter() {
+        let (tx,rx) = oneshot::<Payload>();
+        let set = setter_into_c(tx) as usize;
+        let h = thread::spawn(move || unsafe {
+            assert!(transmitter_oneshot_set(set as *mut TransmitterSet,42usize as *mut c_void,None));
+        });
+        assert_eq!(rx.wait().unwrap().into_raw() as usize,42);
+        h.join().unwrap();
+    }
+
+    #[test]
+    fn test_c_pair() {
+        unsafe {
+            let mut set = ptr::null_mut();
+            let mut get = ptr::null_mut();
+            let mut value = ptr::null_mut();
+            transmitter_oneshot_new(&mut set,&mut get);
+            assert_eq!(transmitter_oneshot_wait_timeout(get,10,&mut value),TRANSMITTER_TIMEOUT);
+            transmitter_oneshot_free(set,ptr::null_mut());
+            assert_eq!(transmitter_oneshot_wait_timeout(get,10,&mut value),TRANSMITTER_DROPPED);
+            transmitter_oneshot_new(&mut set,&mut get);
+            assert!(transmitter_oneshot_set(set,7usize as *mut c_void,None));
+            assert_eq!(transmitter_oneshot_wait(get,&mut value),TRANSMITTER_READY);
+            assert_eq!(value as usize,7);
+            transmitter_oneshot_new(&mut set,&mut get);
+            transmitter_oneshot_free(ptr::null_mut(),get);
+            assert!(!transmitter_oneshot_set(set,ptr::null_mut(),None));
+        }
+    }
+
+    static FREED: AtomicUsize = AtomicUsize::new(0);
+    unsafe extern "C" fn free_value(value: *mut c_void) {
+        drop(Box::from_raw(value as *mut u32));
+        FREED.fetch_add(1,Ordering::Relaxed);
+    }
+
+    #[test]
+    fn test_unreceived_freed() {
+        unsafe {
+            let mut set = ptr::null_mut();
+            let mut get = ptr::null_mut();
+            transmitter_oneshot_new(&mut set,&mut get);
+            let value = Box::into_raw(Box::new(7u32)) as *mut c_void;
+            assert!(transmitter_oneshot_set(set,value,Some(free_value)));
+            assert_eq!(FREED.load(Ordering::Relaxed),0);
+            transmitter_oneshot_free(ptr::null_mut(),get);
+            assert_eq!(FREED.load(Ordering::Relaxed),1);
+            // a received value is the receiver's to free
+            transmitter_oneshot_new(&mut set,&mut get);
+            let value = Box::into_raw(Box::new(8u32)) as *mut c_void;
+            assert!(transmitter_oneshot_set(set,value,Some(free_value)));
+            let mut got = ptr::null_mut();
+            assert_eq!(transmitter_oneshot_wait(get,&mut got),TRANSMITTER_READY);
+            assert_eq!(FREED.load(Ordering::Relaxed),1);
+            free_value(got);
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use core::ffi::{c_int,c_long};
+use core::ptr;
+// the words are handed to the kernel, nothing for a model checker to swap in
+use std::sync::atomic::AtomicU32;
+use std::time::Duration;
+
+extern "C" {
+    fn syscall(num: c_long, ...) -> c_long;
+}
+
+#[cfg(target_arch = "x86_64")]
+const SYS_FUTEX: c_long = 202;
+#[cfg(target_arch = "aarch64")]
+const SYS_FUTEX: c_long = 98;
+const FUTEX_WAIT: c_int = 0;
+const FUTEX_WAKE: c_int = 1;
+// the word is only ever touched by one process, the kernel skips the shared lookup
+#[cfg(futex)]
+const FUTEX_PRIVATE_FLAG: c_int = 128;
+
+#[repr(C)]
+struct Timespec {
+    sec: i64,
+    nsec: i64,
+}
+
+fn futex(word: &AtomicU32, op: c_int, val: u32, timeout: Option<Duration>) {
+    let ts = timeout.map(|t| Timespec { sec: t.as_secs().min(i64::MAX as u64) as i64, nsec: t.subsec_nanos() as i64 });
+    let ts = ts.as_ref().map_or(ptr::null(),|ts| ts as *const Timespec);
+    unsafe { syscall(SYS_FUTEX,word.as_ptr(),op,val,ts,ptr::null::<u32>(),0u32) };
+}
+
+// sleeps while word holds expected; spurious returns, EAGAIN and EINTR included, are
+// rechecked by the callers
+#[cfg(futex)]
+pub(crate) fn wait(word: &AtomicU32, expected: u32, timeout: Option<Duration>) {
+    futex(word,FUTEX_WAIT | FUTEX_PRIVATE_FLAG,expected,timeout);
+}
+
+// a oneshot word has a single sleeper
+#[cfg(futex)]
+pub(crate) fn wake_one(word: &AtomicU32) {
+    futex(word,FUTEX_WAKE | FUTEX_PRIVATE_FLAG,1,None);
+}
+
+// the variants for a word in memory mapped by several processes
+pub(crate) fn wait_shared(word: &AtomicU32, expected: u32, timeout: Option<Duration>) {
+    futex(word,FUTEX_WAIT,expected,timeout);
+}
+
+pub(crate) fn wake_shared(word: &AtomicU32) {
+    futex(word,FUTEX_WAKE,i32::MAX as u32,None);
+}
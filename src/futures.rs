@@ -0,0 +1,160 @@
+use core::pin::Pin;
+use core::task::{Context,Poll};
+
+use futures_channel::oneshot;
+use futures_core::future::FusedFuture;
+use futures_core::stream::Stream;
+use futures_sink::Sink;
+
+use crate::error::TrySendError;
+use crate::relay::Relay;
+use crate::{mpmc,mpsc,OneGet,OneSet};
+
+impl<T> FusedFuture for OneGet<T> {
+    fn is_terminated(&self) -> bool {
+        OneGet::is_terminated(self)
+    }
+}
+
+impl<T> Stream for mpsc::Receiver<T> {
+    type Item = T;
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        self.poll_recv(cx)
+    }
+}
+impl<T> Stream for mpmc::Receiver<T> {
+    type Item = T;
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        self.poll_recv(cx)
+    }
+}
+
+// unbounded, always ready; a dropped receiver fails the send with the value
+impl<T> Sink<T> for mpsc::Sender<T> {
+    type Error = TrySendError<T>;
+    fn poll_ready(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(),TrySendError<T>>> {
+        Poll::Ready(Ok(()))
+    }
+    fn start_send(self: Pin<&mut Self>, t: T) -> Result<(),TrySendError<T>> {
+        self.send(t).map_err(TrySendError::Disconnected)
+    }
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(),TrySendError<T>>> {
+        Poll::Ready(Ok(()))
+    }
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(),TrySendError<T>>> {
+        Poll::Ready(Ok(()))
+    }
+}
+// waits for room in poll_ready, a disconnect shows up in start_send
+impl<T> Sink<T> for mpmc::Sender<T> {
+    type Error = TrySendError<T>;
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(),TrySendError<T>>> {
+        mpmc::Sender::poll_ready(&self,cx).map(Ok)
+    }
+    fn start_send(self: Pin<&mut Self>, t: T) -> Result<(),TrySendError<T>> {
+        self.force_send(t).map_err(TrySendError::Disconnected)
+    }
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(),TrySendError<T>>> {
+        Poll::Ready(Ok(()))
+    }
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(),TrySendError<T>>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+// a dropped OneSet is the futures receiver's Canceled
+impl<T: Send + 'static> From<OneGet<T>> for oneshot::Receiver<T> {
+    fn from(rx: OneGet<T>) -> oneshot::Receiver<T> {
+        let (tx,frx) = oneshot::channel();
+        rx.on_ready(move |res| if let Some(t) = res {
+            let _ = tx.send(t);
+        });
+        frx
+    }
+}
+impl<T: Send + 'static> From<oneshot::Receiver<T>> for OneGet<T> {
+    fn from(rx: oneshot::Receiver<T>) -> OneGet<T> {
+        let (tx,get) = crate::oneshot();
+        Relay::start(rx,tx);
+        get
+    }
+}
+impl<T: Send + 'static> From<oneshot::Sender<T>> for OneSet<T> {
+    fn from(tx: oneshot::Sender<T>) -> OneSet<T> {
+        let (set,rx) = crate::oneshot();
+        rx.on_ready(move |res| if let Some(t) = res {
+            let _ = tx.send(t);
+        });
+        set
+    }
+}
+impl<T: Send + 'static> From<OneSet<T>> for oneshot::Sender<T> {
+    fn from(set: OneSet<T>) -> oneshot::Sender<T> {
+        let (tx,rx) = oneshot::channel();
+        Relay::start(rx,set);
+        tx
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::error::TrySendError;
+    use crate::{mpmc,mpsc,oneshot,OneGet,OneSet};
+    use core::future::Future;
+    use core::pin::Pin;
+    use futures_channel::oneshot as futures_oneshot;
+    use futures_core::future::FusedFuture;
+    use futures_core::stream::Stream;
+    use futures_sink::Sink;
+    use std::task::{Context,Poll};
+    use std::thread;
+
+    #[test]
+    fn test_fused() {
+        let waker = crate::select::thread_waker();
+        let mut cx = Context::from_waker(&waker);
+        let (tx,mut rx) = oneshot();
+        assert!(!FusedFuture::is_terminated(&rx));
+        tx.set(1).unwrap();
+        assert_eq!(Pin::new(&mut rx).poll(&mut cx),Poll::Ready(Some(1)));
+        assert!(FusedFuture::is_terminated(&rx));
+    }
+
+    #[test]
+    fn test_stream_and_sink() {
+        let waker = crate::select::thread_waker();
+        let mut cx = Context::from_waker(&waker);
+        let (mut tx,mut rx) = mpmc::bounded(1);
+        assert_eq!(Pin::new(&mut tx).poll_ready(&mut cx),Poll::Ready(Ok(())));
+        Pin::new(&mut tx).start_send(1).unwrap();
+        assert_eq!(Pin::new(&mut tx).poll_ready(&mut cx),Poll::Pending);
+        assert_eq!(Pin::new(&mut rx).poll_next(&mut cx),Poll::Ready(Some(1)));
+        drop(tx);
+        assert_eq!(Pin::new(&mut rx).poll_next(&mut cx),Poll::Ready(None));
+        let (mut tx,mut rx) = mpsc::channel();
+        Pin::new(&mut tx).start_send(2).unwrap();
+        assert_eq!(Pin::new(&mut rx).poll_next(&mut cx),Poll::Ready(Some(2)));
+        drop(rx);
+        assert_eq!(Pin::new(&mut tx).start_send(3),Err(TrySendError::Disconnected(3)));
+    }
+
+    #[test]
+    fn test_conversions() {
+        let (tx,rx) = futures_oneshot::channel();
+        let rx = OneGet::from(rx);
+        let h = thread::spawn(move || tx.send(1).unwrap());
+        assert_eq!(rx.wait(),Some(1));
+        h.join().unwrap();
+        let (tx,rx) = futures_oneshot::channel::<u8>();
+        drop(tx);
+        assert_eq!(OneGet::from(rx).wait(),None);
+        let (tx,mut rx) = futures_oneshot::channel();
+        OneSet::from(tx).set(2).unwrap();
+        assert_eq!(rx.try_recv(),Ok(Some(2)));
+        let (tx,rx) = oneshot();
+        let mut rx = futures_oneshot::Receiver::from(rx);
+        tx.set(3).unwrap();
+        assert_eq!(rx.try_recv(),Ok(Some(3)));
+    }
+}
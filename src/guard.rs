@@ -0,0 +1,85 @@
+use core::fmt::{self,Debug};
+
+use crate::OneSet;
+
+// sets the fallback's value on drop unless completed or disarmed first
+pub struct CompletionGuard<T, F: FnOnce() -> T> {
+    tx: Option<OneSet<T>>,
+    fallback: Option<F>,
+}
+impl<T, F: FnOnce() -> T> CompletionGuard<T,F> {
+    pub fn new(tx: OneSet<T>, fallback: F) -> CompletionGuard<T,F> {
+        CompletionGuard { tx: Some(tx), fallback: Some(fallback) }
+    }
+    pub fn is_needed(&self) -> bool {
+        self.tx.as_ref().is_some_and(|tx| tx.is_needed())
+    }
+    pub fn complete(mut self, t: T) -> Result<(),T> {
+        self.fallback = None;
+        self.tx.take().unwrap().set(t)
+    }
+    // the setter comes back as is, dropping it closes the channel
+    pub fn disarm(mut self) -> OneSet<T> {
+        self.fallback = None;
+        self.tx.take().unwrap()
+    }
+}
+impl<T, F: FnOnce() -> T> Debug for CompletionGuard<T,F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "CompletionGuard")
+    }
+}
+impl<T, F: FnOnce() -> T> Drop for CompletionGuard<T,F> {
+    fn drop(&mut self) {
+        if let (Some(tx),Some(fallback)) = (self.tx.take(),self.fallback.take()) {
+            if tx.is_needed() {
+                let _ = tx.set(fallback());
+            }
+        }
+    }
+}
+
+impl<T> OneSet<T> {
+    pub fn guard<F: FnOnce() -> T>(self, fallback: F) -> CompletionGuard<T,F> {
+        CompletionGuard::new(self,fallback)
+    }
+    pub fn guard_value(self, t: T) -> CompletionGuard<T,impl FnOnce() -> T> {
+        CompletionGuard::new(self,move || t)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::oneshot;
+    use std::thread;
+
+    #[test]
+    fn test_guard_fires() {
+        let (tx,rx) = oneshot();
+        let res = (|| -> Result<u8,()> {
+            let guard = tx.guard_value(0);
+            Err(())?;
+            guard.complete(1).unwrap();
+            Ok(1)
+        })();
+        assert_eq!(res,Err(()));
+        assert_eq!(rx.wait(),Some(0));
+        let (tx,rx) = oneshot();
+        thread::spawn(move || {
+            let _guard = tx.guard(|| "panicked");
+            panic!("worker failed");
+        }).join().unwrap_err();
+        assert_eq!(rx.wait(),Some("panicked"));
+    }
+
+    #[test]
+    fn test_guard_complete_disarm() {
+        let (tx,rx) = oneshot();
+        tx.guard_value(0).complete(1).unwrap();
+        assert_eq!(rx.wait(),Some(1));
+        let (tx,rx) = oneshot::<u8>();
+        drop(tx.guard(|| unreachable!()).disarm());
+        assert_eq!(rx.wait(),None);
+    }
+}
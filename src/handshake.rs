@@ -0,0 +1,75 @@
+use core::fmt::{self,Debug};
+#[cfg(feature = "std")]
+use std::time::Duration;
+
+#[cfg(not(poll_only))]
+use crate::RecvError;
+#[cfg(feature = "std")]
+use crate::RecvTimeoutError;
+use crate::{oneshot,OneGet,OneSet};
+
+// sends S to the other side and receives its R
+pub struct Handshake<S,R> {
+    tx: OneSet<S>,
+    rx: OneGet<R>,
+}
+impl<S,R> Handshake<S,R> {
+    pub fn is_needed(&self) -> bool {
+        self.tx.is_needed()
+    }
+    // the value goes out first, so both sides can call this without a fixed order
+    #[cfg(not(poll_only))]
+    pub fn exchange(self, s: S) -> Result<R,RecvError> {
+        let _ = self.tx.set(s);
+        self.rx.recv()
+    }
+    #[cfg(feature = "std")]
+    pub fn exchange_timeout(self, s: S, timeout: Duration) -> Result<R,RecvTimeoutError<R>> {
+        let _ = self.tx.set(s);
+        self.rx.recv_timeout(timeout)
+    }
+    pub fn split(self) -> (OneSet<S>,OneGet<R>) {
+        (self.tx,self.rx)
+    }
+}
+impl<S,R> Debug for Handshake<S,R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Handshake")
+    }
+}
+
+pub fn handshake<A,B>() -> (Handshake<A,B>,Handshake<B,A>) {
+    let (atx,arx) = oneshot();
+    let (btx,brx) = oneshot();
+    (Handshake { tx: atx, rx: brx },Handshake { tx: btx, rx: arx })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::handshake;
+    use crate::RecvError;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_exchange() {
+        let (a,b) = handshake::<u8,&str>();
+        let h = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            b.exchange("ready")
+        });
+        assert_eq!(a.exchange(1),Ok("ready"));
+        assert_eq!(h.join().unwrap(),Ok(1));
+    }
+
+    #[test]
+    fn test_peer_dropped() {
+        let (a,b) = handshake::<u8,u8>();
+        drop(b);
+        assert!(!a.is_needed());
+        assert_eq!(a.exchange(1),Err(RecvError::Disconnected));
+        let (a,_b) = handshake::<u8,u8>();
+        assert!(a.exchange_timeout(1,Duration::from_millis(10)).is_err());
+    }
+}
@@ -0,0 +1,147 @@
+use core::fmt::{self,Debug};
+
+#[cfg(feature = "std")]
+use std::thread::{self,Thread};
+#[cfg(feature = "std")]
+use std::time::Duration;
+
+#[cfg(feature = "std")]
+use crate::coalesce::{Debounce,Throttle};
+use crate::error::{RecvError,TryRecvError};
+#[cfg(feature = "std")]
+use crate::sync::Arc;
+
+// a thread waiting on several receivers; they keep it weakly, so dropping it
+// unregisters the wait
+#[cfg(feature = "std")]
+#[doc(hidden)]
+pub struct Watcher(Thread);
+#[cfg(feature = "std")]
+impl Watcher {
+    pub fn current() -> Arc<Watcher> {
+        Arc::new(Watcher(thread::current()))
+    }
+    pub(crate) fn unpark(&self) {
+        self.0.unpark();
+    }
+}
+#[cfg(feature = "std")]
+impl Debug for Watcher {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Watcher")
+    }
+}
+
+// multi-value receivers whose recv blocks until the next value or disconnect
+pub trait Receive {
+    type Item;
+    fn recv(&self) -> Result<Self::Item,RecvError>;
+    fn try_recv(&self) -> Result<Self::Item,TryRecvError>;
+    // asks to unpark the watcher on every value and disconnect while it is alive,
+    // select! polls receivers that can't
+    #[cfg(feature = "std")]
+    #[doc(hidden)]
+    fn watch(&self, _watcher: &Arc<Watcher>) -> bool {
+        false
+    }
+    #[cfg(feature = "std")]
+    fn debounce(self, quiet: Duration) -> Debounce<Self>
+    where Self: Sized
+    {
+        Debounce::new(self,quiet)
+    }
+    #[cfg(feature = "std")]
+    fn throttle(self, period: Duration) -> Throttle<Self>
+    where Self: Sized
+    {
+        Throttle::new(self,period)
+    }
+}
+
+// what every queue receiver has on top of its inherent recv and try_recv: iter,
+// try_iter, Receive, both IntoIterator impls and Debug; extra Receive items go after
+// the type. Their Drop impls take undelivered values out under the lock and drop
+// them after it, those may be senders whose drop wakes this very channel
+macro_rules! impl_receiver {
+    ($name:ident<$t:ident $(: $bound:path)?> $(, $extra:item)*) => {
+        impl<$t $(: $bound)?> $name<$t> {
+            pub fn iter(&self) -> $crate::iter::Iter<'_,$name<$t>> {
+                $crate::iter::Iter(self)
+            }
+            pub fn try_iter(&self) -> $crate::iter::TryIter<'_,$name<$t>> {
+                $crate::iter::TryIter(self)
+            }
+        }
+        impl<$t $(: $bound)?> $crate::iter::Receive for $name<$t> {
+            type Item = $t;
+            fn recv(&self) -> Result<$t,$crate::error::RecvError> {
+                $name::recv(self)
+            }
+            fn try_recv(&self) -> Result<$t,$crate::error::TryRecvError> {
+                $name::try_recv(self)
+            }
+            $($extra)*
+        }
+        impl<$t $(: $bound)?> IntoIterator for $name<$t> {
+            type Item = $t;
+            type IntoIter = $crate::iter::IntoIter<$name<$t>>;
+            fn into_iter(self) -> $crate::iter::IntoIter<$name<$t>> {
+                $crate::iter::IntoIter(self)
+            }
+        }
+        impl<'r,$t $(: $bound)?> IntoIterator for &'r $name<$t> {
+            type Item = $t;
+            type IntoIter = $crate::iter::Iter<'r,$name<$t>>;
+            fn into_iter(self) -> $crate::iter::Iter<'r,$name<$t>> {
+                self.iter()
+            }
+        }
+        impl<$t> core::fmt::Debug for $name<$t> {
+            fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+                write!(f, stringify!($name))
+            }
+        }
+    };
+}
+pub(crate) use impl_receiver;
+
+// blocks per value, ends once every sender is gone
+pub struct Iter<'r,R>(pub(crate) &'r R);
+impl<'r,R: Receive> Iterator for Iter<'r,R> {
+    type Item = R::Item;
+    fn next(&mut self) -> Option<R::Item> {
+        self.0.recv().ok()
+    }
+}
+impl<'r,R> Debug for Iter<'r,R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Iter")
+    }
+}
+
+// drains what is ready without blocking
+pub struct TryIter<'r,R>(pub(crate) &'r R);
+impl<'r,R: Receive> Iterator for TryIter<'r,R> {
+    type Item = R::Item;
+    fn next(&mut self) -> Option<R::Item> {
+        self.0.try_recv().ok()
+    }
+}
+impl<'r,R> Debug for TryIter<'r,R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "TryIter")
+    }
+}
+
+pub struct IntoIter<R>(pub(crate) R);
+impl<R: Receive> Iterator for IntoIter<R> {
+    type Item = R::Item;
+    fn next(&mut self) -> Option<R::Item> {
+        self.0.recv().ok()
+    }
+}
+impl<R> Debug for IntoIter<R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "IntoIter")
+    }
+}
@@ -0,0 +1,96 @@
+use std::task::{Poll,Waker};
+use std::thread;
+
+use crate::select::thread_waker;
+use crate::OneGet;
+
+fn poll_into<T>(rx: &OneGet<T>, res: &mut Option<Option<T>>, waker: &Waker) -> bool {
+    if res.is_none() {
+        if let Poll::Ready(v) = rx.0.poll(waker) {
+            *res = Some(v);
+        }
+    }
+    res.is_some()
+}
+
+pub fn join_all<T>(receivers: Vec<OneGet<T>>) -> Vec<Option<T>> {
+    let waker = thread_waker();
+    let mut results = receivers.iter().map(|_| None).collect::<Vec<_>>();
+    loop {
+        let mut done = true;
+        for (rx,res) in receivers.iter().zip(results.iter_mut()) {
+            done &= poll_into(rx,res,&waker);
+        }
+        if done {
+            return results.into_iter().map(Option::unwrap).collect();
+        }
+        thread::park();
+    }
+}
+
+pub fn join2<A,B>(a: OneGet<A>, b: OneGet<B>) -> (Option<A>,Option<B>) {
+    let waker = thread_waker();
+    let (mut ra,mut rb) = (None,None);
+    while !(poll_into(&a,&mut ra,&waker) & poll_into(&b,&mut rb,&waker)) {
+        thread::park();
+    }
+    (ra.unwrap(),rb.unwrap())
+}
+
+pub fn join3<A,B,C>(a: OneGet<A>, b: OneGet<B>, c: OneGet<C>) -> (Option<A>,Option<B>,Option<C>) {
+    let waker = thread_waker();
+    let (mut ra,mut rb,mut rc) = (None,None,None);
+    while !(poll_into(&a,&mut ra,&waker) & poll_into(&b,&mut rb,&waker) & poll_into(&c,&mut rc,&waker)) {
+        thread::park();
+    }
+    (ra.unwrap(),rb.unwrap(),rc.unwrap())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{join2,join3,join_all};
+    use crate::oneshot;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_join_all() {
+        let (txs,rxs): (Vec<_>,Vec<_>) = (0 .. 8).map(|_| oneshot::<u64>()).unzip();
+        let hs = txs.into_iter().enumerate().map(|(i,tx)| {
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(((i * 37) % 100) as u64));
+                match i {
+                    5 => drop(tx),
+                    _ => tx.set(i as u64).unwrap(),
+                }
+            })
+        }).collect::<Vec<_>>();
+        let res = join_all(rxs);
+        assert_eq!(res,vec![Some(0),Some(1),Some(2),Some(3),Some(4),None,Some(6),Some(7)]);
+        for h in hs {
+            h.join().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_join2_join3() {
+        let (ta,ra) = oneshot();
+        let (tb,rb) = oneshot();
+        let h = thread::spawn(move || {
+            tb.set("b").unwrap();
+            thread::sleep(Duration::from_millis(50));
+            ta.set(1).unwrap();
+        });
+        assert_eq!(join2(ra,rb),(Some(1),Some("b")));
+        h.join().unwrap();
+
+        let (ta,ra) = oneshot();
+        let (tb,rb) = oneshot::<u8>();
+        let (tc,rc) = oneshot();
+        ta.set(1).unwrap();
+        drop(tb);
+        tc.set('c').unwrap();
+        assert_eq!(join3(ra,rb,rc),(Some(1),None,Some('c')));
+    }
+}
@@ -0,0 +1,91 @@
+use core::fmt::{self,Debug};
+#[cfg(feature = "std")]
+use std::time::{Duration,Instant};
+
+use crate::sync::{Condvar,Mutex};
+
+pub struct Latch {
+    count: Mutex<usize>,
+    cond: Condvar,
+}
+impl Latch {
+    pub const fn new(count: usize) -> Latch {
+        Latch {
+            count: Mutex::new(count),
+            cond: Condvar::new(),
+        }
+    }
+    // extra count downs after the release are ignored
+    pub fn count_down(&self) {
+        let mut count = self.count.lock();
+        if *count > 0 {
+            *count -= 1;
+            if *count == 0 {
+                self.cond.notify_all();
+            }
+        }
+    }
+    pub fn count(&self) -> usize {
+        *self.count.lock()
+    }
+    pub fn is_released(&self) -> bool {
+        self.count() == 0
+    }
+    pub fn wait(&self) {
+        let mut count = self.count.lock();
+        while *count > 0 {
+            count = self.cond.wait(count);
+        }
+    }
+    #[cfg(feature = "std")]
+    pub fn wait_timeout(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now().checked_add(timeout);
+        let mut count = self.count.lock();
+        while *count > 0 {
+            let (guard,timed_out) = self.cond.wait_deadline(count,deadline);
+            if timed_out {
+                return false;
+            }
+            count = guard;
+        }
+        true
+    }
+}
+impl Debug for Latch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Latch({})", self.count())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::Latch;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_release() {
+        let latch = Latch::new(3);
+        thread::scope(|s| {
+            let waiters = (0 .. 4).map(|_| s.spawn(|| latch.wait())).collect::<Vec<_>>();
+            for _ in 0 .. 3 {
+                assert!(!latch.is_released());
+                latch.count_down();
+            }
+            for h in waiters {
+                h.join().unwrap();
+            }
+        });
+        latch.count_down();
+        assert_eq!(latch.count(),0);
+    }
+
+    #[test]
+    fn test_wait_timeout() {
+        let latch = Latch::new(1);
+        assert!(!latch.wait_timeout(Duration::from_millis(50)));
+        latch.count_down();
+        assert!(latch.wait_timeout(Duration::from_millis(50)));
+    }
+}
@@ -0,0 +1,132 @@
+use core::fmt::{self,Debug};
+
+use crate::error::{RecvError,TryRecvError};
+use crate::iter::impl_receiver;
+use crate::sync::{Arc,Condvar,Mutex};
+
+struct Cell<T> {
+    value: Option<T>,
+    senders: usize,
+    receiver: bool,
+}
+
+struct Inner<T> {
+    cell: Mutex<Cell<T>>,
+    cond: Condvar,
+}
+
+pub struct Sender<T>(Arc<Inner<T>>);
+impl<T> Sender<T> {
+    // never blocks, an unread value is replaced and handed back
+    pub fn send(&self, t: T) -> Result<Option<T>,T> {
+        let mut lock = self.0.cell.lock();
+        if !lock.receiver {
+            return Err(t);
+        }
+        let old = lock.value.replace(t);
+        self.0.cond.notify_one();
+        Ok(old)
+    }
+    pub fn is_needed(&self) -> bool {
+        self.0.cell.lock().receiver
+    }
+}
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Sender<T> {
+        self.0.cell.lock().senders += 1;
+        Sender(self.0.clone())
+    }
+}
+impl<T> Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Sender")
+    }
+}
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut lock = self.0.cell.lock();
+        lock.senders -= 1;
+        if lock.senders == 0 {
+            self.0.cond.notify_one();
+        }
+    }
+}
+
+pub struct Receiver<T>(Arc<Inner<T>>);
+impl<T> Receiver<T> {
+    pub fn recv(&self) -> Result<T,RecvError> {
+        let mut lock = self.0.cell.lock();
+        loop {
+            if let Some(t) = lock.value.take() {
+                return Ok(t);
+            }
+            if lock.senders == 0 {
+                return Err(RecvError::Disconnected);
+            }
+            lock = self.0.cond.wait(lock);
+        }
+    }
+    pub fn try_recv(&self) -> Result<T,TryRecvError> {
+        let mut lock = self.0.cell.lock();
+        match lock.value.take() {
+            Some(t) => Ok(t),
+            None if lock.senders == 0 => Err(TryRecvError::Disconnected),
+            None => Err(TryRecvError::Empty),
+        }
+    }
+}
+impl_receiver!(Receiver<T>);
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        let value = {
+            let mut lock = self.0.cell.lock();
+            lock.receiver = false;
+            lock.value.take()
+        };
+        drop(value);
+    }
+}
+
+pub fn channel<T>() -> (Sender<T>,Receiver<T>) {
+    let r = Arc::new(Inner {
+        cell: Mutex::new(Cell {
+            value: None,
+            senders: 1,
+            receiver: true,
+        }),
+        cond: Condvar::new(),
+    });
+    (Sender(r.clone()),Receiver(r))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::channel;
+    use crate::error::{RecvError,TryRecvError};
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_overwrite() {
+        let (tx,rx) = channel();
+        assert_eq!(tx.send(1),Ok(None));
+        assert_eq!(tx.send(2),Ok(Some(1)));
+        assert_eq!(rx.recv(),Ok(2));
+        assert_eq!(rx.try_recv(),Err(TryRecvError::Empty));
+        drop(rx);
+        assert_eq!(tx.send(3),Err(3));
+    }
+
+    #[test]
+    fn test_blocking_recv() {
+        let (tx,rx) = channel();
+        let h = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            tx.send(7).unwrap();
+        });
+        assert_eq!(rx.recv(),Ok(7));
+        h.join().unwrap();
+        assert_eq!(rx.recv(),Err(RecvError::Disconnected));
+    }
+}
@@ -1,95 +1,969 @@
-use std::fmt::{self,Debug};
-use std::sync::{Arc,Condvar,Mutex};
+#![cfg_attr(not(feature = "std"), no_std)]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "alloc")]
+use alloc::boxed::Box;
+use core::cell::UnsafeCell;
+#[cfg(feature = "alloc")]
+use core::fmt::{self,Debug};
+#[cfg(feature = "alloc")]
+use core::mem::ManuallyDrop;
+use core::mem::MaybeUninit;
+#[cfg(feature = "alloc")]
+use core::task::Poll;
+#[cfg(any(feature = "std", all(feature = "alloc", feature = "async")))]
+use core::task::Waker;
+#[cfg(feature = "std")]
+use std::thread::{self,Thread};
+// the kernel sleeps on it, nothing for a model checker to swap in
+#[cfg(futex)]
+use std::sync::atomic::AtomicU32;
+#[cfg(feature = "std")]
+use std::time::{Duration,Instant};
+
+mod error;
+mod sync;
+#[cfg(feature = "std")]
+mod actor;
+#[cfg(feature = "allocator_api2")]
+mod allocator;
+#[cfg(feature = "alloc")]
+mod barrier;
+#[cfg(feature = "alloc")]
+pub mod broadcast;
+#[cfg(feature = "std")]
+mod cancel;
+#[cfg(feature = "std")]
+mod coalesce;
+#[cfg(feature = "std")]
+mod correlator;
+#[cfg(feature = "std")]
+mod diag;
+#[cfg(feature = "alloc")]
+mod erased;
+#[cfg(feature = "alloc")]
+mod event;
+#[cfg(feature = "std")]
+mod exchanger;
+#[cfg(feature = "std")]
+pub mod ffi;
+#[cfg(all(feature = "std", target_os = "linux", any(target_arch = "x86_64", target_arch = "aarch64")))]
+mod futex;
+#[cfg(feature = "futures")]
+mod futures;
+#[cfg(feature = "alloc")]
+mod guard;
+#[cfg(feature = "alloc")]
+mod handshake;
+#[cfg(feature = "alloc")]
+mod iter;
+#[cfg(feature = "std")]
+mod join;
+#[cfg(feature = "alloc")]
+mod latch;
+#[cfg(feature = "alloc")]
+pub mod latest;
+#[cfg(feature = "alloc")]
+pub mod local;
+#[cfg(feature = "std")]
+mod merge;
+#[cfg(feature = "std")]
+mod metrics;
+#[cfg(feature = "alloc")]
+pub mod mpmc;
+#[cfg(feature = "alloc")]
+pub mod mpsc;
+#[cfg(feature = "alloc")]
+mod multi;
+#[cfg(feature = "std")]
+pub mod net;
+#[cfg(feature = "std")]
+pub mod pipeline;
+#[cfg(feature = "alloc")]
+mod pool;
+#[cfg(feature = "alloc")]
+pub mod priority;
+#[cfg(feature = "alloc")]
+mod promise;
+#[cfg(feature = "std")]
+mod rate;
+#[cfg(feature = "std")]
+mod registry;
+#[cfg(any(feature = "tokio", feature = "futures"))]
+mod relay;
+#[cfg(feature = "alloc")]
+pub mod rendezvous;
+#[cfg(feature = "alloc")]
+mod request;
+#[cfg(feature = "alloc")]
+mod result;
+#[cfg(feature = "std")]
+mod scatter;
+mod scoped;
+#[cfg(feature = "alloc")]
+mod semaphore;
+#[cfg(feature = "std")]
+mod select;
+#[cfg(feature = "alloc")]
+pub mod session;
+#[cfg(feature = "alloc")]
+mod shared;
+#[cfg(all(feature = "std", target_os = "linux", any(target_arch = "x86_64", target_arch = "aarch64")))]
+pub mod shm;
+#[cfg(feature = "alloc")]
+mod signal;
+#[cfg(feature = "std")]
+mod spawn;
+#[cfg(feature = "std")]
+mod strategy;
+#[cfg(feature = "alloc")]
+pub mod spsc;
+#[cfg(feature = "std")]
+mod timer;
+#[cfg(feature = "tokio")]
+mod tokio;
+#[cfg(feature = "alloc")]
+mod transmit;
+#[cfg(feature = "std")]
+pub mod transport;
+#[cfg(all(feature = "std", unix))]
+pub mod uds;
+#[cfg(feature = "alloc")]
+mod waitgroup;
+#[cfg(feature = "alloc")]
+mod wake;
+#[cfg(feature = "alloc")]
+pub mod watch;
+#[cfg(feature = "alloc")]
+mod wire;
+#[cfg(feature = "alloc")]
+mod zip;
+
+#[cfg(feature = "std")]
+pub use actor::{spawn_actor,Address};
+#[cfg(feature = "allocator_api2")]
+pub use allocator::{oneshot_alloc,AllocGet,AllocSet};
+#[cfg(feature = "alloc")]
+pub use barrier::Barrier;
+#[cfg(feature = "std")]
+pub use cancel::CancellationToken;
+#[cfg(feature = "std")]
+pub use coalesce::{Debounce,Throttle};
+#[cfg(feature = "std")]
+pub use correlator::Correlator;
+#[cfg(feature = "std")]
+pub use diag::{set_long_wait_hook,LongWait};
+#[cfg(feature = "alloc")]
+pub use erased::{dyn_oneshot,DynOneGet,DynOneSet};
+pub use error::{Elapsed,RecvError,TryRecvError,TrySendError};
+#[cfg(feature = "alloc")]
+pub use error::{DowncastError,RecvTimeoutError};
+#[cfg(feature = "alloc")]
+pub use event::Event;
+#[cfg(feature = "std")]
+pub use exchanger::Exchanger;
+#[cfg(feature = "alloc")]
+pub use guard::CompletionGuard;
+#[cfg(feature = "alloc")]
+pub use handshake::{handshake,Handshake};
+#[cfg(feature = "alloc")]
+pub use iter::{IntoIter,Iter,Receive,TryIter};
+#[cfg(feature = "std")]
+pub use join::{join2,join3,join_all};
+#[cfg(feature = "alloc")]
+pub use latch::Latch;
+#[cfg(feature = "std")]
+pub use merge::{merge,merge_by_key};
+#[cfg(feature = "std")]
+pub use metrics::{set_metrics,Metrics};
+#[cfg(feature = "alloc")]
+pub use multi::{fan_out,multi_oneshot,MultiSet};
+#[cfg(feature = "std")]
+pub use multi::oneshot_with_ttl;
+#[cfg(feature = "alloc")]
+pub use pool::{OneshotPool,PooledGet,PooledSet};
+#[cfg(feature = "alloc")]
+pub use promise::{promise,Promise,PromiseHandle};
+#[cfg(feature = "std")]
+pub use rate::RateLimited;
+#[cfg(feature = "std")]
+pub use registry::{live_channels,oneshot_named,ChannelInfo};
+#[cfg(feature = "alloc")]
+pub use request::{request,Caller,Responder};
+#[cfg(feature = "alloc")]
+pub use result::{oneshot_result,ResultGet,ResultSet};
+#[cfg(feature = "std")]
+pub use scatter::{gather,scatter};
+pub use scoped::{oneshot_in,ScopedGet,ScopedSet,Slot,StaticOneshot};
+#[cfg(feature = "alloc")]
+pub use semaphore::{Permit,Semaphore};
+#[cfg(feature = "alloc")]
+pub use shared::{shared_oneshot,SharedGet,SharedSet};
+#[cfg(feature = "alloc")]
+pub use signal::{signal,SignalGet,SignalSet};
+#[cfg(feature = "std")]
+pub use spawn::{spawn_transmit,Worker,WorkerStop};
+#[cfg(feature = "std")]
+pub use strategy::{set_wait_strategy,Block,Spin,SpinThenPark,SpinThenYield,WaitStrategy};
+#[cfg(feature = "std")]
+pub use timer::{after,tick,tick_with,MissedTicks,Ticker};
+#[cfg(feature = "alloc")]
+pub use transmit::Transmit;
+#[cfg(feature = "alloc")]
+pub use waitgroup::{WaitGroup,WaitGuard};
+#[cfg(feature = "alloc")]
+pub use wake::WakeHandle;
+#[cfg(feature = "alloc")]
+pub use wire::Wire;
+#[cfg(feature = "serde")]
+pub use wire::Bincode;
+#[cfg(feature = "std")]
+pub use select::{race,select,select2,Either,Select2};
+#[cfg(feature = "std")]
+#[doc(hidden)]
+pub use select::macro_support as __select;
+
+#[cfg(feature = "alloc")]
+use crate::sync::Arc;
+use crate::sync::{const_fn,AtomicU8,Ordering};
+
+#[cfg(all(feature = "alloc", feature = "async"))]
+use core::{future::Future,pin::Pin,task::Context};
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum State {
+    Pending,
+    Ready,
+    SenderDropped,
+    ReceiverDropped,
+}
+
+// state bits; the value may only be read after SET was observed
+const WAITING: u8 = 1;
+const SET: u8 = 2;
+// no value will come: the sender was dropped or the value is already taken
+const CLOSED: u8 = 4;
+const RECEIVER_GONE: u8 = 8;
+// comes with CLOSED when a deadline completed the channel instead of the sender
+#[cfg(not(poll_only))]
+const EXPIRED: u8 = 16;
+// comes with CLOSED when the sender was dropped during a panic
+#[cfg(not(poll_only))]
+const PANICKED: u8 = 32;
+// the receiver has seen the outcome, value or not
+const TAKEN: u8 = 64;
+#[cfg(any(feature = "alloc", not(poll_only)))]
+const DONE: u8 = SET | CLOSED;
+
+// handoff bits, for a sender waiting until the receiver waits
+#[cfg(feature = "std")]
+const RX_WAITING: u8 = 1;
+#[cfg(feature = "std")]
+const TX_PARKED: u8 = 2;
+
+enum Waiter {
+    #[cfg(all(feature = "std", not(futex)))]
+    Thread(Thread),
+    #[cfg(any(feature = "std", all(feature = "alloc", feature = "async")))]
+    Waker(Waker),
+    // runs on the completing thread
+    #[cfg(feature = "alloc")]
+    Callback(Box<dyn FnOnce() + Send>),
+    // a receiver sleeping on the channel's events word, woken by complete
+    #[cfg(futex)]
+    Futex,
+}
+impl Waiter {
+    fn wake(self) {
+        match self {
+            #[cfg(all(feature = "std", not(futex)))]
+            Waiter::Thread(thread) => thread.unpark(),
+            #[cfg(any(feature = "std", all(feature = "alloc", feature = "async")))]
+            Waiter::Waker(waker) => waker.wake(),
+            #[cfg(feature = "alloc")]
+            Waiter::Callback(f) => f(),
+            #[cfg(futex)]
+            Waiter::Futex => {},
+        }
+    }
+}
+
+// a trace level event tagged with the channel's address, nothing without the tracing feature
+macro_rules! trace_event {
+    ($inner:expr, $($arg:tt)+) => {
+        #[cfg(feature = "tracing")]
+        tracing::trace!(channel = $inner as *const _ as usize, $($arg)+);
+    };
+}
+
+// left unpadded: each side writes the state about once, padding would only grow
+// every short-lived channel
 struct InnerOne<T> {
-    payload: Mutex<Option<T>>,
-    cond: Condvar,
+    state: AtomicU8,
+    // initialized exactly while SET is in the state
+    value: UnsafeCell<MaybeUninit<T>>,
+    // belongs to the receiver until the sender completes a WAITING state
+    waiter: UnsafeCell<Option<Waiter>>,
+    #[cfg(feature = "std")]
+    handoff: AtomicU8,
+    // belongs to the receiver once TX_PARKED is set without RX_WAITING
+    #[cfg(feature = "std")]
+    sender: UnsafeCell<Option<Thread>>,
+    // bumped on completing a Futex waiter, the word it sleeps on
+    #[cfg(futex)]
+    events: AtomicU32,
 }
+unsafe impl<T: Send> Send for InnerOne<T> {}
+unsafe impl<T: Send> Sync for InnerOne<T> {}
 impl<T> InnerOne<T> {
-    fn new() -> InnerOne<T> {
+    const_fn! {
+    const fn new() -> InnerOne<T> {
         InnerOne {
-            payload: Mutex::new(None),
-            cond: Condvar::new(),
+            state: AtomicU8::new(0),
+            value: UnsafeCell::new(MaybeUninit::uninit()),
+            waiter: UnsafeCell::new(None),
+            #[cfg(feature = "std")]
+            handoff: AtomicU8::new(0),
+            #[cfg(feature = "std")]
+            sender: UnsafeCell::new(None),
+            #[cfg(futex)]
+            events: AtomicU32::new(0),
+        }
+    }
+    }
+    fn complete(&self, bit: u8) -> u8 {
+        let prev = self.state.fetch_or(bit,Ordering::AcqRel);
+        if prev & WAITING != 0 {
+            if let Some(waiter) = unsafe { (*self.waiter.get()).take() } {
+                #[cfg(futex)]
+                if let Waiter::Futex = waiter {
+                    self.events.fetch_add(1,Ordering::SeqCst);
+                    futex::wake_one(&self.events);
+                    return prev;
+                }
+                waiter.wake();
+            }
         }
+        prev
     }
-    fn set(&self, t: T) {
-        let mut lock = self.payload.lock().unwrap();
-        *lock = Some(t);
-        self.cond.notify_one();
+    fn send(&self, t: T) -> Result<(),T> {
+        if !self.is_needed() {
+            trace_event!(self,"oneshot set, receiver gone");
+            // a closed receiver may still be waiting
+            self.complete(CLOSED);
+            return Err(t);
+        }
+        // there is a single sender and the receiver doesn't touch the value before SET
+        unsafe { (*self.value.get()).write(t) };
+        if self.complete(SET) & RECEIVER_GONE != 0 {
+            // the receiver left or closed in the meantime, it won't read a value set after that
+            let t = unsafe { (*self.value.get()).assume_init_read() };
+            self.state.fetch_xor(SET | CLOSED,Ordering::AcqRel);
+            trace_event!(self,"oneshot set, receiver gone");
+            return Err(t);
+        }
+        trace_event!(self,"oneshot set");
+        #[cfg(feature = "std")]
+        if let Some(m) = metrics::get() {
+            m.sent();
+        }
+        Ok(())
+    }
+    fn drop_sender(&self) {
+        #[cfg(feature = "std")]
+        if let Some(m) = metrics::get() {
+            m.sender_dropped();
+        }
+        #[cfg(feature = "std")]
+        if thread::panicking() {
+            trace_event!(self,"oneshot sender panicked");
+            self.complete(CLOSED | PANICKED);
+            return;
+        }
+        trace_event!(self,"oneshot sender dropped");
+        self.complete(CLOSED);
+    }
+    fn drop_receiver(&self) {
+        self.state.fetch_or(RECEIVER_GONE,Ordering::Release);
+        #[cfg(feature = "std")]
+        self.notify_needed();
+    }
+    #[cfg(feature = "alloc")]
+    fn close(&self) {
+        if self.state.fetch_or(RECEIVER_GONE,Ordering::AcqRel) & SET != 0 {
+            // delivered before the close, the sender is done with the state
+            unsafe { (*self.value.get()).assume_init_drop() };
+            self.state.store(CLOSED | TAKEN | RECEIVER_GONE,Ordering::Relaxed);
+        }
+        #[cfg(feature = "std")]
+        self.notify_needed();
+    }
+    #[cfg(feature = "std")]
+    fn notify_needed(&self) {
+        if self.handoff.fetch_or(RX_WAITING,Ordering::AcqRel) & (RX_WAITING | TX_PARKED) == TX_PARKED {
+            if let Some(thread) = unsafe { (*self.sender.get()).take() } {
+                thread.unpark();
+            }
+        }
+    }
+    #[cfg(feature = "std")]
+    fn wait_needed(&self) {
+        if self.handoff.load(Ordering::Acquire) & RX_WAITING != 0 {
+            return;
+        }
+        unsafe { *self.sender.get() = Some(thread::current()) };
+        if self.handoff.fetch_or(TX_PARKED,Ordering::AcqRel) & RX_WAITING != 0 {
+            // the receiver came first and never looked at the slot
+            unsafe { *self.sender.get() = None };
+            return;
+        }
+        while self.handoff.load(Ordering::Acquire) & RX_WAITING == 0 {
+            thread::park();
+        }
     }
-    fn wait(&self) -> T {
-        let mut lock = self.payload.lock().unwrap();
-        while lock.is_none() {
-            lock = self.cond.wait(lock).unwrap();
+    fn is_needed(&self) -> bool {
+        self.state.load(Ordering::Acquire) & RECEIVER_GONE == 0
+    }
+    #[cfg(not(poll_only))]
+    fn closed_error(&self) -> RecvError {
+        let state = self.state.load(Ordering::Acquire);
+        match () {
+            _ if state & EXPIRED != 0 => RecvError::Timeout,
+            _ if state & PANICKED != 0 => RecvError::SenderPanicked,
+            _ => RecvError::Disconnected,
         }
-        lock.take().unwrap()
+    }
+    #[cfg(not(poll_only))]
+    fn is_done(&self) -> bool {
+        self.state.load(Ordering::Acquire) & DONE != 0
+    }
+    fn receiver_state(&self) -> State {
+        let state = self.state.load(Ordering::Acquire);
+        match () {
+            _ if state & (SET | RECEIVER_GONE) == SET => State::Ready,
+            _ if state & CLOSED != 0 => State::SenderDropped,
+            _ => State::Pending,
+        }
+    }
+    fn sender_state(&self) -> State {
+        match self.is_needed() {
+            true => State::Pending,
+            false => State::ReceiverDropped,
+        }
+    }
+    // a spent receiver looks like one whose sender was dropped
+    fn try_take(&self) -> Option<Option<T>> {
+        let mut state = self.state.load(Ordering::Acquire);
+        // a sender that raced close() is taking its value back
+        while state & (SET | RECEIVER_GONE) == SET | RECEIVER_GONE {
+            core::hint::spin_loop();
+            state = self.state.load(Ordering::Acquire);
+        }
+        if state & SET != 0 {
+            // the sender is gone once SET is published, so the state is ours
+            self.state.store(CLOSED | TAKEN,Ordering::Relaxed);
+            return Some(Some(unsafe { (*self.value.get()).assume_init_read() }));
+        }
+        match state & CLOSED != 0 {
+            true => {
+                self.state.fetch_or(TAKEN,Ordering::Relaxed);
+                Some(None)
+            },
+            false => None,
+        }
+    }
+    // the value stays in place until the receiver takes it, which needs &mut or self
+    #[cfg(feature = "alloc")]
+    fn peek(&self) -> Option<&T> {
+        match self.state.load(Ordering::Acquire) & (SET | RECEIVER_GONE) == SET {
+            true => Some(unsafe { (*self.value.get()).assume_init_ref() }),
+            false => None,
+        }
+    }
+    #[cfg(all(feature = "alloc", feature = "async"))]
+    fn is_taken(&self) -> bool {
+        self.state.load(Ordering::Relaxed) & TAKEN != 0
+    }
+    #[cfg(any(feature = "alloc", not(poll_only)))]
+    fn take(&self) -> Option<T> {
+        self.try_take().unwrap()
+    }
+    // gives the waiter back if the channel was completed in the meantime
+    #[cfg(feature = "alloc")]
+    fn register(&self, waiter: Waiter) -> Result<(),Waiter> {
+        #[cfg(feature = "std")]
+        self.notify_needed();
+        // take the slot back from a previously registered waiter
+        if let Err(state) = self.state.compare_exchange(WAITING,0,Ordering::Acquire,Ordering::Acquire) {
+            if state & DONE != 0 {
+                return Err(waiter);
+            }
+        }
+        unsafe { *self.waiter.get() = Some(waiter) };
+        match self.state.compare_exchange(0,WAITING,Ordering::AcqRel,Ordering::Acquire) {
+            Ok(_) => Ok(()),
+            Err(_) => {
+                // the sender didn't see WAITING, the slot is still ours
+                Err(unsafe { (*self.waiter.get()).take().unwrap() })
+            },
+        }
+    }
+    #[cfg(feature = "std")]
+    fn unregister(&self) -> bool {
+        match self.state.compare_exchange(WAITING,0,Ordering::Acquire,Ordering::Acquire) {
+            Ok(_) => {
+                unsafe { *self.waiter.get() = None };
+                true
+            },
+            Err(state) => state & DONE == 0,
+        }
+    }
+    // nothing can block in a browser without std, only try_get, state and the Future are left
+    #[cfg(all(not(poll_only), not(feature = "std")))]
+    fn wait(&self) -> Option<T> {
+        self.park_until_done()
+    }
+    #[cfg(feature = "std")]
+    fn wait(&self) -> Option<T> {
+        self.wait_with(strategy::get())
+    }
+    #[cfg(feature = "std")]
+    fn wait_with(&self, strategy: Option<&dyn WaitStrategy>) -> Option<T> {
+        let start = metrics::start();
+        if let Some(strategy) = strategy {
+            self.spin(strategy,None);
+        }
+        let res = self.park_until_done();
+        metrics::received(start,res.is_some());
+        res
+    }
+    // false once the strategy gives up or the deadline passes
+    #[cfg(feature = "std")]
+    fn spin(&self, strategy: &dyn WaitStrategy, deadline: Option<Instant>) -> bool {
+        let mut round = 0;
+        while !self.is_done() {
+            if deadline.is_some_and(|d| Instant::now() >= d) || !strategy.spin(round) {
+                return false;
+            }
+            round = round.saturating_add(1);
+        }
+        true
+    }
+    #[cfg(not(poll_only))]
+    fn park_until_done(&self) -> Option<T> {
+        if let Some(res) = self.try_take() {
+            return res;
+        }
+        trace_event!(self,"oneshot wait parked");
+        #[cfg(feature = "std")]
+        if self.register(Self::sleeper()).is_ok() {
+            let since = Instant::now();
+            let mut report_at = diag::threshold().and_then(|t| since.checked_add(t));
+            while !self.is_done() {
+                match report_at {
+                    Some(at) => match at.checked_duration_since(Instant::now()) {
+                        Some(left) => self.sleep(Some(left)),
+                        None => {
+                            diag::long_wait(self as *const InnerOne<T> as usize,since);
+                            report_at = None;
+                        },
+                    },
+                    None => self.sleep(None),
+                }
+            }
+        }
+        #[cfg(not(feature = "std"))]
+        while !self.is_done() {
+            core::hint::spin_loop();
+        }
+        let res = self.take();
+        trace_event!(self,received = res.is_some(),"oneshot wait woken");
+        res
+    }
+    // the waiter of a blocking receive, woken by sleep's counterpart in complete
+    #[cfg(feature = "std")]
+    fn sleeper() -> Waiter {
+        #[cfg(futex)]
+        return Waiter::Futex;
+        #[cfg(not(futex))]
+        Waiter::Thread(thread::current())
+    }
+    // returns on completion, timeout or spuriously, the callers recheck the state
+    #[cfg(feature = "std")]
+    fn sleep(&self, timeout: Option<Duration>) {
+        #[cfg(futex)]
+        {
+            let events = self.events.load(Ordering::SeqCst);
+            if !self.is_done() {
+                futex::wait(&self.events,events,timeout);
+            }
+        }
+        #[cfg(not(futex))]
+        match timeout {
+            Some(timeout) => thread::park_timeout(timeout),
+            None => thread::park(),
+        }
+    }
+    #[cfg(feature = "std")]
+    fn wait_deadline(&self, deadline: Instant) -> Option<Option<T>> {
+        self.wait_deadline_with(deadline,strategy::get())
+    }
+    #[cfg(feature = "std")]
+    fn wait_deadline_with(&self, deadline: Instant, strategy: Option<&dyn WaitStrategy>) -> Option<Option<T>> {
+        let start = metrics::start();
+        if let Some(strategy) = strategy {
+            self.spin(strategy,Some(deadline));
+        }
+        let res = self.park_until_deadline(deadline);
+        match &res {
+            Some(res) => metrics::received(start,res.is_some()),
+            None => metrics::timed_out(start),
+        }
+        res
+    }
+    #[cfg(feature = "std")]
+    fn park_until_deadline(&self, deadline: Instant) -> Option<Option<T>> {
+        if let Some(res) = self.try_take() {
+            return Some(res);
+        }
+        trace_event!(self,"oneshot wait parked");
+        if self.register(Self::sleeper()).is_ok() {
+            while !self.is_done() {
+                let now = Instant::now();
+                if now >= deadline {
+                    if self.unregister() {
+                        trace_event!(self,"oneshot wait timed out");
+                        return None;
+                    }
+                    break;
+                }
+                self.sleep(Some(deadline - now));
+            }
+        }
+        let res = self.take();
+        trace_event!(self,received = res.is_some(),"oneshot wait woken");
+        Some(res)
+    }
+    #[cfg(feature = "std")]
+    fn wait_timeout(&self, timeout: Duration) -> Option<Option<T>> {
+        match Instant::now().checked_add(timeout) {
+            Some(deadline) => self.wait_deadline(deadline),
+            None => Some(self.wait()),
+        }
+    }
+    #[cfg(any(feature = "std", all(feature = "alloc", feature = "async")))]
+    fn poll(&self, waker: &Waker) -> Poll<Option<T>> {
+        if let Some(res) = self.try_take() {
+            return Poll::Ready(res);
+        }
+        match self.register(Waiter::Waker(waker.clone())) {
+            Ok(()) => Poll::Pending,
+            Err(_) => Poll::Ready(self.take()),
+        }
+    }
+}
+
+impl<T> Drop for InnerOne<T> {
+    fn drop(&mut self) {
+        // exclusive here, a relaxed load sees the last state like get_mut would
+        if self.state.load(Ordering::Relaxed) & SET != 0 {
+            unsafe { self.value.get_mut().assume_init_drop() };
+        }
+        #[cfg(feature = "std")]
+        diag::freed(self as *const InnerOne<T> as usize);
     }
 }
 
-pub struct OneGet<T>(Arc<InnerOne<Option<T>>>);
+#[cfg(feature = "alloc")]
+pub struct OneGet<T>(Arc<InnerOne<T>>);
+#[cfg(feature = "alloc")]
 impl<T> OneGet<T> {
     pub fn is_ready(&self) -> bool {
-        // relaxed variant
-        Arc::strong_count(&self.0) == 1
+        self.state() == State::Ready
+    }
+    pub fn is_closed(&self) -> bool {
+        self.state() == State::SenderDropped
+    }
+    pub fn state(&self) -> State {
+        self.0.receiver_state()
     }
+    #[cfg(not(poll_only))]
     pub fn wait(self) -> Option<T> {
         self.0.wait()
     }
+    #[cfg(not(poll_only))]
+    pub fn recv(self) -> Result<T,RecvError> {
+        self.0.wait().ok_or_else(|| self.0.closed_error())
+    }
+    #[cfg(not(poll_only))]
+    pub fn wait_or(self, t: T) -> T {
+        self.wait().unwrap_or(t)
+    }
+    #[cfg(not(poll_only))]
+    pub fn wait_or_default(self) -> T
+    where T: Default
+    {
+        self.wait().unwrap_or_default()
+    }
+    #[cfg(not(poll_only))]
+    pub fn wait_or_else<F: FnOnce() -> T>(self, f: F) -> T {
+        self.wait().unwrap_or_else(f)
+    }
+    #[cfg(feature = "std")]
+    pub fn wait_timeout(self, timeout: Duration) -> Result<Option<T>,OneGet<T>> {
+        match self.0.wait_timeout(timeout) {
+            Some(res) => Ok(res),
+            None => Err(self),
+        }
+    }
+    // the strategy replaces the process-wide one for this wait
+    #[cfg(feature = "std")]
+    pub fn wait_with(self, strategy: &dyn WaitStrategy) -> Option<T> {
+        self.0.wait_with(Some(strategy))
+    }
+    #[cfg(feature = "std")]
+    pub fn wait_timeout_with(self, timeout: Duration, strategy: &dyn WaitStrategy) -> Result<Option<T>,OneGet<T>> {
+        let res = match Instant::now().checked_add(timeout) {
+            Some(deadline) => self.0.wait_deadline_with(deadline,Some(strategy)),
+            None => Some(self.0.wait_with(Some(strategy))),
+        };
+        res.ok_or(self)
+    }
+    #[cfg(feature = "std")]
+    pub fn wait_deadline(self, deadline: Instant) -> Result<Option<T>,OneGet<T>> {
+        match self.0.wait_deadline(deadline) {
+            Some(res) => Ok(res),
+            None => Err(self),
+        }
+    }
+    #[cfg(feature = "std")]
+    pub fn recv_timeout(self, timeout: Duration) -> Result<T,RecvTimeoutError<T>> {
+        match self.wait_timeout(timeout) {
+            Ok(res) => res.ok_or(RecvTimeoutError::Disconnected),
+            Err(handle) => Err(RecvTimeoutError::Timeout { handle }),
+        }
+    }
+    #[cfg(feature = "std")]
+    pub fn recv_deadline(self, deadline: Instant) -> Result<T,RecvTimeoutError<T>> {
+        match self.wait_deadline(deadline) {
+            Ok(res) => res.ok_or(RecvTimeoutError::Disconnected),
+            Err(handle) => Err(RecvTimeoutError::Timeout { handle }),
+        }
+    }
+    // gives the handle back once the token is cancelled
+    #[cfg(feature = "std")]
+    pub fn wait_cancellable(self, token: &CancellationToken) -> Result<Option<T>,OneGet<T>> {
+        let waker = select::thread_waker();
+        let id = match token.watch(&waker) {
+            Some(id) => id,
+            None => return Err(self),
+        };
+        let res = loop {
+            if token.is_cancelled() {
+                break Err(self);
+            }
+            if let Poll::Ready(res) = self.0.poll(&waker) {
+                break Ok(res);
+            }
+            thread::park();
+        };
+        token.unwatch(id);
+        res
+    }
+    pub fn try_take(&mut self) -> Option<Option<T>> {
+        self.0.try_take()
+    }
+    // for event loops without Future: while pending, register gets a WakeHandle that is
+    // woken when the channel completes; each pending poll replaces the previous handle
+    pub fn poll_get<F: FnOnce(WakeHandle)>(&mut self, register: F) -> Poll<Option<T>> {
+        if let Some(res) = self.0.try_take() {
+            return Poll::Ready(res);
+        }
+        let handle = WakeHandle::new();
+        let waker = handle.clone();
+        match self.0.register(Waiter::Callback(Box::new(move || waker.wake()))) {
+            Ok(()) => {
+                register(handle);
+                Poll::Pending
+            },
+            Err(_) => Poll::Ready(self.0.take()),
+        }
+    }
+    // a shared OneGet hands the reference to other threads, hence Sync
+    pub fn peek(&self) -> Option<&T>
+    where T: Sync
+    {
+        self.0.peek()
+    }
+    // declines the value while keeping the handle, the sender sees it as a dropped receiver
+    // and the handle completes with None
+    pub fn close(&mut self) {
+        self.0.close();
+    }
     pub fn try_get(self) -> Result<Option<T>,OneGet<T>> {
-        match Arc::try_unwrap(self.0) {
-            Ok(inner) => Ok(inner.wait()),
-            Err(arc_inner) => Err(OneGet(arc_inner)),
+        match self.0.try_take() {
+            Some(res) => Ok(res),
+            None => Err(self),
+        }
+    }
+    pub fn into_raw(self) -> *const () {
+        let this = ManuallyDrop::new(self);
+        Arc::into_raw(unsafe { core::ptr::read(&this.0) }) as *const ()
+    }
+    /// # Safety
+    /// `raw` must come from `OneGet::<T>::into_raw` and be turned back only once.
+    pub unsafe fn from_raw(raw: *const ()) -> OneGet<T> {
+        OneGet(Arc::from_raw(raw as *const InnerOne<T>))
+    }
+    pub fn map<U, F>(self, f: F) -> OneGet<U>
+    where T: Send + 'static, U: Send + 'static, F: FnOnce(T) -> U + Send + 'static
+    {
+        let (tx,rx) = oneshot();
+        self.on_ready(move |res| if let Some(t) = res {
+            let _ = tx.set(f(t));
+        });
+        rx
+    }
+    pub fn forward(self, tx: OneSet<T>)
+    where T: Send + 'static
+    {
+        self.on_ready(move |res| if let Some(t) = res {
+            let _ = tx.set(t);
+        });
+    }
+    #[cfg(feature = "std")]
+    pub fn forward_to(self, tx: std::sync::mpsc::Sender<T>)
+    where T: Send + 'static
+    {
+        self.on_ready(move |res| if let Some(t) = res {
+            let _ = tx.send(t);
+        });
+    }
+    pub fn on_ready<F>(self, f: F)
+    where T: Send + 'static, F: FnOnce(Option<T>) + Send + 'static
+    {
+        if let Some(res) = self.0.try_take() {
+            return f(res);
+        }
+        let inner = self.0.clone();
+        // the callback keeps the receiver alive until the channel completes
+        let callback = Box::new(move || f(self.0.take()));
+        if let Err(waiter) = inner.register(Waiter::Callback(callback)) {
+            waiter.wake();
         }
     }
 }
+#[cfg(all(feature = "alloc", feature = "async"))]
+impl<T> Future for OneGet<T> {
+    type Output = Option<T>;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        self.0.poll(cx.waker())
+    }
+}
+// matches futures' FusedFuture: true once polling has returned Ready
+#[cfg(all(feature = "alloc", feature = "async"))]
+impl<T> OneGet<T> {
+    pub fn is_terminated(&self) -> bool {
+        self.0.is_taken()
+    }
+}
+#[cfg(feature = "alloc")]
 impl<T> Debug for OneGet<T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "OneGet")
     }
 }
+#[cfg(feature = "alloc")]
+impl<T> Drop for OneGet<T> {
+    fn drop(&mut self) {
+        self.0.drop_receiver();
+    }
+}
 
-pub struct OneSet<T>(Arc<InnerOne<Option<T>>>,bool);
+#[cfg(feature = "alloc")]
+pub struct OneSet<T>(Arc<InnerOne<T>>,bool);
+#[cfg(feature = "alloc")]
 impl<T> OneSet<T> {
     pub fn is_needed(&self) -> bool {
-        // relaxed variant
-        Arc::strong_count(&self.0) == 2
+        self.0.is_needed()
     }
-    pub fn set(mut self, t: T) {
-        self.0.set(Some(t));
+    pub fn state(&self) -> State {
+        self.0.sender_state()
+    }
+    pub fn set(mut self, t: T) -> Result<(),T> {
+        self.1 = true;
+        self.0.send(t)
+    }
+    // set already gives the value back when the receiver is gone, even if it leaves mid-set;
+    // this is the name to reach for instead of is_needed() followed by set()
+    pub fn set_if_needed(self, t: T) -> Result<(),T> {
+        self.set(t)
+    }
+    // returns once the receiver waits, by any means, or is dropped or closed
+    #[cfg(feature = "std")]
+    pub fn wait_needed(&self) {
+        self.0.wait_needed();
+    }
+    // f only runs while the receiver is still there, Err(None) if it didn't run
+    pub fn set_with<F: FnOnce() -> T>(self, f: F) -> Result<(),Option<T>> {
+        match self.is_needed() {
+            true => self.set(f()).map_err(Some),
+            false => Err(None),
+        }
+    }
+    pub fn into_raw(self) -> *const () {
+        let this = ManuallyDrop::new(self);
+        Arc::into_raw(unsafe { core::ptr::read(&this.0) }) as *const ()
+    }
+    /// # Safety
+    /// `raw` must come from `OneSet::<T>::into_raw` and be turned back only once.
+    pub unsafe fn from_raw(raw: *const ()) -> OneSet<T> {
+        OneSet(Arc::from_raw(raw as *const InnerOne<T>),false)
+    }
+    #[cfg(feature = "std")]
+    fn expire(mut self) {
         self.1 = true;
+        self.0.complete(CLOSED | EXPIRED);
     }
 }
+#[cfg(feature = "alloc")]
 impl<T> Debug for OneSet<T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "OneSet")
     }
 }   
+#[cfg(feature = "alloc")]
 impl<T> Drop for OneSet<T> {
     fn drop(&mut self) {
         if !self.1 {
-            self.0.set(None);
+            self.0.drop_sender();
         }
     }
 }
 
+#[cfg(feature = "alloc")]
 pub fn oneshot<T>() -> (OneSet<T>,OneGet<T>) {
     let r = Arc::new(InnerOne::new());
+    #[cfg(feature = "std")]
+    diag::created(&*r as *const InnerOne<T> as usize);
     (OneSet(r.clone(),false),OneGet(r))
 }
 
 
 #[cfg(test)]
 mod tests {
-    use super::oneshot;
+    use super::{oneshot,RecvError,RecvTimeoutError,State};
     use std::thread;
-    use std::time::Duration;
+    use std::time::{Duration,Instant};
     
     #[test]
     fn test_wait_setting() {
         let (tx,rx) = oneshot();
         let h = thread::spawn(move || {
             thread::sleep(Duration::from_millis(500));
-            tx.set(5);
+            tx.set(5).unwrap();
         });
         assert_eq!(rx.wait(),Some(5));
         h.join().unwrap();
@@ -99,7 +973,7 @@ mod tests {
     fn test_wait_getting() {
         let (tx,rx) = oneshot();
         let h = thread::spawn(move || {                
-            tx.set(3);
+            tx.set(3).unwrap();
         });
         thread::sleep(Duration::from_millis(500));
         assert_eq!(rx.wait(),Some(3));
@@ -116,6 +990,506 @@ mod tests {
         assert_eq!(rx.wait(),None);
         h.join().unwrap();
     }
-    
-}
 
+    #[test]
+    fn test_recv() {
+        let (tx,rx) = oneshot();
+        tx.set(1).unwrap();
+        assert_eq!(rx.recv(),Ok(1));
+        let (tx,rx) = oneshot::<u64>();
+        drop(tx);
+        assert_eq!(rx.recv(),Err(RecvError::Disconnected));
+    }
+
+    #[test]
+    fn test_sender_panicked() {
+        let (tx,rx) = oneshot::<u64>();
+        let h = thread::spawn(move || {
+            let _tx = tx;
+            panic!("sender failed");
+        });
+        assert!(h.join().is_err());
+        assert_eq!(rx.recv(),Err(RecvError::SenderPanicked));
+    }
+
+    #[test]
+    fn test_set_with() {
+        let (tx,rx) = oneshot();
+        assert_eq!(tx.set_with(|| 1),Ok(()));
+        assert_eq!(rx.wait(),Some(1));
+        let (tx,rx) = oneshot::<u8>();
+        drop(rx);
+        assert_eq!(tx.set_with(|| unreachable!()),Err(None));
+    }
+
+    #[test]
+    fn test_set_if_needed() {
+        for _ in 0 .. 100 {
+            let (tx,rx) = oneshot();
+            let h = thread::spawn(move || drop(rx));
+            if let Err(v) = tx.set_if_needed(vec![1]) {
+                assert_eq!(v,vec![1]);
+            }
+            h.join().unwrap();
+        }
+        let (tx,rx) = oneshot();
+        assert_eq!(tx.set_if_needed(2),Ok(()));
+        assert_eq!(rx.wait(),Some(2));
+    }
+
+    #[test]
+    fn test_close() {
+        let (tx,mut rx) = oneshot::<u8>();
+        rx.close();
+        assert!(!tx.is_needed());
+        assert_eq!(tx.state(),State::ReceiverDropped);
+        assert_eq!(tx.set(1),Err(1));
+        assert_eq!(rx.wait(),None);
+        let (tx,mut rx) = oneshot();
+        tx.set(vec![2]).unwrap();
+        rx.close();
+        assert_eq!(rx.try_take(),Some(None));
+        let (tx,mut rx) = oneshot();
+        let h = thread::spawn(move || tx.set(3));
+        thread::sleep(Duration::from_millis(20));
+        rx.close();
+        assert!(h.join().unwrap().is_ok() || rx.is_closed());
+        assert_eq!(rx.wait(),None);
+    }
+
+    #[test]
+    fn test_wait_needed() {
+        let (tx,rx) = oneshot();
+        let h = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(30));
+            rx.wait()
+        });
+        let start = Instant::now();
+        tx.wait_needed();
+        assert!(start.elapsed() >= Duration::from_millis(30));
+        tx.set(1).unwrap();
+        assert_eq!(h.join().unwrap(),Some(1));
+        let (tx,rx) = oneshot::<u8>();
+        thread::spawn(move || drop(rx));
+        tx.wait_needed();
+        assert!(!tx.is_needed());
+    }
+
+    #[test]
+    fn test_peek() {
+        let (tx,mut rx) = oneshot();
+        assert_eq!(rx.peek(),None);
+        tx.set(String::from("result")).unwrap();
+        assert_eq!(rx.peek().map(|s| s.len()),Some(6));
+        assert_eq!(rx.peek().map(String::as_str),Some("result"));
+        assert_eq!(rx.try_take(),Some(Some(String::from("result"))));
+        assert_eq!(rx.peek(),None);
+    }
+
+    #[test]
+    fn test_wait_or() {
+        let (tx,rx) = oneshot();
+        tx.set(1).unwrap();
+        assert_eq!(rx.wait_or(0),1);
+        let (_,rx) = oneshot();
+        assert_eq!(rx.wait_or(2),2);
+        let (_,rx) = oneshot::<Vec<u8>>();
+        assert_eq!(rx.wait_or_default(),vec![]);
+        let (_,rx) = oneshot();
+        assert_eq!(rx.wait_or_else(|| 3),3);
+    }
+
+    #[test]
+    fn test_raw() {
+        let (tx,rx) = oneshot::<String>();
+        let raw_tx = tx.into_raw() as usize;
+        let raw_rx = rx.into_raw();
+        let h = thread::spawn(move || {
+            let tx = unsafe { super::OneSet::from_raw(raw_tx as *const ()) };
+            tx.set("through void*".to_string()).unwrap();
+        });
+        let rx = unsafe { super::OneGet::<String>::from_raw(raw_rx) };
+        assert_eq!(rx.wait().as_deref(),Some("through void*"));
+        h.join().unwrap();
+        let (tx,rx) = oneshot::<u64>();
+        drop(unsafe { super::OneSet::<u64>::from_raw(tx.into_raw()) });
+        assert_eq!(rx.wait(),None);
+    }
+
+    #[test]
+    fn test_set_dropped_getter() {
+        let (tx,rx) = oneshot();
+        drop(rx);
+        assert_eq!(tx.set(13),Err(13));
+    }
+
+    #[test]
+    fn test_try_get() {
+        let (tx,rx) = oneshot();
+        let rx = rx.try_get().unwrap_err();
+        tx.set(21).unwrap();
+        assert_eq!(rx.try_get().unwrap(),Some(21));
+    }
+
+    #[test]
+    fn test_try_take() {
+        let (tx,mut rx) = oneshot();
+        assert_eq!(rx.try_take(),None);
+        tx.set(22).unwrap();
+        assert_eq!(rx.try_take(),Some(Some(22)));
+        assert_eq!(rx.try_take(),Some(None));
+        assert_eq!(rx.wait(),None);
+    }
+
+    #[test]
+    fn test_map() {
+        let (tx,rx) = oneshot();
+        tx.set(4).unwrap();
+        assert_eq!(rx.map(|x| x * 2).wait(),Some(8));
+        let (tx,rx) = oneshot();
+        let rx = rx.map(|x: u64| x.to_string());
+        let h = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(100));
+            tx.set(7).unwrap();
+        });
+        assert_eq!(rx.wait(),Some("7".to_string()));
+        h.join().unwrap();
+        let (tx,rx) = oneshot::<u64>();
+        let rx = rx.map(|x| x + 1);
+        drop(tx);
+        assert_eq!(rx.wait(),None);
+    }
+
+    #[test]
+    fn test_map_dropped_getter() {
+        let (tx,rx) = oneshot::<u64>();
+        drop(rx.map(|x| x + 1));
+        assert!(tx.is_needed());
+        tx.set(1).unwrap();
+    }
+
+    #[test]
+    fn test_on_ready() {
+        use std::sync::mpsc;
+        let (done,events) = mpsc::channel();
+        let (tx,rx) = oneshot();
+        let main = thread::current().id();
+        let d = done.clone();
+        rx.on_ready(move |res| d.send((res,thread::current().id())).unwrap());
+        thread::spawn(move || tx.set(3).unwrap()).join().unwrap();
+        let (res,id) = events.recv().unwrap();
+        assert_eq!(res,Some(3));
+        assert_ne!(id,main);
+        let (tx,rx) = oneshot::<u64>();
+        rx.on_ready(move |res| done.send((res,thread::current().id())).unwrap());
+        drop(tx);
+        assert_eq!(events.recv().unwrap(),(None,main));
+    }
+
+    #[test]
+    fn test_forward() {
+        let (tx,rx) = oneshot();
+        let (down_tx,down_rx) = oneshot();
+        rx.forward(down_tx);
+        let h = thread::spawn(move || tx.set(9).unwrap());
+        assert_eq!(down_rx.wait(),Some(9));
+        h.join().unwrap();
+        let (tx,rx) = oneshot::<u64>();
+        let (down_tx,down_rx) = oneshot();
+        rx.forward(down_tx);
+        drop(tx);
+        assert_eq!(down_rx.wait(),None);
+    }
+
+    #[test]
+    fn test_forward_to() {
+        use std::sync::mpsc;
+        let (events_tx,events) = mpsc::channel();
+        let (tx1,rx1) = oneshot();
+        let (tx2,rx2) = oneshot();
+        rx1.forward_to(events_tx.clone());
+        rx2.forward_to(events_tx);
+        drop(tx1);
+        thread::spawn(move || tx2.set(2).unwrap()).join().unwrap();
+        assert_eq!(events.recv(),Ok(2));
+        assert!(events.recv().is_err());
+    }
+
+    #[test]
+    fn test_state() {
+        let (tx,rx) = oneshot();
+        assert_eq!(rx.state(),State::Pending);
+        assert_eq!(tx.state(),State::Pending);
+        tx.set(1).unwrap();
+        assert_eq!(rx.state(),State::Ready);
+        let (tx,rx) = oneshot::<u8>();
+        drop(tx);
+        assert_eq!(rx.state(),State::SenderDropped);
+        let (tx,rx) = oneshot::<u8>();
+        drop(rx);
+        assert_eq!(tx.state(),State::ReceiverDropped);
+    }
+
+    #[test]
+    fn test_is_needed_while_waiting() {
+        let (tx,rx) = oneshot::<u8>();
+        let h = thread::spawn(move || rx.wait());
+        thread::sleep(Duration::from_millis(100));
+        assert!(tx.is_needed());
+        tx.set(4).unwrap();
+        assert_eq!(h.join().unwrap(),Some(4));
+    }
+
+    #[test]
+    fn test_is_ready() {
+        let (tx,rx) = oneshot();
+        assert!(!rx.is_ready() && !rx.is_closed());
+        tx.set(1).unwrap();
+        assert!(rx.is_ready() && !rx.is_closed());
+        let (tx,rx) = oneshot::<u8>();
+        drop(tx);
+        assert!(!rx.is_ready() && rx.is_closed());
+    }
+
+    #[test]
+    fn test_race_set_wait() {
+        for i in 0 .. 1000 {
+            let (tx,rx) = oneshot();
+            let h = thread::spawn(move || tx.set(i).unwrap());
+            assert_eq!(rx.wait(),Some(i));
+            h.join().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_unreceived_value_dropped() {
+        let item = std::sync::Arc::new(());
+        let (tx,rx) = oneshot();
+        tx.set(item.clone()).unwrap();
+        assert_eq!(std::sync::Arc::strong_count(&item),2);
+        drop(rx);
+        assert_eq!(std::sync::Arc::strong_count(&item),1);
+    }
+
+    #[test]
+    fn test_wait_timeout() {
+        let (tx,rx) = oneshot();
+        let h = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(500));
+            tx.set(7).unwrap();
+        });
+        let rx = match rx.wait_timeout(Duration::from_millis(50)) {
+            Ok(_) => panic!("value before timeout"),
+            Err(rx) => rx,
+        };
+        assert_eq!(rx.wait_timeout(Duration::from_secs(5)).unwrap(),Some(7));
+        h.join().unwrap();
+    }
+
+    #[test]
+    fn test_wait_deadline() {
+        let (tx,rx) = oneshot();
+        let deadline = Instant::now() + Duration::from_millis(100);
+        let rx = rx.wait_deadline(deadline).unwrap_err();
+        assert!(Instant::now() >= deadline);
+        tx.set(8).unwrap();
+        assert_eq!(rx.wait_deadline(deadline).unwrap(),Some(8));
+    }
+
+    #[test]
+    fn test_recv_timeout() {
+        let (tx,rx) = oneshot::<u8>();
+        let rx = match rx.recv_timeout(Duration::from_millis(50)) {
+            Err(RecvTimeoutError::Timeout { handle }) => handle,
+            res => panic!("unexpected {:?}", res),
+        };
+        drop(tx);
+        assert!(matches!(rx.recv_timeout(Duration::from_millis(50)),Err(RecvTimeoutError::Disconnected)));
+    }
+
+    #[test]
+    fn test_wait_timeout_race() {
+        for i in 0 .. 500 {
+            let (tx,rx) = oneshot();
+            let h = thread::spawn(move || tx.set(i).unwrap());
+            let res = match rx.wait_timeout(Duration::from_micros(i % 50)) {
+                Ok(res) => res,
+                Err(rx) => rx.wait(),
+            };
+            assert_eq!(res,Some(i));
+            h.join().unwrap();
+        }
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn test_tracing_events() {
+        use std::fmt::Debug;
+        use std::sync::{Arc,Mutex};
+        use tracing::field::{Field,Visit};
+        use tracing::span::{Attributes,Id,Record};
+        use tracing::{Event,Metadata,Subscriber};
+
+        // collects the messages of the events on this thread
+        struct Messages(Arc<Mutex<Vec<String>>>);
+        struct Message(Option<String>);
+        impl Visit for Message {
+            fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+                if field.name() == "message" {
+                    self.0 = Some(format!("{:?}",value));
+                }
+            }
+        }
+        impl Subscriber for Messages {
+            fn enabled(&self, _: &Metadata<'_>) -> bool {
+                true
+            }
+            fn new_span(&self, _: &Attributes<'_>) -> Id {
+                Id::from_u64(1)
+            }
+            fn record(&self, _: &Id, _: &Record<'_>) {}
+            fn record_follows_from(&self, _: &Id, _: &Id) {}
+            fn event(&self, event: &Event<'_>) {
+                let mut message = Message(None);
+                event.record(&mut message);
+                self.0.lock().unwrap().extend(message.0);
+            }
+            fn enter(&self, _: &Id) {}
+            fn exit(&self, _: &Id) {}
+        }
+        let messages = Arc::new(Mutex::new(Vec::new()));
+        tracing::subscriber::with_default(Messages(messages.clone()),|| {
+            let (tx,rx) = oneshot();
+            tx.set(1).unwrap();
+            assert_eq!(rx.wait(),Some(1));
+            let (tx,rx) = oneshot::<u8>();
+            let rx = rx.wait_timeout(Duration::from_millis(1)).unwrap_err();
+            drop(tx);
+            assert_eq!(rx.wait(),None);
+        });
+        let expected = ["oneshot set","oneshot wait parked","oneshot wait timed out","oneshot sender dropped"];
+        assert_eq!(*messages.lock().unwrap(),expected);
+    }
+
+    #[cfg(futex)]
+    #[test]
+    fn test_futex_wait() {
+        use std::sync::atomic::Ordering;
+
+        let (tx,rx) = oneshot();
+        let inner = rx.0.clone();
+        let h = thread::spawn(move || rx.wait());
+        while inner.state.load(Ordering::Acquire) & super::WAITING == 0 {
+            thread::yield_now();
+        }
+        tx.set(5).unwrap();
+        assert_eq!(h.join().unwrap(),Some(5));
+        // the receiver slept on the events word, no thread was unparked
+        assert_eq!(inner.events.load(Ordering::Relaxed),1);
+    }
+
+    #[cfg(feature = "async")]
+    fn block_on<F: std::future::Future>(fut: F) -> F::Output {
+        use std::task::{Context,Poll};
+
+        let waker = crate::select::thread_waker();
+        let mut cx = Context::from_waker(&waker);
+        let mut fut = std::pin::pin!(fut);
+        loop {
+            match fut.as_mut().poll(&mut cx) {
+                Poll::Ready(res) => return res,
+                Poll::Pending => thread::park(),
+            }
+        }
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn test_await_setting() {
+        let (tx,rx) = oneshot();
+        let h = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(500));
+            tx.set(11).unwrap();
+        });
+        assert_eq!(block_on(rx),Some(11));
+        h.join().unwrap();
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn test_await_drop_setter() {
+        let (tx,rx) = oneshot::<u64>();
+        let h = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(500));
+            let _tx = tx;
+        });
+        assert_eq!(block_on(rx),None);
+        h.join().unwrap();
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn test_is_terminated() {
+        use std::future::Future;
+        use std::task::{Context,Poll};
+
+        let waker = crate::select::thread_waker();
+        let mut cx = Context::from_waker(&waker);
+        let (tx,mut rx) = oneshot();
+        assert_eq!(std::pin::Pin::new(&mut rx).poll(&mut cx),Poll::Pending);
+        drop(tx);
+        assert!(!rx.is_terminated());
+        assert_eq!(std::pin::Pin::new(&mut rx).poll(&mut cx),Poll::Ready(None::<u64>));
+        assert!(rx.is_terminated());
+    }
+
+    // RUSTFLAGS="--cfg loom" cargo test --release --lib loom; both sides get a model
+    // thread of their own, loom misses interleavings against the spawning thread
+    #[cfg(loom)]
+    #[test]
+    fn test_loom_set_and_drop() {
+        loom::model(|| {
+            let value = std::sync::Arc::new(());
+            let (tx,rx) = oneshot();
+            let sent = value.clone();
+            let a = loom::thread::spawn(move || {
+                let _ = tx.set(sent);
+            });
+            let b = loom::thread::spawn(move || drop(rx));
+            a.join().unwrap();
+            b.join().unwrap();
+            // whichever side saw the other leave dropped the value, exactly once
+            assert_eq!(std::sync::Arc::strong_count(&value),1);
+        });
+    }
+
+    #[cfg(loom)]
+    #[test]
+    fn test_loom_set_and_take() {
+        loom::model(|| {
+            let (tx,mut rx) = oneshot();
+            let a = loom::thread::spawn(move || tx.set(5).unwrap());
+            let b = loom::thread::spawn(move || (rx.try_take(),rx));
+            a.join().unwrap();
+            match b.join().unwrap() {
+                (Some(res),_) => assert_eq!(res,Some(5)),
+                (None,mut rx) => assert_eq!(rx.try_take(),Some(Some(5))),
+            }
+        });
+    }
+
+    #[cfg(loom)]
+    #[test]
+    fn test_loom_drop_and_take() {
+        loom::model(|| {
+            let (tx,mut rx) = oneshot::<u8>();
+            let a = loom::thread::spawn(move || drop(tx));
+            let b = loom::thread::spawn(move || (rx.try_take(),rx));
+            a.join().unwrap();
+            match b.join().unwrap() {
+                (Some(res),_) => assert_eq!(res,None),
+                (None,mut rx) => assert_eq!(rx.try_take(),Some(None)),
+            }
+        });
+    }
+}
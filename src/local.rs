@@ -0,0 +1,159 @@
+use alloc::rc::Rc;
+use core::cell::RefCell;
+use core::fmt::{self,Debug};
+#[cfg(feature = "async")]
+use core::{future::Future,pin::Pin,task::{Context,Poll,Waker}};
+
+use crate::State;
+
+struct Inner<T> {
+    value: Option<T>,
+    closed: bool,
+    receiver: bool,
+    #[cfg(feature = "async")]
+    waker: Option<Waker>,
+}
+
+pub struct OneGet<T>(Rc<RefCell<Inner<T>>>);
+impl<T> OneGet<T> {
+    pub fn state(&self) -> State {
+        let inner = self.0.borrow();
+        match () {
+            _ if inner.value.is_some() => State::Ready,
+            _ if inner.closed => State::SenderDropped,
+            _ => State::Pending,
+        }
+    }
+    pub fn is_ready(&self) -> bool {
+        self.state() == State::Ready
+    }
+    pub fn is_closed(&self) -> bool {
+        self.state() == State::SenderDropped
+    }
+    pub fn try_take(&mut self) -> Option<Option<T>> {
+        let mut inner = self.0.borrow_mut();
+        match inner.value.take() {
+            Some(t) => {
+                // a spent receiver looks like one whose sender was dropped
+                inner.closed = true;
+                Some(Some(t))
+            },
+            None if inner.closed => Some(None),
+            None => None,
+        }
+    }
+    pub fn try_get(mut self) -> Result<Option<T>,OneGet<T>> {
+        match self.try_take() {
+            Some(res) => Ok(res),
+            None => Err(self),
+        }
+    }
+}
+#[cfg(feature = "async")]
+impl<T> Future for OneGet<T> {
+    type Output = Option<T>;
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        if let Some(res) = self.try_take() {
+            return Poll::Ready(res);
+        }
+        self.0.borrow_mut().waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+impl<T> Debug for OneGet<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "local::OneGet")
+    }
+}
+impl<T> Drop for OneGet<T> {
+    fn drop(&mut self) {
+        self.0.borrow_mut().receiver = false;
+    }
+}
+
+pub struct OneSet<T>(Rc<RefCell<Inner<T>>>);
+impl<T> OneSet<T> {
+    pub fn is_needed(&self) -> bool {
+        self.0.borrow().receiver
+    }
+    pub fn state(&self) -> State {
+        match self.is_needed() {
+            true => State::Pending,
+            false => State::ReceiverDropped,
+        }
+    }
+    pub fn set(self, t: T) -> Result<(),T> {
+        let mut inner = self.0.borrow_mut();
+        if !inner.receiver {
+            return Err(t);
+        }
+        inner.value = Some(t);
+        Ok(())
+    }
+    #[cfg(feature = "async")]
+    fn wake(&self) {
+        let waker = self.0.borrow_mut().waker.take();
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+impl<T> Debug for OneSet<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "local::OneSet")
+    }
+}
+impl<T> Drop for OneSet<T> {
+    fn drop(&mut self) {
+        // both a set value and a dropped sender complete the channel
+        self.0.borrow_mut().closed = true;
+        #[cfg(feature = "async")]
+        self.wake();
+    }
+}
+
+pub fn oneshot<T>() -> (OneSet<T>,OneGet<T>) {
+    let r = Rc::new(RefCell::new(Inner {
+        value: None,
+        closed: false,
+        receiver: true,
+        #[cfg(feature = "async")]
+        waker: None,
+    }));
+    (OneSet(r.clone()),OneGet(r))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::oneshot;
+    use crate::State;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_rc_payload() {
+        let (tx,rx) = oneshot();
+        let rx = rx.try_get().unwrap_err();
+        let payload = Rc::new(5);
+        tx.set(payload.clone()).unwrap();
+        assert_eq!(rx.state(),State::Ready);
+        assert_eq!(rx.try_get().unwrap(),Some(payload));
+    }
+
+    #[test]
+    fn test_drop_setter() {
+        let (tx,mut rx) = oneshot::<Rc<u8>>();
+        assert_eq!(rx.try_take(),None);
+        drop(tx);
+        assert_eq!(rx.try_take(),Some(None));
+        assert!(rx.is_closed());
+    }
+
+    #[test]
+    fn test_drop_getter() {
+        let (tx,rx) = oneshot();
+        drop(rx);
+        assert_eq!(tx.state(),State::ReceiverDropped);
+        assert_eq!(tx.set(1),Err(1));
+    }
+}
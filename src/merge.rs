@@ -0,0 +1,128 @@
+use std::thread;
+
+use crate::error::TryRecvError;
+use crate::iter::Watcher;
+use crate::mpsc::{self,Receiver,Sender};
+
+// one thread drains every source, parking while they are all empty; it stops once
+// the sources are disconnected or at the first send after the output is dropped
+fn spawn_merge<T,F>(sources: Vec<Receiver<T>>, run: F) -> Receiver<T>
+where T: Send + 'static, F: FnOnce(Vec<Receiver<T>>,Sender<T>) + Send + 'static
+{
+    let (tx,rx) = mpsc::channel();
+    thread::Builder::new()
+        .name("transmitter-merge".to_string())
+        .spawn(move || {
+            let watcher = Watcher::current();
+            for source in &sources {
+                source.watch(&watcher);
+            }
+            run(sources,tx);
+        })
+        .expect("failed to spawn the merge thread");
+    rx
+}
+
+// takes at most one value from each source per round, so a busy source can't
+// starve the others
+pub fn merge<T: Send + 'static>(receivers: Vec<Receiver<T>>) -> Receiver<T> {
+    spawn_merge(receivers,|mut sources,tx| {
+        while !sources.is_empty() {
+            let mut idle = true;
+            let mut stopped = false;
+            sources.retain(|rx| match rx.try_recv() {
+                Ok(t) => {
+                    idle = false;
+                    stopped |= tx.send(t).is_err();
+                    true
+                },
+                Err(TryRecvError::Empty | TryRecvError::Malformed) => true,
+                Err(TryRecvError::Disconnected) => false,
+            });
+            if stopped {
+                return;
+            }
+            if idle && !sources.is_empty() {
+                thread::park();
+            }
+        }
+    })
+}
+
+// for sources already ordered by key: the smallest head goes out once every live
+// source has a value waiting, so the output is ordered as well
+pub fn merge_by_key<T,K,F>(receivers: Vec<Receiver<T>>, key: F) -> Receiver<T>
+where T: Send + 'static, K: Ord, F: Fn(&T) -> K + Send + 'static
+{
+    spawn_merge(receivers,move |sources,tx| {
+        let mut heads = sources.into_iter().map(|rx| (rx,None)).collect::<Vec<(Receiver<T>,Option<T>)>>();
+        loop {
+            let mut missing = false;
+            heads.retain_mut(|(rx,head)| {
+                if head.is_none() {
+                    match rx.try_recv() {
+                        Ok(t) => *head = Some(t),
+                        Err(TryRecvError::Empty | TryRecvError::Malformed) => missing = true,
+                        Err(TryRecvError::Disconnected) => return false,
+                    }
+                }
+                true
+            });
+            if missing {
+                thread::park();
+                continue;
+            }
+            let next = heads.iter_mut()
+                .min_by_key(|(_,head)| head.as_ref().map(&key))
+                .and_then(|(_,head)| head.take());
+            if next.is_none_or(|t| tx.send(t).is_err()) {
+                return;
+            }
+        }
+    })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{merge,merge_by_key};
+    use crate::mpsc::channel;
+    use crate::RecvError;
+    use std::thread;
+
+    #[test]
+    fn test_merge() {
+        let (txs,rxs): (Vec<_>,Vec<_>) = (0 .. 3).map(|_| channel::<u32>()).unzip();
+        for (n,tx) in txs.iter().enumerate() {
+            for i in 0 .. 3 {
+                tx.send(n as u32 * 10 + i).unwrap();
+            }
+        }
+        let out = merge(rxs);
+        // every source had values queued, so the rounds interleave them
+        let first = (0 .. 3).map(|_| out.recv().unwrap() / 10).collect::<Vec<_>>();
+        assert_eq!(first,vec![0,1,2]);
+        drop(txs);
+        assert_eq!(out.iter().count(),6);
+        assert_eq!(out.recv(),Err(RecvError::Disconnected));
+    }
+
+    #[test]
+    fn test_merge_by_key() {
+        let (atx,arx) = channel();
+        let (btx,brx) = channel();
+        let out = merge_by_key(vec![arx,brx],|v: &(u32,&str)| v.0);
+        let h = thread::spawn(move || {
+            for i in [1,4,5,9] {
+                atx.send((i,"a")).unwrap();
+            }
+        });
+        for i in [2,3,8] {
+            btx.send((i,"b")).unwrap();
+        }
+        drop(btx);
+        let got = out.iter().map(|v| v.0).collect::<Vec<_>>();
+        assert_eq!(got,vec![1,2,3,4,5,8,9]);
+        h.join().unwrap();
+    }
+}
@@ -0,0 +1,87 @@
+use std::sync::OnceLock;
+use std::time::{Duration,Instant};
+
+// hooks called by the oneshot family, every method defaults to doing nothing
+pub trait Metrics: Send + Sync {
+    fn sent(&self) {}
+    fn received(&self, _waited: Duration) {}
+    fn sender_dropped(&self) {}
+    fn timed_out(&self, _waited: Duration) {}
+}
+
+static METRICS: OnceLock<Box<dyn Metrics>> = OnceLock::new();
+
+// can be installed once per process, false if there already was one
+pub fn set_metrics(metrics: impl Metrics + 'static) -> bool {
+    METRICS.set(Box::new(metrics)).is_ok()
+}
+
+pub(crate) fn get() -> Option<&'static dyn Metrics> {
+    METRICS.get().map(|m| &**m)
+}
+
+// only reads the clock when somebody is listening
+pub(crate) fn start() -> Option<Instant> {
+    get().map(|_| Instant::now())
+}
+
+pub(crate) fn received(start: Option<Instant>, got: bool) {
+    if let (Some(m),Some(start)) = (get(),start) {
+        if got {
+            m.received(start.elapsed());
+        }
+    }
+}
+
+pub(crate) fn timed_out(start: Option<Instant>) {
+    if let (Some(m),Some(start)) = (get(),start) {
+        m.timed_out(start.elapsed());
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{set_metrics,Metrics};
+    use crate::oneshot;
+    use std::sync::atomic::{AtomicUsize,Ordering};
+    use std::time::Duration;
+
+    struct Counters;
+    static SENT: AtomicUsize = AtomicUsize::new(0);
+    static RECEIVED: AtomicUsize = AtomicUsize::new(0);
+    static DROPPED: AtomicUsize = AtomicUsize::new(0);
+    static TIMED_OUT: AtomicUsize = AtomicUsize::new(0);
+    impl Metrics for Counters {
+        fn sent(&self) {
+            SENT.fetch_add(1,Ordering::SeqCst);
+        }
+        fn received(&self, _waited: Duration) {
+            RECEIVED.fetch_add(1,Ordering::SeqCst);
+        }
+        fn sender_dropped(&self) {
+            DROPPED.fetch_add(1,Ordering::SeqCst);
+        }
+        fn timed_out(&self, _waited: Duration) {
+            TIMED_OUT.fetch_add(1,Ordering::SeqCst);
+        }
+    }
+
+    // other tests run concurrently in this process, so only check the counters grow
+    #[test]
+    fn test_hooks() {
+        assert!(set_metrics(Counters));
+        assert!(!set_metrics(Counters));
+        let (tx,rx) = oneshot();
+        tx.set(1).unwrap();
+        assert_eq!(rx.wait(),Some(1));
+        assert!(SENT.load(Ordering::SeqCst) >= 1);
+        assert!(RECEIVED.load(Ordering::SeqCst) >= 1);
+        let (tx,rx) = oneshot::<u8>();
+        let rx = rx.wait_timeout(Duration::from_millis(10)).unwrap_err();
+        assert!(TIMED_OUT.load(Ordering::SeqCst) >= 1);
+        drop(tx);
+        assert_eq!(rx.wait(),None);
+        assert!(DROPPED.load(Ordering::SeqCst) >= 1);
+    }
+}
@@ -0,0 +1,388 @@
+use alloc::collections::VecDeque;
+#[cfg(feature = "async")]
+use alloc::vec::Vec;
+use core::fmt::{self,Debug};
+#[cfg(feature = "async")]
+use core::task::{Context,Poll,Waker};
+
+use crate::error::{RecvError,TryRecvError,TrySendError};
+use crate::iter::impl_receiver;
+#[cfg(feature = "std")]
+use crate::strategy::{self,WaitStrategy};
+use crate::transmit::Transmit;
+use crate::sync::{Arc,Condvar,Mutex};
+
+struct Queue<T> {
+    items: VecDeque<T>,
+    capacity: usize,
+    senders: usize,
+    receivers: usize,
+    // closed by a failing user of the channel, both sides see it as disconnected
+    closed: bool,
+    // tasks polling for a value or for room, each registered once
+    #[cfg(feature = "async")]
+    recv_wakers: Vec<Waker>,
+    #[cfg(feature = "async")]
+    send_wakers: Vec<Waker>,
+}
+impl<T> Queue<T> {
+    // who to wake, taken under the lock and woken after it is released: a waker may
+    // poll inline or send on this very channel
+    #[cfg_attr(not(feature = "async"), allow(unused_variables))]
+    fn wakeup(&mut self, receivers: bool, senders: bool) -> Wakeup {
+        #[cfg(feature = "async")]
+        let mut wakers = Vec::new();
+        #[cfg(feature = "async")]
+        if receivers {
+            wakers.append(&mut self.recv_wakers);
+        }
+        #[cfg(feature = "async")]
+        if senders {
+            wakers.append(&mut self.send_wakers);
+        }
+        Wakeup {
+            #[cfg(feature = "async")]
+            wakers,
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+fn register(wakers: &mut Vec<Waker>, waker: &Waker) {
+    if !wakers.iter().any(|w| w.will_wake(waker)) {
+        wakers.push(waker.clone());
+    }
+}
+
+struct Wakeup {
+    #[cfg(feature = "async")]
+    wakers: Vec<Waker>,
+}
+impl Wakeup {
+    fn wake(self) {
+        #[cfg(feature = "async")]
+        for waker in self.wakers {
+            waker.wake();
+        }
+    }
+}
+
+struct Inner<T> {
+    queue: Mutex<Queue<T>>,
+    not_empty: Condvar,
+    not_full: Condvar,
+}
+impl<T> Inner<T> {
+    #[cfg(feature = "std")]
+    fn close(&self) {
+        let mut lock = self.queue.lock();
+        lock.closed = true;
+        let wakeup = lock.wakeup(true,true);
+        drop(lock);
+        self.not_empty.notify_all();
+        self.not_full.notify_all();
+        wakeup.wake();
+    }
+}
+
+pub struct Sender<T>(Arc<Inner<T>>);
+impl<T> Sender<T> {
+    pub fn send(&self, t: T) -> Result<(),T> {
+        let mut lock = self.0.queue.lock();
+        loop {
+            if lock.receivers == 0 || lock.closed {
+                return Err(t);
+            }
+            if lock.items.len() < lock.capacity {
+                lock.items.push_back(t);
+                let wakeup = lock.wakeup(true,false);
+                drop(lock);
+                self.0.not_empty.notify_one();
+                wakeup.wake();
+                return Ok(());
+            }
+            lock = self.0.not_full.wait(lock);
+        }
+    }
+    pub fn try_send(&self, t: T) -> Result<(),TrySendError<T>> {
+        let mut lock = self.0.queue.lock();
+        match () {
+            _ if lock.receivers == 0 || lock.closed => Err(TrySendError::Disconnected(t)),
+            _ if lock.items.len() == lock.capacity => Err(TrySendError::Full(t)),
+            _ => {
+                lock.items.push_back(t);
+                let wakeup = lock.wakeup(true,false);
+                drop(lock);
+                self.0.not_empty.notify_one();
+                wakeup.wake();
+                Ok(())
+            },
+        }
+    }
+    pub fn is_needed(&self) -> bool {
+        let lock = self.0.queue.lock();
+        lock.receivers > 0 && !lock.closed
+    }
+    // ready once there is room or nobody is left to receive, what a Sink's poll_ready
+    // forwards to
+    #[cfg(feature = "async")]
+    pub fn poll_ready(&self, cx: &mut Context<'_>) -> Poll<()> {
+        let mut lock = self.0.queue.lock();
+        if lock.receivers == 0 || lock.closed || lock.items.len() < lock.capacity {
+            return Poll::Ready(());
+        }
+        register(&mut lock.send_wakers,cx.waker());
+        Poll::Pending
+    }
+    // queues past a full channel: clones racing between poll_ready and this exceed the
+    // capacity by one each, as futures' bounded channel does
+    #[cfg(feature = "futures")]
+    pub(crate) fn force_send(&self, t: T) -> Result<(),T> {
+        let mut lock = self.0.queue.lock();
+        if lock.receivers == 0 || lock.closed {
+            return Err(t);
+        }
+        lock.items.push_back(t);
+        let wakeup = lock.wakeup(true,false);
+        drop(lock);
+        self.0.not_empty.notify_one();
+        wakeup.wake();
+        Ok(())
+    }
+    pub fn capacity(&self) -> usize {
+        self.0.queue.lock().capacity
+    }
+    // queued values can still be received
+    #[cfg(feature = "std")]
+    pub(crate) fn close(&self) {
+        self.0.close();
+    }
+}
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Sender<T> {
+        self.0.queue.lock().senders += 1;
+        Sender(self.0.clone())
+    }
+}
+impl<T> Transmit for Sender<T> {
+    type Item = T;
+    fn send(&self, t: T) -> Result<(),T> {
+        Sender::send(self,t)
+    }
+}
+impl<T> Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Sender")
+    }
+}
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut lock = self.0.queue.lock();
+        lock.senders -= 1;
+        if lock.senders == 0 {
+            let wakeup = lock.wakeup(true,false);
+            drop(lock);
+            self.0.not_empty.notify_all();
+            wakeup.wake();
+        }
+    }
+}
+
+pub struct Receiver<T>(Arc<Inner<T>>);
+impl<T> Receiver<T> {
+    // the senders to wake once the lock is released
+    fn pop(&self, lock: &mut Queue<T>) -> Option<(T,Wakeup)> {
+        let t = lock.items.pop_front()?;
+        self.0.not_full.notify_one();
+        Some((t,lock.wakeup(false,true)))
+    }
+    pub fn recv(&self) -> Result<T,RecvError> {
+        #[cfg(feature = "std")]
+        if let Some(res) = strategy::spin_recv(strategy::get(),|| self.try_recv()) {
+            return res;
+        }
+        self.block()
+    }
+    // the strategy replaces the process-wide one for this receive
+    #[cfg(feature = "std")]
+    pub fn recv_with(&self, strategy: &dyn WaitStrategy) -> Result<T,RecvError> {
+        strategy::spin_recv(Some(strategy),|| self.try_recv()).unwrap_or_else(|| self.block())
+    }
+    fn block(&self) -> Result<T,RecvError> {
+        let mut lock = self.0.queue.lock();
+        loop {
+            if let Some((t,wakeup)) = self.pop(&mut lock) {
+                drop(lock);
+                wakeup.wake();
+                return Ok(t);
+            }
+            if lock.senders == 0 || lock.closed {
+                return Err(RecvError::Disconnected);
+            }
+            lock = self.0.not_empty.wait(lock);
+        }
+    }
+    pub fn try_recv(&self) -> Result<T,TryRecvError> {
+        let mut lock = self.0.queue.lock();
+        match self.pop(&mut lock) {
+            Some((t,wakeup)) => {
+                drop(lock);
+                wakeup.wake();
+                Ok(t)
+            },
+            None if lock.senders == 0 || lock.closed => Err(TryRecvError::Disconnected),
+            None => Err(TryRecvError::Empty),
+        }
+    }
+    // what a Stream impl's poll_next forwards to
+    #[cfg(feature = "async")]
+    pub fn poll_recv(&self, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let mut lock = self.0.queue.lock();
+        match self.pop(&mut lock) {
+            Some((t,wakeup)) => {
+                drop(lock);
+                wakeup.wake();
+                Poll::Ready(Some(t))
+            },
+            None if lock.senders == 0 || lock.closed => Poll::Ready(None),
+            None => {
+                register(&mut lock.recv_wakers,cx.waker());
+                Poll::Pending
+            },
+        }
+    }
+    #[cfg(feature = "std")]
+    pub(crate) fn close(&self) {
+        self.0.close();
+    }
+}
+impl<T> Clone for Receiver<T> {
+    fn clone(&self) -> Receiver<T> {
+        self.0.queue.lock().receivers += 1;
+        Receiver(self.0.clone())
+    }
+}
+impl_receiver!(Receiver<T>);
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        let (items,wakeup) = {
+            let mut lock = self.0.queue.lock();
+            lock.receivers -= 1;
+            if lock.receivers > 0 {
+                return;
+            }
+            self.0.not_full.notify_all();
+            (core::mem::take(&mut lock.items),lock.wakeup(false,true))
+        };
+        wakeup.wake();
+        drop(items);
+    }
+}
+
+// values are taken in the order they were sent, waiters are woken one at a time
+pub fn bounded<T>(capacity: usize) -> (Sender<T>,Receiver<T>) {
+    assert!(capacity > 0, "mpmc capacity must be positive");
+    let r = Arc::new(Inner {
+        queue: Mutex::new(Queue {
+            items: VecDeque::with_capacity(capacity),
+            capacity,
+            senders: 1,
+            receivers: 1,
+            closed: false,
+            #[cfg(feature = "async")]
+            recv_wakers: Vec::new(),
+            #[cfg(feature = "async")]
+            send_wakers: Vec::new(),
+        }),
+        not_empty: Condvar::new(),
+        not_full: Condvar::new(),
+    });
+    (Sender(r.clone()),Receiver(r))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::bounded;
+    use crate::error::{RecvError,TryRecvError,TrySendError};
+    use std::thread;
+
+    #[test]
+    fn test_workers() {
+        let (tx,rx) = bounded(4);
+        let producers = (0 .. 3).map(|n| {
+            let tx = tx.clone();
+            thread::spawn(move || {
+                for i in 0 .. 100 {
+                    tx.send(n * 100 + i).unwrap();
+                }
+            })
+        }).collect::<Vec<_>>();
+        drop(tx);
+        let workers = (0 .. 3).map(|_| {
+            let rx = rx.clone();
+            thread::spawn(move || {
+                let mut got = Vec::new();
+                while let Ok(v) = rx.recv() {
+                    got.push(v);
+                }
+                got
+            })
+        }).collect::<Vec<_>>();
+        drop(rx);
+        for h in producers {
+            h.join().unwrap();
+        }
+        let mut all = workers.into_iter().flat_map(|h| h.join().unwrap()).collect::<Vec<_>>();
+        all.sort();
+        assert_eq!(all,(0 .. 300).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_try() {
+        let (tx,rx) = bounded(1);
+        assert_eq!(rx.try_recv(),Err(TryRecvError::Empty));
+        tx.try_send(1).unwrap();
+        assert_eq!(tx.try_send(2),Err(TrySendError::Full(2)));
+        assert_eq!(rx.try_recv(),Ok(1));
+        let rx2 = rx.clone();
+        drop(rx);
+        tx.send(3).unwrap();
+        drop(rx2);
+        assert_eq!(tx.try_send(4),Err(TrySendError::Disconnected(4)));
+        assert_eq!(tx.send(5),Err(5));
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn test_poll() {
+        use std::task::{Context,Poll};
+
+        let waker = crate::select::thread_waker();
+        let mut cx = Context::from_waker(&waker);
+        let (tx,rx) = bounded(1);
+        assert_eq!(rx.poll_recv(&mut cx),Poll::Pending);
+        assert_eq!(tx.poll_ready(&mut cx),Poll::Ready(()));
+        tx.send(1).unwrap();
+        assert_eq!(tx.poll_ready(&mut cx),Poll::Pending);
+        let h = thread::spawn(move || rx.recv());
+        // woken by the receive making room
+        while tx.poll_ready(&mut cx).is_pending() {
+            thread::park();
+        }
+        assert_eq!(h.join().unwrap(),Ok(1));
+        let (tx,rx) = bounded::<u8>(1);
+        let h = thread::spawn(move || drop(tx));
+        while rx.poll_recv(&mut cx).is_pending() {
+            thread::park();
+        }
+        h.join().unwrap();
+    }
+
+    #[test]
+    fn test_disconnect() {
+        let (tx,rx) = bounded::<u8>(2);
+        drop(tx);
+        assert_eq!(rx.recv(),Err(RecvError::Disconnected));
+    }
+}
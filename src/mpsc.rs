@@ -0,0 +1,390 @@
+use alloc::collections::VecDeque;
+use core::fmt::{self,Debug};
+#[cfg(feature = "std")]
+use alloc::sync::Weak;
+#[cfg(feature = "std")]
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::time::{Duration,Instant};
+#[cfg(feature = "async")]
+use core::task::{Context,Poll,Waker};
+
+use crate::error::{RecvError,TryRecvError};
+#[cfg(feature = "std")]
+use crate::iter::Watcher;
+use crate::iter::impl_receiver;
+#[cfg(feature = "std")]
+use crate::strategy::{self,WaitStrategy};
+use crate::transmit::Transmit;
+use crate::sync::{Arc,CachePadded,Condvar,Mutex};
+
+struct Queue<T> {
+    items: VecDeque<T>,
+    senders: usize,
+    receiver: bool,
+    #[cfg(feature = "async")]
+    waker: Option<Waker>,
+    // threads waiting on several receivers, unparked on every send and disconnect;
+    // the ones that stopped waiting are pruned
+    #[cfg(feature = "std")]
+    watchers: Vec<Weak<Watcher>>,
+}
+impl<T> Queue<T> {
+    // who to wake, taken under the lock and woken after it is released: a waker may
+    // poll inline or send on this very channel
+    fn wakeup(&mut self) -> Wakeup {
+        Wakeup {
+            #[cfg(feature = "async")]
+            waker: self.waker.take(),
+            #[cfg(feature = "std")]
+            watchers: {
+                let mut live = Vec::new();
+                self.watchers.retain(|w| match w.upgrade() {
+                    Some(watcher) => {
+                        live.push(watcher);
+                        true
+                    },
+                    None => false,
+                });
+                live
+            },
+        }
+    }
+}
+
+struct Wakeup {
+    #[cfg(feature = "async")]
+    waker: Option<Waker>,
+    #[cfg(feature = "std")]
+    watchers: Vec<Arc<Watcher>>,
+}
+impl Wakeup {
+    fn wake(self) {
+        #[cfg(feature = "async")]
+        if let Some(waker) = self.waker {
+            waker.wake();
+        }
+        #[cfg(feature = "std")]
+        for watcher in self.watchers {
+            watcher.unpark();
+        }
+    }
+}
+
+struct Inner<T> {
+    // off the line with the Arc counts, which every Sender clone and drop writes
+    queue: CachePadded<Mutex<Queue<T>>>,
+    cond: Condvar,
+}
+
+pub struct Sender<T>(Arc<Inner<T>>);
+impl<T> Sender<T> {
+    pub fn send(&self, t: T) -> Result<(),T> {
+        let mut lock = self.0.queue.lock();
+        if !lock.receiver {
+            return Err(t);
+        }
+        lock.items.push_back(t);
+        let wakeup = lock.wakeup();
+        drop(lock);
+        self.0.cond.notify_one();
+        wakeup.wake();
+        Ok(())
+    }
+    pub fn is_needed(&self) -> bool {
+        self.0.queue.lock().receiver
+    }
+}
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Sender<T> {
+        self.0.queue.lock().senders += 1;
+        Sender(self.0.clone())
+    }
+}
+impl<T> Transmit for Sender<T> {
+    type Item = T;
+    fn send(&self, t: T) -> Result<(),T> {
+        Sender::send(self,t)
+    }
+}
+impl<T> Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Sender")
+    }
+}
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut lock = self.0.queue.lock();
+        lock.senders -= 1;
+        if lock.senders == 0 {
+            let wakeup = lock.wakeup();
+            drop(lock);
+            self.0.cond.notify_one();
+            wakeup.wake();
+        }
+    }
+}
+
+pub struct Receiver<T>(Arc<Inner<T>>);
+impl<T> Receiver<T> {
+    pub fn recv(&self) -> Result<T,RecvError> {
+        #[cfg(feature = "std")]
+        if let Some(res) = strategy::spin_recv(strategy::get(),|| self.try_recv()) {
+            return res;
+        }
+        self.block()
+    }
+    // the strategy replaces the process-wide one for this receive
+    #[cfg(feature = "std")]
+    pub fn recv_with(&self, strategy: &dyn WaitStrategy) -> Result<T,RecvError> {
+        strategy::spin_recv(Some(strategy),|| self.try_recv()).unwrap_or_else(|| self.block())
+    }
+    fn block(&self) -> Result<T,RecvError> {
+        let mut lock = self.0.queue.lock();
+        loop {
+            if let Some(t) = lock.items.pop_front() {
+                return Ok(t);
+            }
+            if lock.senders == 0 {
+                return Err(RecvError::Disconnected);
+            }
+            lock = self.0.cond.wait(lock);
+        }
+    }
+    #[cfg(feature = "std")]
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<T,RecvError> {
+        let deadline = Instant::now().checked_add(timeout);
+        let mut lock = self.0.queue.lock();
+        loop {
+            if let Some(t) = lock.items.pop_front() {
+                return Ok(t);
+            }
+            if lock.senders == 0 {
+                return Err(RecvError::Disconnected);
+            }
+            let (guard,timed_out) = self.0.cond.wait_deadline(lock,deadline);
+            lock = guard;
+            if timed_out && lock.items.is_empty() {
+                return Err(RecvError::Timeout);
+            }
+        }
+    }
+    pub fn try_recv(&self) -> Result<T,TryRecvError> {
+        let mut lock = self.0.queue.lock();
+        match lock.items.pop_front() {
+            Some(t) => Ok(t),
+            None if lock.senders == 0 => Err(TryRecvError::Disconnected),
+            None => Err(TryRecvError::Empty),
+        }
+    }
+    // what a Stream impl's poll_next forwards to
+    #[cfg(feature = "async")]
+    pub fn poll_recv(&self, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let mut lock = self.0.queue.lock();
+        match lock.items.pop_front() {
+            Some(t) => Poll::Ready(Some(t)),
+            None if lock.senders == 0 => Poll::Ready(None),
+            None => {
+                lock.waker = Some(cx.waker().clone());
+                Poll::Pending
+            },
+        }
+    }
+    #[cfg(feature = "std")]
+    pub(crate) fn watch(&self, watcher: &Arc<Watcher>) {
+        let mut lock = self.0.queue.lock();
+        lock.watchers.retain(|w| w.strong_count() > 0);
+        if !lock.watchers.iter().any(|w| w.as_ptr() == Arc::as_ptr(watcher)) {
+            lock.watchers.push(Arc::downgrade(watcher));
+        }
+    }
+}
+impl_receiver!(Receiver<T>,
+    #[cfg(feature = "std")]
+    fn watch(&self, watcher: &Arc<Watcher>) -> bool {
+        Receiver::watch(self,watcher);
+        true
+    }
+);
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        let items = {
+            let mut lock = self.0.queue.lock();
+            lock.receiver = false;
+            core::mem::take(&mut lock.items)
+        };
+        drop(items);
+    }
+}
+
+pub fn channel<T>() -> (Sender<T>,Receiver<T>) {
+    let r = Arc::new(Inner {
+        queue: CachePadded(Mutex::new(Queue {
+            items: VecDeque::new(),
+            senders: 1,
+            receiver: true,
+            #[cfg(feature = "async")]
+            waker: None,
+            #[cfg(feature = "std")]
+            watchers: Vec::new(),
+        })),
+        cond: Condvar::new(),
+    });
+    (Sender(r.clone()),Receiver(r))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::channel;
+    use crate::error::{RecvError,TryRecvError};
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_send_recv() {
+        let (tx,rx) = channel();
+        let h = thread::spawn(move || {
+            for i in 0 .. 10 {
+                tx.send(i).unwrap();
+                thread::sleep(Duration::from_millis(10));
+            }
+        });
+        for i in 0 .. 10 {
+            assert_eq!(rx.recv(),Ok(i));
+        }
+        assert_eq!(rx.recv(),Err(RecvError::Disconnected));
+        h.join().unwrap();
+    }
+
+    #[test]
+    fn test_many_senders() {
+        let (tx,rx) = channel();
+        let hs = (0 .. 4).map(|n| {
+            let tx = tx.clone();
+            thread::spawn(move || {
+                for i in 0 .. 100 {
+                    tx.send(n * 100 + i).unwrap();
+                }
+            })
+        }).collect::<Vec<_>>();
+        drop(tx);
+        let mut all = Vec::new();
+        while let Ok(v) = rx.recv() {
+            all.push(v);
+        }
+        all.sort();
+        assert_eq!(all,(0 .. 400).collect::<Vec<_>>());
+        for h in hs {
+            h.join().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_try_recv() {
+        let (tx,rx) = channel();
+        assert_eq!(rx.try_recv(),Err(TryRecvError::Empty));
+        tx.send(1).unwrap();
+        drop(tx);
+        assert_eq!(rx.try_recv(),Ok(1));
+        assert_eq!(rx.try_recv(),Err(TryRecvError::Disconnected));
+    }
+
+    #[test]
+    fn test_drop_receiver() {
+        let (tx,rx) = channel();
+        drop(rx);
+        assert!(!tx.is_needed());
+        assert_eq!(tx.send(2),Err(2));
+    }
+
+    #[test]
+    fn test_recv_timeout() {
+        let (tx,rx) = channel();
+        assert_eq!(rx.recv_timeout(Duration::from_millis(10)),Err(RecvError::Timeout));
+        tx.send(1).unwrap();
+        assert_eq!(rx.recv_timeout(Duration::from_millis(10)),Ok(1));
+        drop(tx);
+        assert_eq!(rx.recv_timeout(Duration::from_millis(10)),Err(RecvError::Disconnected));
+    }
+
+    #[test]
+    fn test_iter() {
+        let (tx,rx) = channel();
+        for i in 0 .. 3 {
+            tx.send(i).unwrap();
+        }
+        assert_eq!(rx.try_iter().collect::<Vec<_>>(),vec![0,1,2]);
+        let h = thread::spawn(move || {
+            for i in 3 .. 6 {
+                tx.send(i).unwrap();
+            }
+        });
+        assert_eq!(rx.into_iter().collect::<Vec<_>>(),vec![3,4,5]);
+        h.join().unwrap();
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn test_poll_recv() {
+        use std::task::{Context,Poll};
+
+        let waker = crate::select::thread_waker();
+        let mut cx = Context::from_waker(&waker);
+        let (tx,rx) = channel();
+        assert_eq!(rx.poll_recv(&mut cx),Poll::Pending);
+        let h = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            tx.send(1).unwrap();
+        });
+        let mut got = Vec::new();
+        loop {
+            match rx.poll_recv(&mut cx) {
+                Poll::Ready(Some(v)) => got.push(v),
+                Poll::Ready(None) => break,
+                Poll::Pending => thread::park(),
+            }
+        }
+        assert_eq!(got,vec![1]);
+        h.join().unwrap();
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn test_waker_sends() {
+        use std::sync::{Arc,Mutex};
+        use std::task::{Context,Poll,Wake};
+
+        // wakes by sending on the channel it is registered with
+        struct Echo(Mutex<Option<super::Sender<u8>>>);
+        impl Wake for Echo {
+            fn wake(self: Arc<Self>) {
+                if let Some(tx) = self.0.lock().unwrap().take() {
+                    tx.send(2).unwrap();
+                }
+            }
+        }
+        let (tx,rx) = channel();
+        let waker = Arc::new(Echo(Mutex::new(Some(tx.clone())))).into();
+        let mut cx = Context::from_waker(&waker);
+        assert_eq!(rx.poll_recv(&mut cx),Poll::Pending);
+        tx.send(1).unwrap();
+        assert_eq!(rx.try_iter().collect::<Vec<_>>(),vec![1,2]);
+    }
+
+    #[test]
+    fn test_watchers() {
+        let (tx,rx) = channel::<u8>();
+        let (a,b) = (crate::iter::Watcher::current(),crate::iter::Watcher::current());
+        rx.watch(&a);
+        rx.watch(&a);
+        rx.watch(&b);
+        assert_eq!(rx.0.queue.lock().watchers.len(),2);
+        drop(a);
+        tx.send(1).unwrap();
+        assert_eq!(rx.0.queue.lock().watchers.len(),1);
+        drop(b);
+        tx.send(2).unwrap();
+        assert!(rx.0.queue.lock().watchers.is_empty());
+    }
+}
@@ -0,0 +1,206 @@
+use alloc::vec::Vec;
+use core::fmt::{self,Debug};
+#[cfg(feature = "std")]
+use std::time::{Duration,Instant};
+
+use crate::sync::{Arc,Mutex};
+#[cfg(feature = "std")]
+use crate::timer;
+#[cfg(feature = "std")]
+use crate::Elapsed;
+use crate::{oneshot,OneGet,OneSet,State};
+
+struct Setter<T> {
+    tx: Option<OneSet<T>>,
+    // taken by a deadline instead of a set
+    expired: bool,
+}
+
+// the inner setter is dropped, closing the channel, once the last clone goes away
+pub struct MultiSet<T>(Arc<Mutex<Setter<T>>>);
+impl<T> MultiSet<T> {
+    pub fn is_needed(&self) -> bool {
+        match &self.0.lock().tx {
+            Some(tx) => tx.is_needed(),
+            None => false,
+        }
+    }
+    pub fn state(&self) -> State {
+        let setter = self.0.lock();
+        match &setter.tx {
+            Some(tx) => tx.state(),
+            // the deadline closed the channel, the receiver gets RecvError::Timeout
+            None if setter.expired => State::SenderDropped,
+            // completed by another setter, the value is on its way or taken
+            None => State::Ready,
+        }
+    }
+    pub fn set(self, t: T) -> Result<(),T> {
+        let tx = self.0.lock().tx.take();
+        match tx {
+            Some(tx) => tx.set(t),
+            None => Err(t),
+        }
+    }
+}
+impl<T> Clone for MultiSet<T> {
+    fn clone(&self) -> MultiSet<T> {
+        MultiSet(self.0.clone())
+    }
+}
+impl<T> Debug for MultiSet<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "MultiSet")
+    }
+}
+
+pub fn multi_oneshot<T>() -> (MultiSet<T>,OneGet<T>) {
+    let (tx,rx) = oneshot();
+    (MultiSet(Arc::new(Mutex::new(Setter { tx: Some(tx), expired: false }))),rx)
+}
+
+// one set completes every receiver, the last one gets the original value
+pub fn fan_out<T: Clone + Send + 'static>(n: usize) -> (OneSet<T>,Vec<OneGet<T>>) {
+    let (tx,rx) = oneshot::<T>();
+    let (txs,rxs): (Vec<OneSet<T>>,Vec<_>) = (0 .. n).map(|_| oneshot()).unzip();
+    rx.on_ready(move |res| if let Some(t) = res {
+        let mut txs = txs.into_iter().filter(|tx| tx.is_needed()).collect::<Vec<_>>();
+        if let Some(last) = txs.pop() {
+            for tx in txs {
+                let _ = tx.set(t.clone());
+            }
+            let _ = last.set(t);
+        }
+    });
+    (tx,rxs)
+}
+
+// the timer races the setters for the inner setter, the receiver sees RecvError::Timeout if it wins
+#[cfg(feature = "std")]
+pub fn oneshot_with_ttl<T: Send + 'static>(ttl: Duration) -> (MultiSet<T>,OneGet<T>) {
+    let (tx,rx) = multi_oneshot();
+    if let Some(deadline) = Instant::now().checked_add(ttl) {
+        let weak = Arc::downgrade(&tx.0);
+        timer::schedule(deadline,Box::new(move || {
+            let Some(setter) = weak.upgrade() else { return };
+            let mut setter = setter.lock();
+            if let Some(tx) = setter.tx.take() {
+                // under the lock, so state never sees the setter gone but not expired
+                setter.expired = true;
+                drop(setter);
+                tx.expire();
+            }
+        }));
+    }
+    (tx,rx)
+}
+
+// the input's callback holds the only strong setter, a dropped input sender closes the
+// output right away instead of at the deadline; a deadline that wins drops the callback,
+// and the input receiver with it, so the input sender sees it's no longer needed
+#[cfg(feature = "std")]
+impl<T: Send + 'static> OneGet<T> {
+    pub fn with_timeout(self, timeout: Duration) -> OneGet<Result<T,Elapsed>> {
+        let (tx,rx) = multi_oneshot();
+        let weak = Arc::downgrade(&tx.0);
+        let input = Arc::downgrade(&self.0);
+        // registered before the timer can fire, unregister finds the callback in place
+        self.on_ready(move |res| if let Some(t) = res {
+            let _ = tx.set(Ok(t));
+        });
+        if let Some(deadline) = Instant::now().checked_add(timeout) {
+            timer::schedule(deadline,Box::new(move || {
+                let tx = weak.upgrade().and_then(|tx| tx.lock().tx.take());
+                if let Some(tx) = tx {
+                    let _ = tx.set(Err(Elapsed));
+                    if let Some(input) = input.upgrade() {
+                        input.unregister();
+                    }
+                }
+            }));
+        }
+        rx
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{fan_out,multi_oneshot,oneshot_with_ttl};
+    use crate::{oneshot,Elapsed,RecvError,State};
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_first_write_wins() {
+        let (tx,rx) = multi_oneshot();
+        let hs = (0 .. 4u64).map(|i| {
+            let tx = tx.clone();
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(i * 50));
+                tx.set(i).is_ok()
+            })
+        }).collect::<Vec<_>>();
+        drop(tx);
+        assert_eq!(rx.wait(),Some(0));
+        let won = hs.into_iter().map(|h| h.join().unwrap()).collect::<Vec<_>>();
+        assert_eq!(won,vec![true,false,false,false]);
+    }
+
+    #[test]
+    fn test_all_setters_dropped() {
+        let (tx,rx) = multi_oneshot::<u8>();
+        let tx2 = tx.clone();
+        drop(tx);
+        assert!(tx2.is_needed());
+        drop(tx2);
+        assert_eq!(rx.wait(),None);
+    }
+
+    #[test]
+    fn test_ttl() {
+        let (tx,rx) = oneshot_with_ttl::<u64>(Duration::from_millis(50));
+        assert_eq!(rx.recv(),Err(RecvError::Timeout));
+        assert!(!tx.is_needed());
+        assert_eq!(tx.state(),State::SenderDropped);
+        assert_eq!(tx.set(1),Err(1));
+        let (tx,rx) = oneshot_with_ttl(Duration::from_secs(5));
+        tx.set(2).unwrap();
+        assert_eq!(rx.recv(),Ok(2));
+        let (tx,rx) = oneshot_with_ttl::<u64>(Duration::from_secs(5));
+        drop(tx);
+        assert_eq!(rx.recv(),Err(RecvError::Disconnected));
+    }
+
+    #[test]
+    fn test_fan_out() {
+        let (tx,mut rxs) = fan_out(3);
+        drop(rxs.pop());
+        let hs = rxs.into_iter().map(|rx| thread::spawn(move || rx.wait())).collect::<Vec<_>>();
+        tx.set(String::from("shared")).unwrap();
+        for h in hs {
+            assert_eq!(h.join().unwrap().as_deref(),Some("shared"));
+        }
+        let (tx,rxs) = fan_out::<u8>(2);
+        drop(tx);
+        assert!(rxs.into_iter().all(|rx| rx.wait().is_none()));
+    }
+
+    #[test]
+    fn test_with_timeout() {
+        let (tx,rx) = oneshot::<u8>();
+        let rx = rx.with_timeout(Duration::from_millis(20));
+        assert_eq!(rx.wait(),Some(Err(Elapsed)));
+        // the expired input's receiver is gone
+        assert!(!tx.is_needed());
+        assert_eq!(tx.set(1),Err(1));
+        let (tx,rx) = oneshot();
+        let rx = rx.with_timeout(Duration::from_secs(5)).map(|res| res.map(|v| v + 1));
+        tx.set(1).unwrap();
+        assert_eq!(rx.wait(),Some(Ok(2)));
+        let (tx,rx) = oneshot::<u8>();
+        let rx = rx.with_timeout(Duration::from_secs(5));
+        drop(tx);
+        assert_eq!(rx.wait(),None);
+    }
+}
@@ -0,0 +1,125 @@
+use std::io;
+use std::thread;
+
+#[cfg(feature = "serde")]
+use serde::{de::DeserializeOwned,Serialize};
+
+use crate::transport::Transport;
+#[cfg(feature = "serde")]
+use crate::wire;
+use crate::wire::Wire;
+use crate::{oneshot,OneGet,OneSet};
+
+// the value is written on a thread of its own, so set never blocks on the transport;
+// dropping the setter shuts the transport down, which the remote receiver sees as a
+// dropped sender
+pub fn oneshot_set<T: Wire + Send + 'static>(transport: impl Transport) -> io::Result<OneSet<T>> {
+    set_with(transport,T::encode)
+}
+
+// a reader thread completes the OneGet, a closed transport or a bad frame closes it
+pub fn oneshot_get<T: Wire + Send + 'static>(transport: impl Transport) -> io::Result<OneGet<T>> {
+    get_with(transport,T::decode)
+}
+
+// the same for any serde type, framed in bincode
+#[cfg(feature = "serde")]
+pub fn serde_oneshot_set<T: Serialize + DeserializeOwned + Send + 'static>(transport: impl Transport) -> io::Result<OneSet<T>> {
+    set_with(transport,wire::bincode_encode)
+}
+
+#[cfg(feature = "serde")]
+pub fn serde_oneshot_get<T: Serialize + DeserializeOwned + Send + 'static>(transport: impl Transport) -> io::Result<OneGet<T>> {
+    get_with(transport,wire::bincode_decode)
+}
+
+fn set_with<T: Send + 'static>(mut transport: impl Transport, encode: fn(&T,&mut Vec<u8>)) -> io::Result<OneSet<T>> {
+    let (tx,rx) = oneshot::<T>();
+    thread::Builder::new()
+        .name("transmitter-net".to_string())
+        .spawn(move || {
+            if let Some(t) = rx.wait() {
+                let mut frame = Vec::new();
+                encode(&t,&mut frame);
+                let _ = transport.write_frame(&frame);
+            }
+            let _ = transport.shutdown();
+        })?;
+    Ok(tx)
+}
+
+fn get_with<T: Send + 'static>(mut transport: impl Transport, decode: fn(&[u8]) -> Option<T>) -> io::Result<OneGet<T>> {
+    let (tx,rx) = oneshot();
+    thread::Builder::new()
+        .name("transmitter-net".to_string())
+        .spawn(move || {
+            if let Some(t) = transport.read_frame().ok().flatten().and_then(|frame| decode(&frame)) {
+                let _ = tx.set(t);
+            }
+        })?;
+    Ok(rx)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{oneshot_get,oneshot_set};
+    use crate::transport::{self,Transport};
+    use std::net::{TcpListener,TcpStream};
+    use std::time::Duration;
+
+    fn pair() -> (TcpStream,TcpStream) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        (client,listener.accept().unwrap().0)
+    }
+
+    #[test]
+    fn test_remote_oneshot() {
+        let (a,b) = pair();
+        let tx = oneshot_set::<String>(a).unwrap();
+        let rx = oneshot_get::<String>(b).unwrap();
+        tx.set(String::from("over tcp")).unwrap();
+        assert_eq!(rx.wait().as_deref(),Some("over tcp"));
+        let (a,b) = pair();
+        drop(oneshot_set::<u64>(a).unwrap());
+        assert_eq!(oneshot_get::<u64>(b).unwrap().wait(),None);
+    }
+
+    #[test]
+    fn test_memory_oneshot() {
+        let (a,b) = transport::memory();
+        let rx = oneshot_get::<bool>(b).unwrap();
+        oneshot_set::<bool>(a).unwrap().set(true).unwrap();
+        assert_eq!(rx.wait(),Some(true));
+    }
+
+    // never takes a frame, a write on the setting thread would hang the test
+    struct Stuck;
+    impl Transport for Stuck {
+        fn write_frame(&mut self, _frame: &[u8]) -> std::io::Result<()> {
+            std::thread::sleep(Duration::from_secs(3600));
+            Ok(())
+        }
+        fn read_frame(&mut self) -> std::io::Result<Option<Vec<u8>>> {
+            Ok(None)
+        }
+        fn shutdown(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_set_does_not_block() {
+        oneshot_set::<u8>(Stuck).unwrap().set(1).unwrap();
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_oneshot() {
+        let (a,b) = pair();
+        let rx = super::serde_oneshot_get::<Vec<(u8,String)>>(b).unwrap();
+        super::serde_oneshot_set::<Vec<(u8,String)>>(a).unwrap().set(vec![(1,String::from("one"))]).unwrap();
+        assert_eq!(rx.wait(),Some(vec![(1,String::from("one"))]));
+    }
+}
@@ -0,0 +1,190 @@
+use std::fmt::{self,Debug};
+use std::thread;
+
+use crate::error::{RecvError,TryRecvError};
+use crate::iter::impl_receiver;
+use crate::mpmc;
+use crate::sync::{Arc,AtomicBool,Ordering};
+use crate::transmit::Transmit;
+
+const DEFAULT_CAPACITY: usize = 64;
+
+// stages of worker threads joined by bounded mpmc channels
+pub struct Pipeline<I,O> {
+    capacity: usize,
+    input: mpmc::Sender<I>,
+    output: mpmc::Receiver<O>,
+    failed: Arc<AtomicBool>,
+}
+impl<I> Pipeline<I,I> {
+    pub fn new() -> Pipeline<I,I> {
+        Pipeline::with_capacity(DEFAULT_CAPACITY)
+    }
+    // the capacity of every channel between stages
+    pub fn with_capacity(capacity: usize) -> Pipeline<I,I> {
+        let (input,output) = mpmc::bounded(capacity);
+        Pipeline { capacity, input, output, failed: Arc::new(AtomicBool::new(false)) }
+    }
+}
+impl<I> Default for Pipeline<I,I> {
+    fn default() -> Pipeline<I,I> {
+        Pipeline::new()
+    }
+}
+impl<I: Send + 'static, O: Send + 'static> Pipeline<I,O> {
+    pub fn stage<U,F>(self, threads: usize, f: F) -> Pipeline<I,U>
+    where U: Send + 'static, F: Fn(O) -> U + Send + Sync + 'static
+    {
+        assert!(threads > 0, "pipeline stage needs a thread");
+        let (tx,rx) = mpmc::bounded(self.capacity);
+        let f = Arc::new(f);
+        for _ in 0 .. threads {
+            let stage = Stage { rx: self.output.clone(), tx: tx.clone(), failed: self.failed.clone() };
+            let f = f.clone();
+            thread::spawn(move || {
+                while let Ok(v) = stage.rx.recv() {
+                    if stage.failed.load(Ordering::Acquire) || stage.tx.send(f(v)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        Pipeline { capacity: self.capacity, input: self.input, output: rx, failed: self.failed }
+    }
+    // dropping every Input drains the stages and then disconnects the Output,
+    // dropping the Output stops the stages at their next send
+    pub fn build(self) -> (Input<I>,Output<O>) {
+        (Input { tx: self.input, failed: self.failed.clone() },Output { rx: self.output, failed: self.failed })
+    }
+}
+impl<I,O> Debug for Pipeline<I,O> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Pipeline")
+    }
+}
+
+struct Stage<A,B> {
+    rx: mpmc::Receiver<A>,
+    tx: mpmc::Sender<B>,
+    failed: Arc<AtomicBool>,
+}
+impl<A,B> Drop for Stage<A,B> {
+    fn drop(&mut self) {
+        // a panicking worker fails the whole pipeline: the neighbouring channels are
+        // closed so the other stages wind down instead of waiting on it
+        if thread::panicking() {
+            self.failed.store(true,Ordering::Release);
+            self.rx.close();
+            self.tx.close();
+        }
+    }
+}
+
+pub struct Input<I> {
+    tx: mpmc::Sender<I>,
+    failed: Arc<AtomicBool>,
+}
+impl<I> Input<I> {
+    // blocks while the first stage is full, the value comes back once the pipeline failed or stopped
+    pub fn send(&self, t: I) -> Result<(),I> {
+        if self.failed.load(Ordering::Acquire) {
+            return Err(t);
+        }
+        self.tx.send(t)
+    }
+}
+impl<I> Clone for Input<I> {
+    fn clone(&self) -> Input<I> {
+        Input { tx: self.tx.clone(), failed: self.failed.clone() }
+    }
+}
+impl<I> Transmit for Input<I> {
+    type Item = I;
+    fn send(&self, t: I) -> Result<(),I> {
+        Input::send(self,t)
+    }
+}
+impl<I> Debug for Input<I> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Input")
+    }
+}
+
+// values of a stage with several threads arrive in completion order
+pub struct Output<O> {
+    rx: mpmc::Receiver<O>,
+    failed: Arc<AtomicBool>,
+}
+impl<O> Output<O> {
+    // RecvError::SenderPanicked once the values are drained from a failed pipeline
+    pub fn recv(&self) -> Result<O,RecvError> {
+        match self.rx.recv() {
+            Err(RecvError::Disconnected) if self.failed.load(Ordering::Acquire) => Err(RecvError::SenderPanicked),
+            res => res,
+        }
+    }
+    pub fn try_recv(&self) -> Result<O,TryRecvError> {
+        self.rx.try_recv()
+    }
+}
+impl_receiver!(Output<O>);
+
+
+#[cfg(test)]
+mod tests {
+    use super::Pipeline;
+    use crate::RecvError;
+    use std::thread;
+
+    #[test]
+    fn test_stages() {
+        let (input,output) = Pipeline::<u32,u32>::with_capacity(2)
+            .stage(3,|v| v * 2)
+            .stage(1,|v| v.to_string())
+            .build();
+        let h = thread::spawn(move || {
+            for i in 0 .. 50 {
+                input.send(i).unwrap();
+            }
+        });
+        let mut got = output.iter().map(|s| s.parse::<u32>().unwrap()).collect::<Vec<_>>();
+        got.sort();
+        assert_eq!(got,(0 .. 50).map(|v| v * 2).collect::<Vec<_>>());
+        h.join().unwrap();
+    }
+
+    #[test]
+    fn test_panic() {
+        let (input,output) = Pipeline::<u32,u32>::new()
+            .stage(2,|v| if v == 3 { panic!("stage failed") } else { v })
+            .stage(2,|v| v + 1)
+            .build();
+        for i in 0 .. 4 {
+            input.send(i).unwrap();
+        }
+        loop {
+            match output.recv() {
+                Ok(_) => {},
+                Err(e) => {
+                    assert_eq!(e,RecvError::SenderPanicked);
+                    break;
+                },
+            }
+        }
+        assert_eq!(input.send(9),Err(9));
+    }
+
+    #[test]
+    fn test_output_dropped() {
+        let (input,output) = Pipeline::<u8,u8>::with_capacity(1).stage(1,|v| v).build();
+        drop(output);
+        let mut stopped = false;
+        for i in 0 .. 10 {
+            if input.send(i).is_err() {
+                stopped = true;
+                break;
+            }
+        }
+        assert!(stopped);
+    }
+}
@@ -0,0 +1,294 @@
+use alloc::sync::Weak;
+use alloc::vec::Vec;
+use core::fmt::{self,Debug};
+use core::mem;
+use core::ops::{Deref,DerefMut};
+#[cfg(feature = "async")]
+use core::future::Future;
+#[cfg(feature = "async")]
+use core::pin::Pin;
+#[cfg(feature = "async")]
+use core::task::{Context,Poll};
+
+use crate::sync::{Arc,Mutex};
+#[cfg(feature = "std")]
+use crate::diag;
+use crate::{InnerOne,OneGet,OneSet};
+
+// a lent channel's place in the pool; its handle count only moves for releases of
+// the generation it was lent under, so a late release never counts against a reuse
+struct Entry<T> {
+    generation: u32,
+    handles: u8,
+    // the channel, parked here by the last handle to go
+    idle: Option<Arc<InnerOne<T>>>,
+}
+
+struct Entries<T> {
+    lent: Vec<Entry<T>>,
+    idle: Vec<usize>,
+}
+
+pub(crate) struct Slab<T> {
+    capacity: usize,
+    entries: Mutex<Entries<T>>,
+}
+
+// what a pooled handle needs to give its channel back; a pair lent past the capacity
+// has no home and is freed as usual
+struct Lease<T> {
+    home: Weak<Slab<T>>,
+    index: usize,
+    generation: u32,
+}
+impl<T> Lease<T> {
+    // r is the released handle's channel, the handle itself is already dropped; r
+    // outlives the lock guard, a stale one is freed after it
+    fn release(&self, r: Arc<InnerOne<T>>) {
+        let Some(slab) = self.home.upgrade() else { return };
+        let mut entries = slab.entries.lock();
+        let Entries { lent, idle } = &mut *entries;
+        let entry = &mut lent[self.index];
+        if entry.generation != self.generation {
+            return;
+        }
+        entry.handles -= 1;
+        if entry.handles == 0 {
+            entry.idle = Some(r);
+            idle.push(self.index);
+        }
+    }
+}
+
+pub struct PooledSet<T>(Option<OneSet<T>>,Lease<T>);
+impl<T> PooledSet<T> {
+    fn take(&mut self) -> (OneSet<T>,Arc<InnerOne<T>>) {
+        let tx = self.0.take().expect("pooled handle");
+        let r = tx.0.clone();
+        (tx,r)
+    }
+    pub fn set(mut self, t: T) -> Result<(),T> {
+        let (tx,r) = self.take();
+        let res = tx.set(t);
+        self.1.release(r);
+        res
+    }
+    // the channel is not recycled after a handle was taken out of the pool
+    pub fn into_inner(mut self) -> OneSet<T> {
+        let (tx,r) = self.take();
+        self.1.release(r);
+        tx
+    }
+}
+impl<T> Deref for PooledSet<T> {
+    type Target = OneSet<T>;
+    fn deref(&self) -> &OneSet<T> {
+        self.0.as_ref().expect("pooled handle")
+    }
+}
+impl<T> Debug for PooledSet<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "PooledSet")
+    }
+}
+impl<T> Drop for PooledSet<T> {
+    fn drop(&mut self) {
+        if self.0.is_some() {
+            let (tx,r) = self.take();
+            drop(tx);
+            self.1.release(r);
+        }
+    }
+}
+
+pub struct PooledGet<T>(Option<OneGet<T>>,Lease<T>);
+impl<T> PooledGet<T> {
+    fn take(&mut self) -> (OneGet<T>,Arc<InnerOne<T>>) {
+        let rx = self.0.take().expect("pooled handle");
+        let r = rx.0.clone();
+        (rx,r)
+    }
+    #[cfg(not(poll_only))]
+    pub fn wait(mut self) -> Option<T> {
+        let (rx,r) = self.take();
+        let res = rx.wait();
+        self.1.release(r);
+        res
+    }
+    pub fn try_get(mut self) -> Result<Option<T>,PooledGet<T>> {
+        let (rx,r) = self.take();
+        match rx.try_get() {
+            Ok(res) => {
+                self.1.release(r);
+                Ok(res)
+            },
+            Err(rx) => {
+                self.0 = Some(rx);
+                Err(self)
+            },
+        }
+    }
+    // the channel is not recycled after a handle was taken out of the pool
+    pub fn into_inner(mut self) -> OneGet<T> {
+        let (rx,r) = self.take();
+        self.1.release(r);
+        rx
+    }
+}
+impl<T> Deref for PooledGet<T> {
+    type Target = OneGet<T>;
+    fn deref(&self) -> &OneGet<T> {
+        self.0.as_ref().expect("pooled handle")
+    }
+}
+impl<T> DerefMut for PooledGet<T> {
+    fn deref_mut(&mut self) -> &mut OneGet<T> {
+        self.0.as_mut().expect("pooled handle")
+    }
+}
+#[cfg(feature = "async")]
+impl<T> Future for PooledGet<T> {
+    type Output = Option<T>;
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        Pin::new(&mut **self).poll(cx)
+    }
+}
+impl<T> Debug for PooledGet<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "PooledGet")
+    }
+}
+impl<T> Drop for PooledGet<T> {
+    fn drop(&mut self) {
+        if self.0.is_some() {
+            let (rx,r) = self.take();
+            drop(rx);
+            self.1.release(r);
+        }
+    }
+}
+
+// keeps up to `capacity` channels; one goes back once both of its pooled handles are
+// dropped and is handed out again under the next generation
+pub struct OneshotPool<T>(Arc<Slab<T>>);
+impl<T> OneshotPool<T> {
+    pub fn new(capacity: usize) -> OneshotPool<T> {
+        OneshotPool(Arc::new(Slab {
+            capacity,
+            entries: Mutex::new(Entries { lent: Vec::with_capacity(capacity), idle: Vec::with_capacity(capacity) }),
+        }))
+    }
+    pub fn oneshot(&self) -> (PooledSet<T>,PooledGet<T>) {
+        let mut entries = self.0.entries.lock();
+        let (lease,reused) = match entries.idle.pop() {
+            Some(index) => {
+                let entry = &mut entries.lent[index];
+                entry.generation = entry.generation.wrapping_add(1);
+                entry.handles = 2;
+                (Lease { home: Arc::downgrade(&self.0), index, generation: entry.generation },entry.idle.take())
+            },
+            None if entries.lent.len() < self.0.capacity => {
+                entries.lent.push(Entry { generation: 0, handles: 2, idle: None });
+                (Lease { home: Arc::downgrade(&self.0), index: entries.lent.len() - 1, generation: 0 },None)
+            },
+            None => (Lease { home: Weak::new(), index: 0, generation: 0 },None),
+        };
+        drop(entries);
+        let r = match reused {
+            Some(mut r) => match Arc::get_mut(&mut r) {
+                Some(inner) => {
+                    // drops a leftover value outside of the pool lock
+                    drop(mem::replace(inner,InnerOne::new()));
+                    r
+                },
+                // a handle taken out with into_inner still holds it
+                None => Arc::new(InnerOne::new()),
+            },
+            None => Arc::new(InnerOne::new()),
+        };
+        #[cfg(feature = "std")]
+        diag::created(&*r as *const InnerOne<T> as usize);
+        let other = Lease { home: lease.home.clone(), index: lease.index, generation: lease.generation };
+        (PooledSet(Some(OneSet(r.clone(),false)),lease),PooledGet(Some(OneGet(r)),other))
+    }
+    // channels ready for reuse
+    pub fn idle(&self) -> usize {
+        self.0.entries.lock().idle.len()
+    }
+}
+impl<T> Debug for OneshotPool<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "OneshotPool")
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{Lease,OneshotPool};
+    use crate::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn test_reuse() {
+        let pool = OneshotPool::new(2);
+        let (tx,rx) = pool.oneshot();
+        tx.set(vec![1]).unwrap();
+        let first = Arc::as_ptr(&rx.0.as_ref().unwrap().0);
+        assert_eq!(pool.idle(),0);
+        drop(rx);
+        assert_eq!(pool.idle(),1);
+        let (tx,rx) = pool.oneshot();
+        assert_eq!(Arc::as_ptr(&rx.0.as_ref().unwrap().0),first);
+        assert_eq!(rx.1.generation,1);
+        assert!(!rx.is_ready());
+        let h = thread::spawn(move || tx.set(vec![2]));
+        assert_eq!(rx.wait(),Some(vec![2]));
+        h.join().unwrap().unwrap();
+    }
+
+    #[test]
+    fn test_capacity() {
+        let pool = OneshotPool::<u8>::new(1);
+        let a = pool.oneshot();
+        let b = pool.oneshot();
+        drop((a,b));
+        assert_eq!(pool.idle(),1);
+        let _ = pool.oneshot();
+        assert_eq!(pool.idle(),1);
+    }
+
+    #[test]
+    fn test_stale_generation() {
+        let pool = OneshotPool::<u8>::new(1);
+        let (tx,rx) = pool.oneshot();
+        let stale = Lease { home: tx.1.home.clone(), index: 0, generation: 0 };
+        let r = tx.0.as_ref().unwrap().0.clone();
+        drop((tx,rx));
+        let (tx,rx) = pool.oneshot();
+        // a release of the previous lending leaves the current handles alone
+        stale.release(r);
+        drop(tx);
+        assert_eq!(pool.idle(),0);
+        drop(rx);
+        assert_eq!(pool.idle(),1);
+    }
+
+    #[test]
+    fn test_handles_return() {
+        let pool = OneshotPool::<String>::new(4);
+        let lent = (0 .. 6).map(|_| pool.oneshot()).collect::<Vec<_>>();
+        let (sets,gets): (Vec<_>,Vec<_>) = lent.into_iter().unzip();
+        drop(sets);
+        assert_eq!(pool.idle(),0);
+        drop(gets);
+        assert_eq!(pool.idle(),4);
+        let (tx,rx) = pool.oneshot();
+        assert_eq!(pool.idle(),3);
+        let rx = rx.into_inner();
+        drop(pool);
+        // outliving the pool, the channel is freed as usual
+        tx.set("late".to_string()).unwrap();
+        assert_eq!(rx.wait(),Some("late".to_string()));
+    }
+}
@@ -0,0 +1,166 @@
+use alloc::collections::BinaryHeap;
+use core::cmp::Ordering;
+use core::fmt::{self,Debug};
+
+use crate::error::{RecvError,TryRecvError};
+use crate::iter::impl_receiver;
+use crate::sync::{Arc,Condvar,Mutex};
+
+// higher is received first
+#[derive(Debug,Clone,Copy,PartialEq,Eq,PartialOrd,Ord,Hash,Default)]
+pub struct Priority(pub u32);
+
+struct Item<T> {
+    priority: Priority,
+    seq: u64,
+    value: T,
+}
+// highest priority first, the earliest sent on ties
+impl<T> Ord for Item<T> {
+    fn cmp(&self, other: &Item<T>) -> Ordering {
+        self.priority.cmp(&other.priority).then(other.seq.cmp(&self.seq))
+    }
+}
+impl<T> PartialOrd for Item<T> {
+    fn partial_cmp(&self, other: &Item<T>) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl<T> PartialEq for Item<T> {
+    fn eq(&self, other: &Item<T>) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+impl<T> Eq for Item<T> {}
+
+struct Queue<T> {
+    items: BinaryHeap<Item<T>>,
+    seq: u64,
+    senders: usize,
+    receiver: bool,
+}
+
+struct Inner<T> {
+    queue: Mutex<Queue<T>>,
+    cond: Condvar,
+}
+
+pub struct Sender<T>(Arc<Inner<T>>);
+impl<T> Sender<T> {
+    pub fn send(&self, t: T, priority: Priority) -> Result<(),T> {
+        let mut lock = self.0.queue.lock();
+        if !lock.receiver {
+            return Err(t);
+        }
+        let seq = lock.seq;
+        lock.seq += 1;
+        lock.items.push(Item { priority, seq, value: t });
+        self.0.cond.notify_one();
+        Ok(())
+    }
+    pub fn is_needed(&self) -> bool {
+        self.0.queue.lock().receiver
+    }
+}
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Sender<T> {
+        self.0.queue.lock().senders += 1;
+        Sender(self.0.clone())
+    }
+}
+impl<T> Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Sender")
+    }
+}
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut lock = self.0.queue.lock();
+        lock.senders -= 1;
+        if lock.senders == 0 {
+            self.0.cond.notify_one();
+        }
+    }
+}
+
+pub struct Receiver<T>(Arc<Inner<T>>);
+impl<T> Receiver<T> {
+    pub fn recv(&self) -> Result<T,RecvError> {
+        let mut lock = self.0.queue.lock();
+        loop {
+            if let Some(item) = lock.items.pop() {
+                return Ok(item.value);
+            }
+            if lock.senders == 0 {
+                return Err(RecvError::Disconnected);
+            }
+            lock = self.0.cond.wait(lock);
+        }
+    }
+    pub fn try_recv(&self) -> Result<T,TryRecvError> {
+        let mut lock = self.0.queue.lock();
+        match lock.items.pop() {
+            Some(item) => Ok(item.value),
+            None if lock.senders == 0 => Err(TryRecvError::Disconnected),
+            None => Err(TryRecvError::Empty),
+        }
+    }
+}
+impl_receiver!(Receiver<T>);
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        let items = {
+            let mut lock = self.0.queue.lock();
+            lock.receiver = false;
+            core::mem::take(&mut lock.items)
+        };
+        drop(items);
+    }
+}
+
+pub fn channel<T>() -> (Sender<T>,Receiver<T>) {
+    let r = Arc::new(Inner {
+        queue: Mutex::new(Queue {
+            items: BinaryHeap::new(),
+            seq: 0,
+            senders: 1,
+            receiver: true,
+        }),
+        cond: Condvar::new(),
+    });
+    (Sender(r.clone()),Receiver(r))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{channel,Priority};
+    use crate::error::{RecvError,TryRecvError};
+
+    #[test]
+    fn test_order() {
+        let (tx,rx) = channel();
+        tx.send("low 1",Priority(0)).unwrap();
+        tx.send("high 1",Priority(2)).unwrap();
+        tx.send("low 2",Priority(0)).unwrap();
+        tx.send("mid",Priority(1)).unwrap();
+        tx.send("high 2",Priority(2)).unwrap();
+        drop(tx);
+        let mut got = Vec::new();
+        while let Ok(v) = rx.recv() {
+            got.push(v);
+        }
+        assert_eq!(got,vec!["high 1","high 2","mid","low 1","low 2"]);
+    }
+
+    #[test]
+    fn test_disconnect() {
+        let (tx,rx) = channel::<u8>();
+        assert_eq!(rx.try_recv(),Err(TryRecvError::Empty));
+        drop(tx);
+        assert_eq!(rx.recv(),Err(RecvError::Disconnected));
+        let (tx,rx) = channel();
+        drop(rx);
+        assert_eq!(tx.send(1,Priority::default()),Err(1));
+    }
+}
@@ -0,0 +1,169 @@
+use core::cell::UnsafeCell;
+use core::fmt::{self,Debug};
+use core::mem::MaybeUninit;
+#[cfg(feature = "std")]
+use std::time::{Duration,Instant};
+
+use crate::sync::{Arc,AtomicU8,Condvar,Mutex,Ordering};
+#[cfg(feature = "std")]
+use crate::RecvError;
+
+const SET: u8 = 1;
+const CLOSED: u8 = 2;
+
+struct Inner<T> {
+    state: AtomicU8,
+    // written once before SET, shared read-only after it
+    value: UnsafeCell<MaybeUninit<T>>,
+    // state changes that waiters sleep on are made under the lock
+    lock: Mutex<()>,
+    cond: Condvar,
+}
+unsafe impl<T: Send> Send for Inner<T> {}
+unsafe impl<T: Send + Sync> Sync for Inner<T> {}
+impl<T> Inner<T> {
+    fn get(&self) -> Option<&T> {
+        match self.state.load(Ordering::Acquire) & SET != 0 {
+            true => Some(unsafe { (*self.value.get()).assume_init_ref() }),
+            false => None,
+        }
+    }
+    fn complete(&self, bits: u8) {
+        let _lock = self.lock.lock();
+        self.state.fetch_or(bits,Ordering::Release);
+        self.cond.notify_all();
+    }
+}
+impl<T> Drop for Inner<T> {
+    fn drop(&mut self) {
+        // exclusive here, a relaxed load sees the last state like get_mut would
+        if self.state.load(Ordering::Relaxed) & SET != 0 {
+            unsafe { self.value.get_mut().assume_init_drop() };
+        }
+    }
+}
+
+// the value stays in place, every handle borrows it
+pub struct PromiseHandle<T>(Arc<Inner<T>>);
+impl<T> PromiseHandle<T> {
+    pub fn get(&self) -> Option<&T> {
+        self.0.get()
+    }
+    pub fn is_closed(&self) -> bool {
+        self.0.state.load(Ordering::Acquire) == CLOSED
+    }
+    // None if the promise was dropped unfulfilled
+    #[cfg(not(poll_only))]
+    pub fn wait(&self) -> Option<&T> {
+        if let Some(t) = self.0.get() {
+            return Some(t);
+        }
+        let mut lock = self.0.lock.lock();
+        while self.0.state.load(Ordering::Acquire) == 0 {
+            lock = self.0.cond.wait(lock);
+        }
+        drop(lock);
+        self.0.get()
+    }
+    #[cfg(feature = "std")]
+    pub fn wait_timeout(&self, timeout: Duration) -> Result<&T,RecvError> {
+        if let Some(t) = self.0.get() {
+            return Ok(t);
+        }
+        let deadline = Instant::now().checked_add(timeout);
+        let mut lock = self.0.lock.lock();
+        while self.0.state.load(Ordering::Acquire) == 0 {
+            let (guard,timed_out) = self.0.cond.wait_deadline(lock,deadline);
+            lock = guard;
+            if timed_out && self.0.state.load(Ordering::Acquire) == 0 {
+                return Err(RecvError::Timeout);
+            }
+        }
+        drop(lock);
+        self.0.get().ok_or(RecvError::Disconnected)
+    }
+}
+impl<T> Clone for PromiseHandle<T> {
+    fn clone(&self) -> PromiseHandle<T> {
+        PromiseHandle(self.0.clone())
+    }
+}
+impl<T> Debug for PromiseHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "PromiseHandle")
+    }
+}
+
+pub struct Promise<T>(Arc<Inner<T>>,bool);
+impl<T> Promise<T> {
+    pub fn handle(&self) -> PromiseHandle<T> {
+        PromiseHandle(self.0.clone())
+    }
+    pub fn is_needed(&self) -> bool {
+        Arc::strong_count(&self.0) > 1
+    }
+    pub fn set(mut self, t: T) {
+        self.1 = true;
+        // nobody reads the value before SET
+        unsafe { (*self.0.value.get()).write(t) };
+        self.0.complete(SET | CLOSED);
+    }
+}
+impl<T> Debug for Promise<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Promise")
+    }
+}
+impl<T> Drop for Promise<T> {
+    fn drop(&mut self) {
+        if !self.1 {
+            self.0.complete(CLOSED);
+        }
+    }
+}
+
+pub fn promise<T>() -> (Promise<T>,PromiseHandle<T>) {
+    let r = Arc::new(Inner {
+        state: AtomicU8::new(0),
+        value: UnsafeCell::new(MaybeUninit::uninit()),
+        lock: Mutex::new(()),
+        cond: Condvar::new(),
+    });
+    (Promise(r.clone(),false),PromiseHandle(r))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::promise;
+    use crate::RecvError;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_many_waiters() {
+        let (tx,rx) = promise();
+        let hs = (0 .. 4).map(|_| {
+            let rx = rx.clone();
+            thread::spawn(move || rx.wait().map(|v: &Vec<u8>| v.len()))
+        }).collect::<Vec<_>>();
+        assert_eq!(rx.get(),None);
+        thread::sleep(Duration::from_millis(20));
+        tx.set(vec![1,2,3]);
+        for h in hs {
+            assert_eq!(h.join().unwrap(),Some(3));
+        }
+        assert_eq!(rx.get(),Some(&vec![1,2,3]));
+    }
+
+    #[test]
+    fn test_dropped() {
+        let (tx,rx) = promise::<u8>();
+        assert_eq!(rx.wait_timeout(Duration::from_millis(10)),Err(RecvError::Timeout));
+        assert!(tx.is_needed());
+        drop(tx);
+        assert!(rx.is_closed());
+        assert_eq!(rx.wait(),None);
+        assert_eq!(rx.wait_timeout(Duration::from_millis(10)),Err(RecvError::Disconnected));
+    }
+}
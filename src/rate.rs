@@ -0,0 +1,120 @@
+use std::fmt::{self,Debug};
+use std::thread;
+use std::time::{Duration,Instant};
+
+use crate::error::TrySendError;
+use crate::sync::Mutex;
+use crate::transmit::Transmit;
+
+struct Bucket {
+    tokens: f64,
+    refilled: Instant,
+}
+
+// token bucket in front of any Transmit: `per_second` tokens refill continuously up
+// to `burst`, every send spends one
+pub struct RateLimited<S> {
+    tx: S,
+    per_second: f64,
+    burst: f64,
+    bucket: Mutex<Bucket>,
+}
+impl<S: Transmit> RateLimited<S> {
+    pub fn new(tx: S, per_second: u32, burst: u32) -> RateLimited<S> {
+        assert!(per_second > 0, "rate must be positive");
+        let burst = burst.max(1) as f64;
+        RateLimited {
+            tx,
+            per_second: per_second as f64,
+            burst,
+            bucket: Mutex::new(Bucket { tokens: burst, refilled: Instant::now() }),
+        }
+    }
+    // how long to wait for a token, after taking it if there is one
+    fn take(&self) -> Option<Duration> {
+        let mut bucket = self.bucket.lock();
+        let now = Instant::now();
+        let refill = now.duration_since(bucket.refilled).as_secs_f64() * self.per_second;
+        bucket.tokens = (bucket.tokens + refill).min(self.burst);
+        bucket.refilled = now;
+        match bucket.tokens >= 1.0 {
+            true => {
+                bucket.tokens -= 1.0;
+                None
+            },
+            false => Some(Duration::from_secs_f64((1.0 - bucket.tokens) / self.per_second)),
+        }
+    }
+    // a failed send gives its token back
+    fn refund(&self) {
+        let mut bucket = self.bucket.lock();
+        bucket.tokens = (bucket.tokens + 1.0).min(self.burst);
+    }
+    // blocks until the budget allows the send
+    pub fn send(&self, t: S::Item) -> Result<(),S::Item> {
+        while let Some(wait) = self.take() {
+            thread::sleep(wait);
+        }
+        self.tx.send(t).inspect_err(|_| self.refund())
+    }
+    // Full while the budget is exhausted
+    pub fn try_send(&self, t: S::Item) -> Result<(),TrySendError<S::Item>> {
+        match self.take() {
+            None => self.tx.send(t).map_err(|t| {
+                self.refund();
+                TrySendError::Disconnected(t)
+            }),
+            Some(_) => Err(TrySendError::Full(t)),
+        }
+    }
+    pub fn into_inner(self) -> S {
+        self.tx
+    }
+}
+impl<S: Transmit> Transmit for RateLimited<S> {
+    type Item = S::Item;
+    fn send(&self, t: S::Item) -> Result<(),S::Item> {
+        RateLimited::send(self,t)
+    }
+}
+impl<S> Debug for RateLimited<S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "RateLimited({}/s)", self.per_second)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::RateLimited;
+    use crate::error::TrySendError;
+    use crate::mpsc;
+    use std::time::{Duration,Instant};
+
+    #[test]
+    fn test_blocking_budget() {
+        let (tx,rx) = mpsc::channel();
+        let tx = RateLimited::new(tx,100,2);
+        let start = Instant::now();
+        for i in 0 .. 6 {
+            tx.send(i).unwrap();
+        }
+        // two from the burst, four refilled at 10ms each
+        assert!(start.elapsed() >= Duration::from_millis(35));
+        assert_eq!(rx.try_iter().count(),6);
+    }
+
+    #[test]
+    fn test_try_send() {
+        let (tx,rx) = mpsc::channel();
+        let tx = RateLimited::new(tx,1,1);
+        assert_eq!(tx.try_send(1),Ok(()));
+        assert_eq!(tx.try_send(2),Err(TrySendError::Full(2)));
+        drop(rx);
+        let tx = RateLimited::new(tx.into_inner(),1,1);
+        assert_eq!(tx.try_send(3),Err(TrySendError::Disconnected(3)));
+        // the failed sends kept the only token
+        assert_eq!(tx.send(4),Err(4));
+        assert_eq!(tx.try_send(5),Err(TrySendError::Disconnected(5)));
+    }
+}
@@ -0,0 +1,99 @@
+use std::fmt::{self,Debug};
+use std::sync::Weak;
+use std::time::{Duration,Instant};
+
+use crate::sync::{Arc,Mutex};
+use crate::{oneshot,InnerOne,OneGet,OneSet,State};
+
+trait Probe: Send + Sync {
+    fn state(&self) -> State;
+}
+impl<T: Send> Probe for InnerOne<T> {
+    fn state(&self) -> State {
+        match self.is_needed() {
+            true => self.receiver_state(),
+            false => State::ReceiverDropped,
+        }
+    }
+}
+
+struct Entry {
+    name: String,
+    created: Instant,
+    probe: Weak<dyn Probe>,
+}
+
+struct Registry {
+    entries: Vec<Entry>,
+    // entries alive at the last cleanup, dead ones are swept when the list doubles
+    live: usize,
+}
+
+static REGISTRY: Mutex<Registry> = Mutex::new(Registry { entries: Vec::new(), live: 0 });
+
+#[derive(Clone)]
+pub struct ChannelInfo {
+    pub name: String,
+    pub state: State,
+    pub age: Duration,
+}
+impl Debug for ChannelInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {:?} for {:?}", self.name, self.state, self.age)
+    }
+}
+
+pub fn oneshot_named<T: Send + 'static>(name: impl Into<String>) -> (OneSet<T>,OneGet<T>) {
+    let (tx,rx) = oneshot();
+    let probe: Weak<dyn Probe> = Arc::downgrade(&rx.0) as Weak<InnerOne<T>>;
+    let mut registry = REGISTRY.lock();
+    if registry.entries.len() >= 2 * registry.live.max(16) {
+        registry.entries.retain(|e| e.probe.strong_count() > 0);
+        registry.live = registry.entries.len();
+    }
+    registry.entries.push(Entry { name: name.into(), created: Instant::now(), probe });
+    (tx,rx)
+}
+
+// named channels with at least one half alive, oldest first
+pub fn live_channels() -> Vec<ChannelInfo> {
+    let now = Instant::now();
+    let mut registry = REGISTRY.lock();
+    registry.entries.retain(|e| e.probe.strong_count() > 0);
+    registry.live = registry.entries.len();
+    let (infos,probes): (Vec<_>,Vec<_>) = registry.entries.iter().filter_map(|e| {
+        let probe = e.probe.upgrade()?;
+        Some((ChannelInfo { name: e.name.clone(), state: probe.state(), age: now - e.created },probe))
+    }).unzip();
+    drop(registry);
+    // a probe may be the last reference by now, its value's drop can name channels too
+    drop(probes);
+    infos
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{live_channels,oneshot_named};
+    use crate::State;
+
+    // the registry is shared with other tests, so look for our names only
+    fn state_of(name: &str) -> Option<State> {
+        live_channels().into_iter().find(|c| c.name == name).map(|c| c.state)
+    }
+
+    #[test]
+    fn test_live_channels() {
+        let (tx,rx) = oneshot_named::<u64>("test.reply");
+        assert_eq!(state_of("test.reply"),Some(State::Pending));
+        tx.set(1).unwrap();
+        assert_eq!(state_of("test.reply"),Some(State::Ready));
+        drop(rx);
+        assert_eq!(state_of("test.reply"),None);
+        let (tx,rx) = oneshot_named::<u64>("test.gone");
+        drop(rx);
+        assert_eq!(state_of("test.gone"),Some(State::ReceiverDropped));
+        drop(tx);
+        assert_eq!(state_of("test.gone"),None);
+    }
+}
@@ -0,0 +1,38 @@
+use alloc::task::Wake;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context,Poll,Waker};
+
+use crate::sync::{Arc,Mutex};
+use crate::OneSet;
+
+// polls another crate's oneshot receiver from within its own waker, so no runtime or
+// thread drives it; the receiver holds the waker and the waker the receiver until the
+// foreign sender sets or drops
+pub(crate) struct Relay<F,T>(Mutex<Option<(F,OneSet<T>)>>);
+impl<F,T,E> Relay<F,T>
+where F: Future<Output = Result<T,E>> + Unpin + Send + 'static, T: Send + 'static
+{
+    pub(crate) fn start(rx: F, tx: OneSet<T>) {
+        Arc::new(Relay(Mutex::new(Some((rx,tx))))).wake();
+    }
+}
+impl<F,T,E> Wake for Relay<F,T>
+where F: Future<Output = Result<T,E>> + Unpin + Send + 'static, T: Send + 'static
+{
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+    fn wake_by_ref(self: &Arc<Self>) {
+        let mut slot = self.0.lock();
+        let Some((rx,_)) = slot.as_mut() else { return };
+        let waker = Waker::from(self.clone());
+        if let Poll::Ready(res) = Pin::new(rx).poll(&mut Context::from_waker(&waker)) {
+            let (_,tx) = slot.take().unwrap();
+            // a dropped foreign sender drops the OneSet as well
+            if let Ok(t) = res {
+                let _ = tx.set(t);
+            }
+        }
+    }
+}
@@ -0,0 +1,187 @@
+use core::fmt::{self,Debug};
+
+use crate::error::{RecvError,TryRecvError};
+use crate::iter::impl_receiver;
+use crate::transmit::Transmit;
+use crate::sync::{Arc,Condvar,Mutex};
+
+struct Handoff<T> {
+    item: Option<T>,
+    // handoffs offered and taken so far, a sender waits for its own to be taken
+    offered: u64,
+    taken: u64,
+    senders: usize,
+    receiver: bool,
+}
+
+struct Inner<T> {
+    handoff: Mutex<Handoff<T>>,
+    cond: Condvar,
+}
+
+pub struct Sender<T>(Arc<Inner<T>>);
+impl<T> Sender<T> {
+    // returns once the receiver has taken the value
+    pub fn send(&self, t: T) -> Result<(),T> {
+        let mut lock = self.0.handoff.lock();
+        while lock.item.is_some() && lock.receiver {
+            lock = self.0.cond.wait(lock);
+        }
+        if !lock.receiver {
+            return Err(t);
+        }
+        lock.item = Some(t);
+        lock.offered += 1;
+        let ticket = lock.offered;
+        self.0.cond.notify_all();
+        while lock.taken < ticket {
+            if !lock.receiver {
+                // still ours: the receiver never took it
+                return Err(lock.item.take().unwrap());
+            }
+            lock = self.0.cond.wait(lock);
+        }
+        Ok(())
+    }
+    pub fn is_needed(&self) -> bool {
+        self.0.handoff.lock().receiver
+    }
+}
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Sender<T> {
+        self.0.handoff.lock().senders += 1;
+        Sender(self.0.clone())
+    }
+}
+impl<T> Transmit for Sender<T> {
+    type Item = T;
+    fn send(&self, t: T) -> Result<(),T> {
+        Sender::send(self,t)
+    }
+}
+impl<T> Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Sender")
+    }
+}
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut lock = self.0.handoff.lock();
+        lock.senders -= 1;
+        if lock.senders == 0 {
+            self.0.cond.notify_all();
+        }
+    }
+}
+
+pub struct Receiver<T>(Arc<Inner<T>>);
+impl<T> Receiver<T> {
+    fn take(&self, lock: &mut Handoff<T>) -> Option<T> {
+        let t = lock.item.take()?;
+        lock.taken += 1;
+        self.0.cond.notify_all();
+        Some(t)
+    }
+    pub fn recv(&self) -> Result<T,RecvError> {
+        let mut lock = self.0.handoff.lock();
+        loop {
+            if let Some(t) = self.take(&mut lock) {
+                return Ok(t);
+            }
+            if lock.senders == 0 {
+                return Err(RecvError::Disconnected);
+            }
+            lock = self.0.cond.wait(lock);
+        }
+    }
+    // only succeeds if a sender is blocked handing a value over
+    pub fn try_recv(&self) -> Result<T,TryRecvError> {
+        let mut lock = self.0.handoff.lock();
+        match self.take(&mut lock) {
+            Some(t) => Ok(t),
+            None if lock.senders == 0 => Err(TryRecvError::Disconnected),
+            None => Err(TryRecvError::Empty),
+        }
+    }
+}
+impl_receiver!(Receiver<T>);
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.0.handoff.lock().receiver = false;
+        self.0.cond.notify_all();
+    }
+}
+
+pub fn channel<T>() -> (Sender<T>,Receiver<T>) {
+    let r = Arc::new(Inner {
+        handoff: Mutex::new(Handoff {
+            item: None,
+            offered: 0,
+            taken: 0,
+            senders: 1,
+            receiver: true,
+        }),
+        cond: Condvar::new(),
+    });
+    (Sender(r.clone()),Receiver(r))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::channel;
+    use crate::error::{RecvError,TryRecvError};
+    use std::sync::atomic::{AtomicBool,Ordering};
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_handoff() {
+        let (tx,rx) = channel();
+        let received = AtomicBool::new(false);
+        thread::scope(|s| {
+            s.spawn(|| {
+                tx.send(1).unwrap();
+                assert!(received.load(Ordering::SeqCst));
+            });
+            thread::sleep(Duration::from_millis(50));
+            received.store(true,Ordering::SeqCst);
+            assert_eq!(rx.recv(),Ok(1));
+        });
+        drop(tx);
+        assert_eq!(rx.recv(),Err(RecvError::Disconnected));
+    }
+
+    #[test]
+    fn test_many_senders() {
+        let (tx,rx) = channel();
+        let hs = (0 .. 4).map(|n| {
+            let tx = tx.clone();
+            thread::spawn(move || {
+                for i in 0 .. 50 {
+                    tx.send(n * 50 + i).unwrap();
+                }
+            })
+        }).collect::<Vec<_>>();
+        drop(tx);
+        let mut all = Vec::new();
+        while let Ok(v) = rx.recv() {
+            all.push(v);
+        }
+        all.sort();
+        assert_eq!(all,(0 .. 200).collect::<Vec<_>>());
+        for h in hs {
+            h.join().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_dropped_receiver() {
+        let (tx,rx) = channel();
+        assert_eq!(rx.try_recv(),Err(TryRecvError::Empty));
+        let h = thread::spawn(move || tx.send(3));
+        thread::sleep(Duration::from_millis(50));
+        drop(rx);
+        assert_eq!(h.join().unwrap(),Err(3));
+    }
+}
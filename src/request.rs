@@ -0,0 +1,108 @@
+use core::fmt::{self,Debug};
+
+use crate::error::{RecvError,TryRecvError};
+use crate::iter::{IntoIter,Iter,Receive,TryIter};
+use crate::mpsc::{self,Receiver,Sender};
+use crate::{oneshot,OneGet,OneSet};
+
+pub struct Caller<Req,Resp>(Sender<(Req,OneSet<Resp>)>);
+impl<Req,Resp> Caller<Req,Resp> {
+    pub fn call(&self, req: Req) -> Result<OneGet<Resp>,Req> {
+        let (tx,rx) = oneshot();
+        match self.0.send((req,tx)) {
+            Ok(()) => Ok(rx),
+            Err((req,_)) => Err(req),
+        }
+    }
+    pub fn is_needed(&self) -> bool {
+        self.0.is_needed()
+    }
+}
+impl<Req,Resp> Clone for Caller<Req,Resp> {
+    fn clone(&self) -> Caller<Req,Resp> {
+        Caller(self.0.clone())
+    }
+}
+impl<Req,Resp> Debug for Caller<Req,Resp> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Caller")
+    }
+}
+
+pub struct Responder<Req,Resp>(Receiver<(Req,OneSet<Resp>)>);
+impl<Req,Resp> Responder<Req,Resp> {
+    pub fn recv(&self) -> Result<(Req,OneSet<Resp>),RecvError> {
+        self.0.recv()
+    }
+    pub fn try_recv(&self) -> Result<(Req,OneSet<Resp>),TryRecvError> {
+        self.0.try_recv()
+    }
+    pub fn iter(&self) -> Iter<'_,Responder<Req,Resp>> {
+        Iter(self)
+    }
+    pub fn try_iter(&self) -> TryIter<'_,Responder<Req,Resp>> {
+        TryIter(self)
+    }
+}
+impl<Req,Resp> Receive for Responder<Req,Resp> {
+    type Item = (Req,OneSet<Resp>);
+    fn recv(&self) -> Result<(Req,OneSet<Resp>),RecvError> {
+        Responder::recv(self)
+    }
+    fn try_recv(&self) -> Result<(Req,OneSet<Resp>),TryRecvError> {
+        Responder::try_recv(self)
+    }
+}
+impl<Req,Resp> IntoIterator for Responder<Req,Resp> {
+    type Item = (Req,OneSet<Resp>);
+    type IntoIter = IntoIter<Responder<Req,Resp>>;
+    fn into_iter(self) -> IntoIter<Responder<Req,Resp>> {
+        IntoIter(self)
+    }
+}
+impl<Req,Resp> Debug for Responder<Req,Resp> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Responder")
+    }
+}
+
+pub fn request<Req,Resp>() -> (Caller<Req,Resp>,Responder<Req,Resp>) {
+    let (tx,rx) = mpsc::channel();
+    (Caller(tx),Responder(rx))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::request;
+    use crate::RecvError;
+    use std::thread;
+
+    #[test]
+    fn test_call() {
+        let (caller,responder) = request::<u64,String>();
+        let h = thread::spawn(move || {
+            for (req,tx) in responder {
+                tx.set(req.to_string()).unwrap();
+            }
+        });
+        let replies = (0 .. 3).map(|i| caller.call(i).unwrap()).collect::<Vec<_>>();
+        let replies = replies.into_iter().map(|rx| rx.wait().unwrap()).collect::<Vec<_>>();
+        assert_eq!(replies,vec!["0","1","2"]);
+        drop(caller);
+        h.join().unwrap();
+    }
+
+    #[test]
+    fn test_dropped_sides() {
+        let (caller,responder) = request::<u64,u64>();
+        let rx = caller.call(1).unwrap();
+        let (req,tx) = responder.recv().unwrap();
+        assert_eq!(req,1);
+        drop(tx);
+        assert_eq!(rx.recv(),Err(RecvError::Disconnected));
+        drop(responder);
+        assert!(!caller.is_needed());
+        assert_eq!(caller.call(2).unwrap_err(),2);
+    }
+}
@@ -0,0 +1,93 @@
+#[cfg(feature = "std")]
+use std::time::Duration;
+
+#[cfg(not(poll_only))]
+use crate::RecvError;
+use crate::{oneshot,OneGet,OneSet};
+
+pub type ResultSet<T,E> = OneSet<Result<T,E>>;
+pub type ResultGet<T,E> = OneGet<Result<T,E>>;
+
+impl<T,E> OneSet<Result<T,E>> {
+    pub fn ok(self, t: T) -> Result<(),T> {
+        self.set(Ok(t)).map_err(|res| match res {
+            Ok(t) => t,
+            Err(_) => unreachable!(),
+        })
+    }
+    pub fn err(self, e: E) -> Result<(),E> {
+        self.set(Err(e)).map_err(|res| match res {
+            Err(e) => e,
+            Ok(_) => unreachable!(),
+        })
+    }
+}
+
+// a dropped, panicked or timed out sender becomes an E, so callers see one Result
+impl<T,E> OneGet<Result<T,E>> {
+    #[cfg(not(poll_only))]
+    pub fn wait_result<F: FnOnce(RecvError) -> E>(self, f: F) -> Result<T,E> {
+        self.recv().unwrap_or_else(|e| Err(f(e)))
+    }
+    #[cfg(feature = "std")]
+    pub fn wait_result_timeout<F: FnOnce(RecvError) -> E>(self, timeout: Duration, f: F) -> Result<T,E> {
+        match self.0.wait_timeout(timeout) {
+            Some(Some(res)) => res,
+            // the closed state still tells a panicked sender from a dropped one
+            Some(None) => Err(f(self.0.closed_error())),
+            None => Err(f(RecvError::Timeout)),
+        }
+    }
+}
+
+pub fn oneshot_result<T,E>() -> (ResultSet<T,E>,ResultGet<T,E>) {
+    oneshot()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::oneshot_result;
+    use crate::RecvError;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_ok_err() {
+        let (tx,rx) = oneshot_result::<u8,String>();
+        tx.ok(1).unwrap();
+        assert_eq!(rx.wait_result(|e| e.to_string()),Ok(1));
+        let (tx,rx) = oneshot_result::<u8,String>();
+        tx.err(String::from("failed")).unwrap();
+        assert_eq!(rx.wait_result(|e| e.to_string()).unwrap_err(),"failed");
+        let (tx,rx) = oneshot_result::<u8,String>();
+        drop(rx);
+        assert_eq!(tx.ok(2),Err(2));
+    }
+
+    #[test]
+    fn test_folded_errors() {
+        let (tx,rx) = oneshot_result::<u8,RecvError>();
+        drop(tx);
+        assert_eq!(rx.wait_result(|e| e),Err(RecvError::Disconnected));
+        let (tx,rx) = oneshot_result::<u8,RecvError>();
+        let h = thread::spawn(move || {
+            let _tx = tx;
+            panic!("sender failed");
+        });
+        assert!(h.join().is_err());
+        assert_eq!(rx.wait_result(|e| e),Err(RecvError::SenderPanicked));
+        let (tx,rx) = oneshot_result::<u8,RecvError>();
+        let h = thread::spawn(move || {
+            let _tx = tx;
+            panic!("sender failed");
+        });
+        assert!(h.join().is_err());
+        assert_eq!(rx.wait_result_timeout(Duration::from_millis(10),|e| e),Err(RecvError::SenderPanicked));
+        let (tx,rx) = oneshot_result::<u8,RecvError>();
+        drop(tx);
+        assert_eq!(rx.wait_result_timeout(Duration::from_millis(10),|e| e),Err(RecvError::Disconnected));
+        let (_tx,rx) = oneshot_result::<u8,RecvError>();
+        assert_eq!(rx.wait_result_timeout(Duration::from_millis(10),|e| e),Err(RecvError::Timeout));
+    }
+}
@@ -0,0 +1,108 @@
+use std::collections::VecDeque;
+use std::panic::{self,AssertUnwindSafe};
+use std::sync::OnceLock;
+use std::thread;
+
+use crate::sync::{Arc,Condvar,Mutex};
+use crate::{oneshot,OneGet,RecvError};
+
+const MAX_THREADS: usize = 8;
+
+type Job = Box<dyn FnOnce() + Send>;
+
+struct Pool {
+    jobs: Mutex<VecDeque<Job>>,
+    cond: Condvar,
+}
+impl Pool {
+    fn run(&self) {
+        loop {
+            let job = {
+                let mut jobs = self.jobs.lock();
+                loop {
+                    match jobs.pop_front() {
+                        Some(job) => break job,
+                        None => jobs = self.cond.wait(jobs),
+                    }
+                }
+            };
+            // the job's OneSet is dropped while unwinding, the thread stays in the pool
+            let _ = panic::catch_unwind(AssertUnwindSafe(job));
+        }
+    }
+}
+
+// shared by every scatter, one thread per core up to MAX_THREADS, started on first use
+fn pool() -> &'static Pool {
+    static POOL: OnceLock<Pool> = OnceLock::new();
+    let mut spawn = false;
+    let pool = POOL.get_or_init(|| {
+        spawn = true;
+        Pool { jobs: Mutex::new(VecDeque::new()), cond: Condvar::new() }
+    });
+    if spawn {
+        let threads = thread::available_parallelism().map_or(1,|n| n.get()).min(MAX_THREADS);
+        for _ in 0 .. threads {
+            thread::Builder::new()
+                .name("transmitter-scatter".to_string())
+                .spawn(move || pool.run())
+                .expect("failed to spawn a scatter thread");
+        }
+    }
+    pool
+}
+
+// one job per item on the shared pool, the receivers are in the order of the items;
+// a panic in f shows as RecvError::SenderPanicked on that item's OneGet
+pub fn scatter<I,F,R>(items: I, f: F) -> Vec<OneGet<R>>
+where I: IntoIterator, I::Item: Send + 'static, F: Fn(I::Item) -> R + Send + Sync + 'static, R: Send + 'static
+{
+    let pool = pool();
+    let f = Arc::new(f);
+    let receivers = {
+        let mut jobs = pool.jobs.lock();
+        items.into_iter().map(|item| {
+            let (tx,rx) = oneshot();
+            let f = f.clone();
+            jobs.push_back(Box::new(move || {
+                if tx.is_needed() {
+                    let _ = tx.set(f(item));
+                }
+            }));
+            rx
+        }).collect::<Vec<_>>()
+    };
+    pool.cond.notify_all();
+    receivers
+}
+
+// waits in order, the first failed item is the error; gathering inside a scattered
+// closure can leave every pool thread waiting on jobs queued behind it
+pub fn gather<R>(receivers: Vec<OneGet<R>>) -> Result<Vec<R>,RecvError> {
+    receivers.into_iter().map(OneGet::recv).collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{gather,scatter};
+    use crate::RecvError;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_order() {
+        let rxs = scatter(0 .. 20u64,|i| {
+            thread::sleep(Duration::from_millis(20 - i));
+            i * i
+        });
+        assert_eq!(gather(rxs),Ok((0 .. 20).map(|i| i * i).collect::<Vec<_>>()));
+    }
+
+    #[test]
+    fn test_panic() {
+        let rxs = scatter(vec![1,0,2],|i| 10 / i);
+        assert_eq!(gather(rxs),Err(RecvError::SenderPanicked));
+        assert_eq!(gather(scatter(vec![5],|i| i + 1)),Ok(vec![6]));
+    }
+}
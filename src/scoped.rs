@@ -0,0 +1,181 @@
+use core::fmt::{self,Debug};
+#[cfg(feature = "std")]
+use std::time::Duration;
+
+#[cfg(not(poll_only))]
+use crate::RecvError;
+use crate::sync::{const_fn,AtomicBool,Ordering};
+use crate::{InnerOne,State};
+
+pub struct Slot<T>(InnerOne<T>);
+impl<T> Slot<T> {
+    const_fn! {
+    pub const fn new() -> Slot<T> {
+        Slot(InnerOne::new())
+    }
+    }
+}
+impl<T> Default for Slot<T> {
+    fn default() -> Slot<T> {
+        Slot::new()
+    }
+}
+impl<T> Debug for Slot<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Slot")
+    }
+}
+
+pub struct ScopedGet<'s,T>(&'s InnerOne<T>);
+impl<'s,T> ScopedGet<'s,T> {
+    pub fn is_ready(&self) -> bool {
+        self.state() == State::Ready
+    }
+    pub fn is_closed(&self) -> bool {
+        self.state() == State::SenderDropped
+    }
+    pub fn state(&self) -> State {
+        self.0.receiver_state()
+    }
+    #[cfg(not(poll_only))]
+    pub fn wait(self) -> Option<T> {
+        self.0.wait()
+    }
+    #[cfg(not(poll_only))]
+    pub fn recv(self) -> Result<T,RecvError> {
+        self.0.wait().ok_or_else(|| self.0.closed_error())
+    }
+    #[cfg(feature = "std")]
+    pub fn wait_timeout(self, timeout: Duration) -> Result<Option<T>,ScopedGet<'s,T>> {
+        match self.0.wait_timeout(timeout) {
+            Some(res) => Ok(res),
+            None => Err(self),
+        }
+    }
+    pub fn try_take(&mut self) -> Option<Option<T>> {
+        self.0.try_take()
+    }
+}
+impl<'s,T> Debug for ScopedGet<'s,T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ScopedGet")
+    }
+}
+impl<'s,T> Drop for ScopedGet<'s,T> {
+    fn drop(&mut self) {
+        self.0.drop_receiver();
+    }
+}
+
+pub struct ScopedSet<'s,T>(&'s InnerOne<T>,bool);
+impl<'s,T> ScopedSet<'s,T> {
+    pub fn is_needed(&self) -> bool {
+        self.0.is_needed()
+    }
+    pub fn state(&self) -> State {
+        self.0.sender_state()
+    }
+    pub fn set(mut self, t: T) -> Result<(),T> {
+        self.1 = true;
+        self.0.send(t)
+    }
+}
+impl<'s,T> Debug for ScopedSet<'s,T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ScopedSet")
+    }
+}
+impl<'s,T> Drop for ScopedSet<'s,T> {
+    fn drop(&mut self) {
+        if !self.1 {
+            self.0.drop_sender();
+        }
+    }
+}
+
+pub fn oneshot_in<T>(slot: &mut Slot<T>) -> (ScopedSet<'_,T>,ScopedGet<'_,T>) {
+    // the exclusive borrow guarantees no handles of a previous round are alive
+    *slot = Slot::new();
+    let inner = &slot.0;
+    (ScopedSet(inner,false),ScopedGet(inner))
+}
+
+// const-constructible for a static, hands out its borrowed pair once
+pub struct StaticOneshot<T> {
+    inner: InnerOne<T>,
+    split: AtomicBool,
+}
+impl<T> StaticOneshot<T> {
+    const_fn! {
+    pub const fn new() -> StaticOneshot<T> {
+        StaticOneshot {
+            inner: InnerOne::new(),
+            split: AtomicBool::new(false),
+        }
+    }
+    }
+    // None after the first call
+    pub fn split(&self) -> Option<(ScopedSet<'_,T>,ScopedGet<'_,T>)> {
+        match self.split.swap(true,Ordering::AcqRel) {
+            true => None,
+            false => Some((ScopedSet(&self.inner,false),ScopedGet(&self.inner))),
+        }
+    }
+}
+impl<T> Default for StaticOneshot<T> {
+    fn default() -> StaticOneshot<T> {
+        StaticOneshot::new()
+    }
+}
+impl<T> Debug for StaticOneshot<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "StaticOneshot")
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{oneshot_in,Slot};
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_scoped_threads() {
+        let mut slot = Slot::new();
+        let (tx,rx) = oneshot_in(&mut slot);
+        thread::scope(|s| {
+            s.spawn(move || {
+                thread::sleep(Duration::from_millis(100));
+                tx.set(String::from("done")).unwrap();
+            });
+            assert_eq!(rx.wait().as_deref(),Some("done"));
+        });
+    }
+
+    #[test]
+    fn test_slot_reuse() {
+        let mut slot = Slot::new();
+        for i in 0 .. 3 {
+            let (tx,rx) = oneshot_in(&mut slot);
+            thread::scope(|s| {
+                s.spawn(move || tx.set(i).unwrap());
+            });
+            assert_eq!(rx.wait(),Some(i));
+        }
+        let (tx,rx) = oneshot_in::<u8>(&mut slot);
+        drop(tx);
+        assert_eq!(rx.wait(),None);
+    }
+
+    // a static needs the const constructor, which the model checker's atomics don't have
+    #[cfg(not(loom))]
+    #[test]
+    fn test_static() {
+        static RESULT: super::StaticOneshot<u32> = super::StaticOneshot::new();
+        let (tx,rx) = RESULT.split().unwrap();
+        assert!(RESULT.split().is_none());
+        thread::spawn(move || tx.set(7).unwrap());
+        assert_eq!(rx.wait(),Some(7));
+    }
+}
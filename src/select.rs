@@ -0,0 +1,467 @@
+use std::sync::Arc;
+use std::task::{Poll,Wake,Waker};
+use std::thread::{self,Thread};
+
+use crate::OneGet;
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum Either<A,B> {
+    Left(A),
+    Right(B),
+}
+
+struct ThreadWaker(Thread);
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+pub(crate) fn thread_waker() -> Waker {
+    Arc::new(ThreadWaker(thread::current())).into()
+}
+
+// what select! expands to, not a stable interface
+#[doc(hidden)]
+pub mod macro_support {
+    use std::task::{Poll,Waker};
+    use std::thread;
+    use std::time::{Duration,Instant};
+
+    use crate::error::{RecvError,TryRecvError};
+    use crate::iter::{Receive,Watcher};
+    use crate::sync::Arc;
+    use crate::{InnerOne,OneGet};
+
+    // receivers that can't unpark the thread are polled this often
+    const POLL: Duration = Duration::from_millis(1);
+
+    // a oneshot arm the thread's waker was left on
+    trait Registered {
+        fn unregister(&self);
+    }
+    impl<T> Registered for InnerOne<T> {
+        fn unregister(&self) {
+            InnerOne::unregister(self);
+        }
+    }
+
+    // dropped once an arm is picked, before its body runs, which unregisters it from
+    // the receivers
+    pub struct Wait<'w> {
+        watcher: Arc<Watcher>,
+        waker: Waker,
+        oneshots: Vec<Arc<dyn Registered + 'w>>,
+        start: Instant,
+        deadline: Option<Instant>,
+        polling: bool,
+    }
+    impl<'w> Wait<'w> {
+        pub fn new() -> Wait<'w> {
+            Wait {
+                watcher: Watcher::current(),
+                waker: super::thread_waker(),
+                oneshots: Vec::new(),
+                start: Instant::now(),
+                deadline: None,
+                polling: false,
+            }
+        }
+        fn track<T: 'w>(&mut self, inner: &Arc<InnerOne<T>>) {
+            let ptr = Arc::as_ptr(inner) as *const ();
+            if !self.oneshots.iter().any(|o| Arc::as_ptr(o) as *const () == ptr) {
+                self.oneshots.push(inner.clone());
+            }
+        }
+        pub fn elapsed(&mut self, after: Duration) -> bool {
+            let deadline = self.start + after;
+            if Instant::now() >= deadline {
+                return true;
+            }
+            self.deadline = Some(self.deadline.map_or(deadline,|d| d.min(deadline)));
+            false
+        }
+        // sleeps until a watched arm wakes it, the nearest timer or the next poll
+        pub fn park(&mut self) {
+            let now = Instant::now();
+            let poll = self.polling.then(|| now + POLL);
+            let limit = match (self.deadline,poll) {
+                (Some(d),Some(p)) => Some(d.min(p)),
+                (d,p) => d.or(p),
+            };
+            match limit {
+                Some(d) => thread::park_timeout(d.saturating_duration_since(now)),
+                None => thread::park(),
+            }
+            self.deadline = None;
+            self.polling = false;
+        }
+    }
+    impl Default for Wait<'_> {
+        fn default() -> Self {
+            Wait::new()
+        }
+    }
+    impl Drop for Wait<'_> {
+        fn drop(&mut self) {
+            // the picked arm's waker is already gone, the others' would unpark the
+            // thread, or keep it alive, long after the select
+            for oneshot in &self.oneshots {
+                oneshot.unregister();
+            }
+        }
+    }
+
+    // taking the value needs the OneGet itself, a shared reference doesn't select
+    pub trait OneshotArm<'w> {
+        type Output;
+        fn poll_arm(&mut self, wait: &mut Wait<'w>) -> Option<Self::Output>;
+    }
+    impl<'w,T: 'w> OneshotArm<'w> for OneGet<T> {
+        type Output = Option<T>;
+        fn poll_arm(&mut self, wait: &mut Wait<'w>) -> Option<Option<T>> {
+            match self.0.poll(&wait.waker) {
+                Poll::Ready(res) => Some(res),
+                Poll::Pending => {
+                    wait.track(&self.0);
+                    None
+                },
+            }
+        }
+    }
+    pub trait ChannelArm {
+        type Output;
+        fn poll_arm(&self, wait: &mut Wait<'_>) -> Option<Self::Output>;
+    }
+    impl<R: Receive> ChannelArm for R {
+        type Output = Result<R::Item,RecvError>;
+        fn poll_arm(&self, wait: &mut Wait<'_>) -> Option<Result<R::Item,RecvError>> {
+            // watched before the check, a value sent in between still unparks
+            let watched = self.watch(&wait.watcher);
+            match self.try_recv() {
+                Ok(t) => Some(Ok(t)),
+                Err(TryRecvError::Disconnected) => Some(Err(RecvError::Disconnected)),
+                Err(TryRecvError::Malformed) => Some(Err(RecvError::Malformed)),
+                Err(TryRecvError::Empty) => {
+                    wait.polling |= !watched;
+                    None
+                },
+            }
+        }
+    }
+}
+
+// waits for the first ready arm, in order of the arms:
+//   recv(rx) -> v => .., a OneGet (borrowed mutably) binds Option<T>, a channel
+//     receiver Result<T,RecvError>; the pattern is irrefutable
+//   after(duration) => .., counted from the start of the select
+//   default => .., last, taken when no other arm is ready right away
+// the bodies run after the wait, a break or continue in one belongs to the enclosing loop
+#[macro_export]
+macro_rules! select {
+    // the picked arm leaves the loop as Left nested in one Right per arm before it
+    (@wrap [] $e:expr) => { $e };
+    (@wrap [R $($r:tt)*] $e:expr) => { $crate::Either::Right($crate::select!(@wrap [$($r)*] $e)) };
+    (@poll $wait:ident [$($r:tt)*];) => {};
+    (@poll $wait:ident [$($r:tt)*]; recv($rx:expr) -> $pat:pat => $body:expr $(, $($rest:tt)*)?) => {
+        let res = {
+            #[allow(unused_imports)]
+            use $crate::__select::{ChannelArm as _,OneshotArm as _};
+            ($rx).poll_arm(&mut $wait)
+        };
+        if let ::core::option::Option::Some(v) = res {
+            break $crate::select!(@wrap [$($r)*] $crate::Either::Left(v));
+        }
+        $crate::select!(@poll $wait [$($r)* R]; $($($rest)*)?);
+    };
+    (@poll $wait:ident [$($r:tt)*]; after($d:expr) => $body:expr $(, $($rest:tt)*)?) => {
+        if $wait.elapsed($d) {
+            break $crate::select!(@wrap [$($r)*] $crate::Either::Left(()));
+        }
+        $crate::select!(@poll $wait [$($r)* R]; $($($rest)*)?);
+    };
+    (@poll $wait:ident [$($r:tt)*]; default => $body:expr $(,)?) => {
+        break $crate::select!(@wrap [$($r)*] $crate::Either::Left(()));
+    };
+    (@pick $res:ident;) => {{
+        let never: ::core::convert::Infallible = $res;
+        match never {}
+    }};
+    (@pick $res:ident; recv($rx:expr) -> $pat:pat => $body:expr $(, $($rest:tt)*)?) => {
+        match $res {
+            $crate::Either::Left($pat) => $body,
+            $crate::Either::Right($res) => $crate::select!(@pick $res; $($($rest)*)?),
+        }
+    };
+    (@pick $res:ident; after($d:expr) => $body:expr $(, $($rest:tt)*)?) => {
+        match $res {
+            $crate::Either::Left(()) => $body,
+            $crate::Either::Right($res) => $crate::select!(@pick $res; $($($rest)*)?),
+        }
+    };
+    (@pick $res:ident; default => $body:expr $(,)?) => {
+        match $res {
+            $crate::Either::Left(()) => $body,
+            $crate::Either::Right($res) => $crate::select!(@pick $res;),
+        }
+    };
+    ($($arms:tt)*) => {{
+        let mut wait = $crate::__select::Wait::new();
+        #[allow(unreachable_code)]
+        let res = loop {
+            $crate::select!(@poll wait []; $($arms)*);
+            wait.park();
+        };
+        drop(wait);
+        $crate::select!(@pick res; $($arms)*)
+    }};
+}
+
+// takes the thread's waker back from the receivers that lost, the winner's is gone
+fn unregister<T>(receivers: &[OneGet<T>]) {
+    for rx in receivers {
+        rx.0.unregister();
+    }
+}
+
+pub fn select<T>(receivers: Vec<OneGet<T>>) -> (usize,Option<T>) {
+    assert!(!receivers.is_empty(), "select on an empty set of receivers");
+    let waker = thread_waker();
+    loop {
+        for (i,rx) in receivers.iter().enumerate() {
+            if let Poll::Ready(res) = rx.0.poll(&waker) {
+                unregister(&receivers);
+                return (i,res);
+            }
+        }
+        thread::park();
+    }
+}
+
+// first delivered value wins, dropped senders are skipped; the losers are dropped
+pub fn race<T>(receivers: impl IntoIterator<Item = OneGet<T>>) -> Option<T> {
+    let mut receivers = receivers.into_iter().collect::<Vec<_>>();
+    let waker = thread_waker();
+    while !receivers.is_empty() {
+        let mut i = 0;
+        while i < receivers.len() {
+            match receivers[i].0.poll(&waker) {
+                Poll::Ready(Some(t)) => {
+                    unregister(&receivers);
+                    return Some(t);
+                },
+                Poll::Ready(None) => drop(receivers.swap_remove(i)),
+                Poll::Pending => i += 1,
+            }
+        }
+        if !receivers.is_empty() {
+            thread::park();
+        }
+    }
+    None
+}
+
+// the receiver that didn't complete is handed back
+pub type Select2<A,B> = Either<(Option<A>,OneGet<B>),(OneGet<A>,Option<B>)>;
+
+pub fn select2<A,B>(a: OneGet<A>, b: OneGet<B>) -> Select2<A,B> {
+    let waker = thread_waker();
+    loop {
+        if let Poll::Ready(res) = a.0.poll(&waker) {
+            b.0.unregister();
+            return Either::Left((res,b));
+        }
+        if let Poll::Ready(res) = b.0.poll(&waker) {
+            a.0.unregister();
+            return Either::Right((a,res));
+        }
+        thread::park();
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{race,select,select2,Either};
+    use crate::sync::Ordering;
+    use crate::{mpsc,oneshot,spsc,RecvError,WAITING};
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_select() {
+        let (txs,rxs): (Vec<_>,Vec<_>) = (0 .. 5).map(|_| oneshot::<usize>()).unzip();
+        let h = thread::spawn(move || {
+            let mut txs = txs;
+            thread::sleep(Duration::from_millis(100));
+            txs.remove(3).set(3).unwrap();
+            thread::sleep(Duration::from_millis(100));
+        });
+        assert_eq!(select(rxs),(3,Some(3)));
+        h.join().unwrap();
+    }
+
+    #[test]
+    fn test_select_dropped() {
+        let (tx1,rx1) = oneshot::<u8>();
+        let (_tx2,rx2) = oneshot::<u8>();
+        drop(tx1);
+        assert_eq!(select(vec![rx2,rx1]),(1,None));
+    }
+
+    #[test]
+    fn test_race() {
+        let (txs,rxs): (Vec<_>,Vec<_>) = (0 .. 3).map(|_| oneshot::<u64>()).unzip();
+        let hs = txs.into_iter().enumerate().map(|(i,tx)| {
+            thread::spawn(move || {
+                match i {
+                    0 => drop(tx),
+                    _ => {
+                        thread::sleep(Duration::from_millis(100 * i as u64));
+                        let _ = tx.set(i as u64);
+                    },
+                }
+            })
+        }).collect::<Vec<_>>();
+        assert_eq!(race(rxs),Some(1));
+        for h in hs {
+            h.join().unwrap();
+        }
+        let (tx,rx) = oneshot::<u8>();
+        drop(tx);
+        assert_eq!(race(vec![rx]),None);
+    }
+
+    #[test]
+    fn test_select2() {
+        let (tx1,rx1) = oneshot::<u8>();
+        let (tx2,rx2) = oneshot::<&str>();
+        let h = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(100));
+            tx2.set("stop").unwrap();
+        });
+        let rx1 = match select2(rx1,rx2) {
+            Either::Right((rx1,Some("stop"))) => rx1,
+            res => panic!("unexpected {:?}", res),
+        };
+        h.join().unwrap();
+        tx1.set(1).unwrap();
+        assert_eq!(rx1.wait(),Some(1));
+    }
+
+    #[test]
+    fn test_select_macro() {
+        let (tx,mut rx) = oneshot::<u8>();
+        let (ctx,crx) = mpsc::channel::<&str>();
+        let h = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            ctx.send("channel").unwrap();
+            thread::sleep(Duration::from_millis(50));
+            tx.set(7).unwrap();
+        });
+        let got = crate::select! {
+            recv(rx) -> v => format!("oneshot {:?}", v),
+            recv(crx) -> v => format!("{:?}", v),
+            after(Duration::from_secs(5)) => String::from("timeout"),
+        };
+        assert_eq!(got,"Ok(\"channel\")");
+        let got = crate::select! {
+            recv(rx) -> v => v,
+            recv(crx) -> _ => None,
+        };
+        assert_eq!(got,Some(7));
+        h.join().unwrap();
+        let got = crate::select! {
+            recv(crx) -> v => v,
+        };
+        assert_eq!(got,Err(RecvError::Disconnected));
+    }
+
+    #[test]
+    fn test_select_macro_loop() {
+        let (tx,rx) = mpsc::channel::<u32>();
+        for i in 0 .. 5 {
+            tx.send(i).unwrap();
+        }
+        drop(tx);
+        let mut odd = Vec::new();
+        loop {
+            crate::select! {
+                recv(rx) -> v => match v {
+                    Ok(v) if v % 2 == 0 => continue,
+                    Ok(v) => odd.push(v),
+                    Err(_) => break,
+                },
+            }
+        }
+        assert_eq!(odd,vec![1,3]);
+    }
+
+    #[test]
+    fn test_losers_unregistered() {
+        let (tx1,rx1) = oneshot::<u8>();
+        let (_tx2,rx2) = oneshot::<u8>();
+        let h = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            tx1.set(1).unwrap();
+        });
+        let rx2 = match select2(rx1,rx2) {
+            Either::Left((Some(1),rx2)) => rx2,
+            res => panic!("unexpected {:?}", res),
+        };
+        h.join().unwrap();
+        assert_eq!(rx2.0.state.load(Ordering::Acquire) & WAITING,0);
+        let (tx3,mut rx3) = oneshot::<u8>();
+        tx3.set(3).unwrap();
+        let mut rx2 = rx2;
+        let got = crate::select! {
+            recv(rx2) -> _ => 2,
+            after(Duration::from_millis(20)) => 0,
+        };
+        assert_eq!(got,0);
+        assert_eq!(rx2.0.state.load(Ordering::Acquire) & WAITING,0);
+        let got = crate::select! {
+            recv(rx2) -> _ => 2,
+            recv(rx3) -> v => v.unwrap(),
+        };
+        assert_eq!(got,3);
+        assert_eq!(rx2.0.state.load(Ordering::Acquire) & WAITING,0);
+    }
+
+    #[test]
+    fn test_select_macro_timer() {
+        let (_tx,rx) = spsc::bounded::<u8>(1);
+        let (_otx,mut orx) = oneshot::<u8>();
+        let fired = crate::select! {
+            recv(rx) -> _ => 0,
+            recv(orx) -> _ => 1,
+            after(Duration::from_millis(20)) => 2,
+        };
+        assert_eq!(fired,2);
+        let fired = crate::select! {
+            recv(rx) -> _ => 0,
+            default => 3,
+        };
+        assert_eq!(fired,3);
+    }
+
+    #[test]
+    fn test_select_macro_shared_receiver() {
+        let (tx,rx) = mpsc::channel::<u32>();
+        thread::scope(|s| {
+            let hs = (0 .. 2).map(|_| s.spawn(|| crate::select! {
+                recv(rx) -> v => v.unwrap(),
+                after(Duration::from_secs(5)) => 0,
+            })).collect::<Vec<_>>();
+            thread::sleep(Duration::from_millis(20));
+            tx.send(1).unwrap();
+            tx.send(2).unwrap();
+            let mut got = hs.into_iter().map(|h| h.join().unwrap()).collect::<Vec<_>>();
+            got.sort();
+            assert_eq!(got,vec![1,2]);
+        });
+    }
+}
@@ -0,0 +1,126 @@
+use core::fmt::{self,Debug};
+#[cfg(feature = "std")]
+use std::time::{Duration,Instant};
+
+use crate::sync::{Condvar,Mutex};
+
+pub struct Semaphore {
+    permits: Mutex<usize>,
+    cond: Condvar,
+}
+impl Semaphore {
+    pub const fn new(permits: usize) -> Semaphore {
+        Semaphore {
+            permits: Mutex::new(permits),
+            cond: Condvar::new(),
+        }
+    }
+    pub fn acquire(&self) -> Permit<'_> {
+        let mut permits = self.permits.lock();
+        while *permits == 0 {
+            permits = self.cond.wait(permits);
+        }
+        *permits -= 1;
+        Permit(self)
+    }
+    pub fn try_acquire(&self) -> Option<Permit<'_>> {
+        let mut permits = self.permits.lock();
+        match *permits {
+            0 => None,
+            _ => {
+                *permits -= 1;
+                Some(Permit(self))
+            },
+        }
+    }
+    #[cfg(feature = "std")]
+    pub fn acquire_timeout(&self, timeout: Duration) -> Option<Permit<'_>> {
+        let deadline = Instant::now().checked_add(timeout);
+        let mut permits = self.permits.lock();
+        while *permits == 0 {
+            let (guard,timed_out) = self.cond.wait_deadline(permits,deadline);
+            if timed_out {
+                return None;
+            }
+            permits = guard;
+        }
+        *permits -= 1;
+        Some(Permit(self))
+    }
+    pub fn add_permits(&self, n: usize) {
+        *self.permits.lock() += n;
+        match n {
+            0 => {},
+            1 => self.cond.notify_one(),
+            _ => self.cond.notify_all(),
+        }
+    }
+    pub fn available_permits(&self) -> usize {
+        *self.permits.lock()
+    }
+}
+impl Debug for Semaphore {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Semaphore({})", self.available_permits())
+    }
+}
+
+pub struct Permit<'s>(&'s Semaphore);
+impl<'s> Permit<'s> {
+    // the permit is not given back on drop
+    pub fn forget(self) {
+        core::mem::forget(self)
+    }
+}
+impl<'s> Debug for Permit<'s> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Permit")
+    }
+}
+impl<'s> Drop for Permit<'s> {
+    fn drop(&mut self) {
+        self.0.add_permits(1);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::Semaphore;
+    use std::sync::atomic::{AtomicUsize,Ordering};
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_bounded_concurrency() {
+        let sem = Semaphore::new(2);
+        let running = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        thread::scope(|s| {
+            for _ in 0 .. 6 {
+                s.spawn(|| {
+                    let _permit = sem.acquire();
+                    let now = running.fetch_add(1,Ordering::SeqCst) + 1;
+                    peak.fetch_max(now,Ordering::SeqCst);
+                    thread::sleep(Duration::from_millis(20));
+                    running.fetch_sub(1,Ordering::SeqCst);
+                });
+            }
+        });
+        assert_eq!(peak.load(Ordering::SeqCst),2);
+        assert_eq!(sem.available_permits(),2);
+    }
+
+    #[test]
+    fn test_try_acquire() {
+        let sem = Semaphore::new(1);
+        let permit = sem.try_acquire().unwrap();
+        assert!(sem.try_acquire().is_none());
+        assert!(sem.acquire_timeout(Duration::from_millis(20)).is_none());
+        drop(permit);
+        sem.acquire().forget();
+        assert_eq!(sem.available_permits(),0);
+        sem.add_permits(2);
+        assert_eq!(sem.available_permits(),2);
+    }
+}
@@ -0,0 +1,154 @@
+use alloc::boxed::Box;
+use core::any::Any;
+use core::fmt::{self,Debug};
+use core::marker::PhantomData;
+
+#[cfg(not(poll_only))]
+use crate::RecvError;
+use crate::{oneshot,OneGet,OneSet};
+
+use self::sealed::{Half,Link,Payload};
+
+mod sealed {
+    use alloc::boxed::Box;
+    use core::any::Any;
+
+    use crate::{OneGet,OneSet};
+
+    pub type Payload = Box<dyn Any + Send>;
+
+    // the end of the next step's oneshot a session holds, set by whoever speaks next
+    pub enum Half {
+        Set(OneSet<Payload>),
+        Get(OneGet<Payload>),
+        Done,
+    }
+
+    pub trait Link {
+        // splits a fresh oneshot into (this side, the peer)
+        fn link(tx: OneSet<Payload>, rx: OneGet<Payload>) -> (Half,Half);
+    }
+}
+
+// session types: each step is a oneshot carrying the value and the peer's end of the next
+pub trait Protocol: Link + 'static {
+    type Dual: Protocol;
+}
+
+pub struct Tx<T,P>(PhantomData<fn(T,P)>);
+pub struct Rx<T,P>(PhantomData<fn(T,P)>);
+pub struct End;
+
+impl<T: Send + 'static, P: Protocol> Link for Tx<T,P> {
+    fn link(tx: OneSet<Payload>, rx: OneGet<Payload>) -> (Half,Half) {
+        (Half::Set(tx),Half::Get(rx))
+    }
+}
+impl<T: Send + 'static, P: Protocol> Protocol for Tx<T,P> {
+    type Dual = Rx<T,P::Dual>;
+}
+impl<T: Send + 'static, P: Protocol> Link for Rx<T,P> {
+    fn link(tx: OneSet<Payload>, rx: OneGet<Payload>) -> (Half,Half) {
+        (Half::Get(rx),Half::Set(tx))
+    }
+}
+impl<T: Send + 'static, P: Protocol> Protocol for Rx<T,P> {
+    type Dual = Tx<T,P::Dual>;
+}
+impl Link for End {
+    fn link(_tx: OneSet<Payload>, _rx: OneGet<Payload>) -> (Half,Half) {
+        (Half::Done,Half::Done)
+    }
+}
+impl Protocol for End {
+    type Dual = End;
+}
+
+// one endpoint in state P, every step consumes it and returns the next state
+pub struct Session<P>(Half,PhantomData<P>);
+impl<T: Send + 'static, P: Protocol> Session<Tx<T,P>> {
+    // the value comes back if the peer dropped its session
+    pub fn send(self, t: T) -> Result<Session<P>,T> {
+        let tx = match self.0 {
+            Half::Set(tx) => tx,
+            _ => unreachable!(),
+        };
+        let (ntx,nrx) = oneshot();
+        let (mine,theirs) = P::link(ntx,nrx);
+        match tx.set(Box::new((t,theirs))) {
+            Ok(()) => Ok(Session(mine,PhantomData)),
+            Err(b) => Err(downcast::<T>(b).0),
+        }
+    }
+}
+#[cfg(not(poll_only))]
+impl<T: Send + 'static, P: Protocol> Session<Rx<T,P>> {
+    pub fn recv(self) -> Result<(T,Session<P>),RecvError> {
+        let rx = match self.0 {
+            Half::Get(rx) => rx,
+            _ => unreachable!(),
+        };
+        let (t,next) = downcast::<T>(rx.recv()?);
+        Ok((t,Session(next,PhantomData)))
+    }
+}
+impl Session<End> {
+    pub fn close(self) {}
+}
+impl<P> Debug for Session<P> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Session")
+    }
+}
+
+// the protocol types guarantee the payload of a step
+fn downcast<T: Any>(b: Payload) -> (T,Half) {
+    *b.downcast::<(T,Half)>().ok().unwrap()
+}
+
+pub fn pair<P: Protocol>() -> (Session<P>,Session<P::Dual>) {
+    let (tx,rx) = oneshot();
+    let (mine,theirs) = P::link(tx,rx);
+    (Session(mine,PhantomData),Session(theirs,PhantomData))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{pair,End,Rx,Tx};
+    use crate::RecvError;
+    use std::thread;
+
+    struct Init(u32);
+    struct Ack;
+    struct Data(String);
+
+    type Client = Tx<Init,Rx<Ack,Tx<Data,End>>>;
+
+    #[test]
+    fn test_protocol() {
+        let (client,server) = pair::<Client>();
+        let h = thread::spawn(move || {
+            let (init,server) = server.recv().unwrap();
+            let server = server.send(Ack).ok().unwrap();
+            let (data,server) = server.recv().unwrap();
+            server.close();
+            format!("{} {}", init.0, data.0)
+        });
+        let client = client.send(Init(1)).ok().unwrap();
+        let (Ack,client) = client.recv().unwrap();
+        client.send(Data(String::from("payload"))).ok().unwrap().close();
+        assert_eq!(h.join().unwrap(),"1 payload");
+    }
+
+    #[test]
+    fn test_peer_dropped() {
+        let (client,server) = pair::<Client>();
+        drop(server);
+        assert!(matches!(client.send(Init(2)),Err(Init(2))));
+        let (client,server) = pair::<Client>();
+        let client = client.send(Init(3)).ok().unwrap();
+        drop(server);
+        assert!(matches!(client.recv(),Err(RecvError::Disconnected)));
+    }
+}
@@ -0,0 +1,165 @@
+use core::fmt::{self,Debug};
+#[cfg(feature = "std")]
+use std::time::{Duration,Instant};
+
+use crate::sync::{Arc,Condvar,Mutex};
+#[cfg(not(poll_only))]
+use crate::RecvError;
+use crate::State;
+
+struct Payload<T> {
+    value: Option<T>,
+    closed: bool,
+    receivers: usize,
+}
+
+struct Inner<T> {
+    slot: Mutex<Payload<T>>,
+    cond: Condvar,
+}
+
+pub struct SharedGet<T>(Arc<Inner<T>>);
+impl<T: Clone> SharedGet<T> {
+    pub fn state(&self) -> State {
+        let slot = self.0.slot.lock();
+        match () {
+            _ if slot.value.is_some() => State::Ready,
+            _ if slot.closed => State::SenderDropped,
+            _ => State::Pending,
+        }
+    }
+    pub fn try_get(&self) -> Option<Option<T>> {
+        let slot = self.0.slot.lock();
+        match slot.closed {
+            true => Some(slot.value.clone()),
+            false => None,
+        }
+    }
+    #[cfg(not(poll_only))]
+    pub fn wait(self) -> Option<T> {
+        let mut slot = self.0.slot.lock();
+        while !slot.closed {
+            slot = self.0.cond.wait(slot);
+        }
+        slot.value.clone()
+    }
+    #[cfg(not(poll_only))]
+    pub fn recv(self) -> Result<T,RecvError> {
+        self.wait().ok_or(RecvError::Disconnected)
+    }
+    #[cfg(feature = "std")]
+    pub fn wait_timeout(self, timeout: Duration) -> Result<Option<T>,SharedGet<T>> {
+        let deadline = Instant::now().checked_add(timeout);
+        let mut slot = self.0.slot.lock();
+        while !slot.closed {
+            let left = match deadline {
+                Some(deadline) => match deadline.checked_duration_since(Instant::now()) {
+                    Some(left) => left,
+                    None => {
+                        drop(slot);
+                        return Err(self);
+                    },
+                },
+                None => Duration::MAX,
+            };
+            slot = self.0.cond.wait_timeout(slot,left);
+        }
+        Ok(slot.value.clone())
+    }
+}
+impl<T> Clone for SharedGet<T> {
+    fn clone(&self) -> SharedGet<T> {
+        self.0.slot.lock().receivers += 1;
+        SharedGet(self.0.clone())
+    }
+}
+impl<T> Debug for SharedGet<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SharedGet")
+    }
+}
+impl<T> Drop for SharedGet<T> {
+    fn drop(&mut self) {
+        self.0.slot.lock().receivers -= 1;
+    }
+}
+
+pub struct SharedSet<T>(Arc<Inner<T>>);
+impl<T> SharedSet<T> {
+    pub fn is_needed(&self) -> bool {
+        self.0.slot.lock().receivers > 0
+    }
+    pub fn state(&self) -> State {
+        match self.is_needed() {
+            true => State::Pending,
+            false => State::ReceiverDropped,
+        }
+    }
+    pub fn set(self, t: T) -> Result<(),T> {
+        let mut slot = self.0.slot.lock();
+        if slot.receivers == 0 {
+            return Err(t);
+        }
+        slot.value = Some(t);
+        Ok(())
+    }
+}
+impl<T> Debug for SharedSet<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SharedSet")
+    }
+}
+impl<T> Drop for SharedSet<T> {
+    fn drop(&mut self) {
+        // both a set value and a dropped sender complete the channel
+        self.0.slot.lock().closed = true;
+        self.0.cond.notify_all();
+    }
+}
+
+pub fn shared_oneshot<T: Clone>() -> (SharedSet<T>,SharedGet<T>) {
+    let r = Arc::new(Inner {
+        slot: Mutex::new(Payload {
+            value: None,
+            closed: false,
+            receivers: 1,
+        }),
+        cond: Condvar::new(),
+    });
+    (SharedSet(r.clone()),SharedGet(r))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::shared_oneshot;
+    use crate::State;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_every_receiver_gets_copy() {
+        let (tx,rx) = shared_oneshot();
+        let hs = (0 .. 4).map(|_| {
+            let rx = rx.clone();
+            thread::spawn(move || rx.wait())
+        }).collect::<Vec<_>>();
+        thread::sleep(Duration::from_millis(100));
+        tx.set(String::from("result")).unwrap();
+        for h in hs {
+            assert_eq!(h.join().unwrap().as_deref(),Some("result"));
+        }
+        assert_eq!(rx.state(),State::Ready);
+        assert_eq!(rx.try_get(),Some(Some(String::from("result"))));
+    }
+
+    #[test]
+    fn test_drop_setter() {
+        let (tx,rx) = shared_oneshot::<u8>();
+        let rx2 = rx.clone();
+        let rx = rx.wait_timeout(Duration::from_millis(50)).unwrap_err();
+        drop(tx);
+        assert_eq!(rx.wait(),None);
+        assert_eq!(rx2.try_get(),Some(None));
+    }
+}
@@ -0,0 +1,372 @@
+use core::ffi::{c_int,c_void};
+use core::marker::PhantomData;
+use core::ptr;
+use std::fmt::{self,Debug};
+use std::fs::{self,File,OpenOptions};
+use std::io;
+use std::os::fd::AsRawFd;
+use std::path::PathBuf;
+// the words live in another process's mapping too, nothing for a model checker to swap in
+use std::sync::atomic::{AtomicU32,Ordering};
+use std::time::{Duration,Instant};
+
+use crate::error::{RecvError,TryRecvError,TrySendError};
+use crate::futex;
+use crate::iter::impl_receiver;
+use crate::wire::Wire;
+use crate::sync::CachePadded;
+use crate::transmit::Transmit;
+
+extern "C" {
+    fn mmap(addr: *mut c_void, len: usize, prot: c_int, flags: c_int, fd: c_int, offset: i64) -> *mut c_void;
+    fn munmap(addr: *mut c_void, len: usize) -> c_int;
+}
+
+const PROT_READ_WRITE: c_int = 3;
+const MAP_SHARED: c_int = 1;
+
+const MAGIC: u32 = 0x7472_6e73;
+
+// one side's counters; events is the futex word the other side sleeps on, sleepers
+// counts those sleeping there
+#[repr(C)]
+struct Side {
+    alive: AtomicU32,
+    // head for the receiver, tail for the sender, in bytes and wrapping
+    pos: AtomicU32,
+    events: AtomicU32,
+    sleepers: AtomicU32,
+}
+impl Side {
+    fn notify(&self) {
+        self.events.fetch_add(1,Ordering::SeqCst);
+        if self.sleepers.load(Ordering::SeqCst) > 0 {
+            futex::wake_shared(&self.events);
+        }
+    }
+    // sleeps on this side's events unless ready turns true
+    fn sleep(&self, ready: impl Fn() -> bool, timeout: Option<Duration>) {
+        self.sleepers.fetch_add(1,Ordering::SeqCst);
+        let events = self.events.load(Ordering::SeqCst);
+        if !ready() {
+            futex::wait_shared(&self.events,events,timeout);
+        }
+        self.sleepers.fetch_sub(1,Ordering::SeqCst);
+    }
+}
+
+#[repr(C)]
+struct Header {
+    magic: AtomicU32,
+    capacity: AtomicU32,
+    // set by the one open that owns the receiving side
+    claimed: AtomicU32,
+    sender: CachePadded<Side>,
+    receiver: CachePadded<Side>,
+}
+
+// the segment: a Header followed by a byte ring of frames, a big-endian u32 length as on
+// the other transports and the encoded value
+struct Segment {
+    ptr: *mut u8,
+    len: usize,
+    capacity: usize,
+    // the creating side removes the name again
+    path: Option<PathBuf>,
+}
+unsafe impl Send for Segment {}
+impl Segment {
+    fn map(file: &File, len: usize) -> io::Result<*mut u8> {
+        let ptr = unsafe { mmap(ptr::null_mut(),len,PROT_READ_WRITE,MAP_SHARED,file.as_raw_fd(),0) };
+        match ptr as isize {
+            -1 => Err(io::Error::last_os_error()),
+            _ => Ok(ptr as *mut u8),
+        }
+    }
+    fn header(&self) -> &Header {
+        unsafe { &*(self.ptr as *const Header) }
+    }
+    fn data(&self) -> *mut u8 {
+        unsafe { self.ptr.add(size_of::<Header>()) }
+    }
+    fn used(&self) -> usize {
+        let h = self.header();
+        h.sender.pos.load(Ordering::Acquire).wrapping_sub(h.receiver.pos.load(Ordering::Acquire)) as usize
+    }
+    fn write_at(&self, pos: u32, bytes: &[u8]) {
+        let off = pos as usize & (self.capacity - 1);
+        let first = bytes.len().min(self.capacity - off);
+        unsafe {
+            ptr::copy_nonoverlapping(bytes.as_ptr(),self.data().add(off),first);
+            ptr::copy_nonoverlapping(bytes[first ..].as_ptr(),self.data(),bytes.len() - first);
+        }
+    }
+    fn read_at(&self, pos: u32, bytes: &mut [u8]) {
+        let off = pos as usize & (self.capacity - 1);
+        let first = bytes.len().min(self.capacity - off);
+        unsafe {
+            ptr::copy_nonoverlapping(self.data().add(off),bytes.as_mut_ptr(),first);
+            ptr::copy_nonoverlapping(self.data(),bytes[first ..].as_mut_ptr(),bytes.len() - first);
+        }
+    }
+}
+impl Drop for Segment {
+    fn drop(&mut self) {
+        unsafe { munmap(self.ptr as *mut c_void,self.len) };
+        if let Some(path) = &self.path {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+fn path(name: &str) -> PathBuf {
+    PathBuf::from(format!("/dev/shm/transmitter-{}", name))
+}
+
+pub struct Sender<T>(Segment,PhantomData<fn(T)>);
+impl<T: Wire> Sender<T> {
+    // TooLarge if the frame can never fit the ring
+    pub fn try_send(&self, t: T) -> Result<(),TrySendError<T>> {
+        let mut frame = vec![0; 4];
+        t.encode(&mut frame);
+        let len = frame.len() - 4;
+        if frame.len() > self.0.capacity {
+            return Err(TrySendError::TooLarge(t));
+        }
+        let h = self.0.header();
+        if h.receiver.alive.load(Ordering::Acquire) == 0 {
+            return Err(TrySendError::Disconnected(t));
+        }
+        if self.0.capacity - self.0.used() < frame.len() {
+            return Err(TrySendError::Full(t));
+        }
+        frame[.. 4].copy_from_slice(&(len as u32).to_be_bytes());
+        let tail = h.sender.pos.load(Ordering::Relaxed);
+        self.0.write_at(tail,&frame);
+        h.sender.pos.store(tail.wrapping_add(frame.len() as u32),Ordering::Release);
+        h.sender.notify();
+        Ok(())
+    }
+    pub fn send(&self, mut t: T) -> Result<(),T> {
+        let h = self.0.header();
+        loop {
+            let head = h.receiver.pos.load(Ordering::Acquire);
+            match self.try_send(t) {
+                Ok(()) => return Ok(()),
+                Err(TrySendError::Disconnected(v) | TrySendError::TooLarge(v)) => return Err(v),
+                Err(TrySendError::Full(v)) => t = v,
+            }
+            // woken by the receiver freeing space or leaving
+            let ready = || h.receiver.pos.load(Ordering::Acquire) != head || h.receiver.alive.load(Ordering::Acquire) == 0;
+            h.receiver.sleep(ready,None);
+        }
+    }
+    pub fn is_needed(&self) -> bool {
+        self.0.header().receiver.alive.load(Ordering::Acquire) != 0
+    }
+    pub fn capacity(&self) -> usize {
+        self.0.capacity
+    }
+}
+impl<T: Wire> Transmit for Sender<T> {
+    type Item = T;
+    fn send(&self, t: T) -> Result<(),T> {
+        Sender::send(self,t)
+    }
+}
+impl<T> Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Sender")
+    }
+}
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let h = self.0.header();
+        h.sender.alive.store(0,Ordering::Release);
+        h.sender.notify();
+    }
+}
+
+pub struct Receiver<T>(Segment,PhantomData<fn() -> T>);
+impl<T: Wire> Receiver<T> {
+    pub fn try_recv(&self) -> Result<T,TryRecvError> {
+        let h = self.0.header();
+        // the alive flag is read first, a value sent right before the drop is still seen
+        let alive = h.sender.alive.load(Ordering::Acquire) != 0;
+        let used = self.0.used();
+        if used == 0 {
+            return Err(match alive {
+                true => TryRecvError::Empty,
+                false => TryRecvError::Disconnected,
+            });
+        }
+        let head = h.receiver.pos.load(Ordering::Relaxed);
+        let mut len = [0; 4];
+        if used < 4 || used > self.0.capacity {
+            return Err(TryRecvError::Disconnected);
+        }
+        self.0.read_at(head,&mut len);
+        // the other process writes these words, a frame that doesn't fit what was
+        // published leaves the segment unreadable from here on
+        let len = u32::from_be_bytes(len) as usize;
+        if len > self.0.capacity || len > used - 4 {
+            return Err(TryRecvError::Disconnected);
+        }
+        let mut buf = vec![0; len];
+        self.0.read_at(head.wrapping_add(4),&mut buf);
+        h.receiver.pos.store(head.wrapping_add(4 + buf.len() as u32),Ordering::Release);
+        h.receiver.notify();
+        // the frame is consumed either way, the next one is read as usual
+        T::decode(&buf).ok_or(TryRecvError::Malformed)
+    }
+    pub fn recv(&self) -> Result<T,RecvError> {
+        self.recv_until(None)
+    }
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<T,RecvError> {
+        match Instant::now().checked_add(timeout) {
+            Some(deadline) => self.recv_until(Some(deadline)),
+            None => self.recv(),
+        }
+    }
+    fn recv_until(&self, deadline: Option<Instant>) -> Result<T,RecvError> {
+        let h = self.0.header();
+        loop {
+            match self.try_recv() {
+                Ok(t) => return Ok(t),
+                Err(TryRecvError::Disconnected) => return Err(RecvError::Disconnected),
+                Err(TryRecvError::Malformed) => return Err(RecvError::Malformed),
+                Err(TryRecvError::Empty) => {},
+            }
+            let left = match deadline {
+                Some(deadline) => match deadline.checked_duration_since(Instant::now()) {
+                    Some(left) => Some(left),
+                    None => return Err(RecvError::Timeout),
+                },
+                None => None,
+            };
+            h.sender.sleep(|| self.0.used() > 0 || h.sender.alive.load(Ordering::Acquire) == 0,left);
+        }
+    }
+}
+impl_receiver!(Receiver<T: Wire>);
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        let h = self.0.header();
+        h.receiver.alive.store(0,Ordering::Release);
+        h.receiver.notify();
+    }
+}
+
+// creates /dev/shm/transmitter-<name>, removed again when the Sender is dropped; the
+// capacity is rounded up to a power of two bytes. A crashed process leaves its side
+// marked alive
+pub fn create<T: Wire>(name: &str, capacity: usize) -> io::Result<Sender<T>> {
+    let capacity = capacity.max(8).checked_next_power_of_two()
+        .filter(|c| *c <= 1 << 30)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "shm capacity too large"))?;
+    let path = path(name);
+    let file = OpenOptions::new().read(true).write(true).create_new(true).open(&path)?;
+    let len = size_of::<Header>() + capacity;
+    let segment = file.set_len(len as u64).and_then(|()| Segment::map(&file,len));
+    let ptr = match segment {
+        Ok(ptr) => ptr,
+        Err(e) => {
+            let _ = fs::remove_file(&path);
+            return Err(e);
+        },
+    };
+    let segment = Segment { ptr, len, capacity, path: Some(path) };
+    let h = segment.header();
+    h.sender.alive.store(1,Ordering::Relaxed);
+    h.receiver.alive.store(1,Ordering::Relaxed);
+    h.capacity.store(capacity as u32,Ordering::Relaxed);
+    h.magic.store(MAGIC,Ordering::Release);
+    Ok(Sender(segment,PhantomData))
+}
+
+// a single receiver per segment, a second open fails with AlreadyExists
+pub fn open<T: Wire>(name: &str) -> io::Result<Receiver<T>> {
+    let file = OpenOptions::new().read(true).write(true).open(path(name))?;
+    let len = file.metadata()?.len() as usize;
+    if len < size_of::<Header>() {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "not a transmitter segment"));
+    }
+    let ptr = Segment::map(&file,len)?;
+    let mut segment = Segment { ptr, len, capacity: 0, path: None };
+    let h = segment.header();
+    if h.magic.load(Ordering::Acquire) != MAGIC || h.capacity.load(Ordering::Relaxed) as usize != len - size_of::<Header>() {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "not a transmitter segment"));
+    }
+    if h.claimed.compare_exchange(0,1,Ordering::AcqRel,Ordering::Acquire).is_err() {
+        return Err(io::Error::new(io::ErrorKind::AlreadyExists, "segment already has a receiver"));
+    }
+    segment.capacity = len - size_of::<Header>();
+    Ok(Receiver(segment,PhantomData))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{create,open};
+    use crate::error::{RecvError,TryRecvError,TrySendError};
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_send_recv() {
+        let name = format!("test-{}", std::process::id());
+        let tx = create::<String>(&name,64).unwrap();
+        let rx = open::<String>(&name).unwrap();
+        let h = thread::spawn(move || {
+            for i in 0 .. 100 {
+                tx.send(format!("message {}", i)).unwrap();
+            }
+        });
+        for i in 0 .. 100 {
+            assert_eq!(rx.recv(),Ok(format!("message {}", i)));
+        }
+        h.join().unwrap();
+        assert_eq!(rx.recv(),Err(RecvError::Disconnected));
+        assert!(open::<String>(&name).is_err());
+    }
+
+    #[test]
+    fn test_full_and_timeout() {
+        let name = format!("test-full-{}", std::process::id());
+        let tx = create::<u64>(&name,16).unwrap();
+        let rx = open::<u64>(&name).unwrap();
+        assert_eq!(open::<u64>(&name).unwrap_err().kind(),std::io::ErrorKind::AlreadyExists);
+        assert_eq!(rx.recv_timeout(Duration::from_millis(10)),Err(RecvError::Timeout));
+        tx.try_send(1).unwrap();
+        assert_eq!(tx.try_send(2),Err(TrySendError::Full(2)));
+        assert_eq!(rx.try_iter().collect::<Vec<_>>(),vec![1]);
+        drop(rx);
+        assert!(!tx.is_needed());
+        assert_eq!(tx.send(3),Err(3));
+    }
+
+    #[test]
+    fn test_corrupt_length() {
+        let name = format!("test-corrupt-{}", std::process::id());
+        let tx = create::<Vec<u8>>(&name,16).unwrap();
+        let rx = open::<Vec<u8>>(&name).unwrap();
+        tx.try_send(vec![1,2]).unwrap();
+        // a peer claiming a frame far larger than the ring
+        tx.0.write_at(0,&u32::MAX.to_be_bytes());
+        assert_eq!(rx.try_recv(),Err(TryRecvError::Disconnected));
+    }
+
+    #[test]
+    fn test_too_large_and_malformed() {
+        let name = format!("test-malformed-{}", std::process::id());
+        let tx = create::<u8>(&name,16).unwrap();
+        let big = create::<Vec<u8>>(&format!("{}-big", name),16).unwrap();
+        assert_eq!(big.try_send(vec![0; 64]),Err(TrySendError::TooLarge(vec![0; 64])));
+        // the receiving process reads the frames as another type
+        let rx = open::<bool>(&name).unwrap();
+        tx.try_send(2).unwrap();
+        tx.try_send(1).unwrap();
+        assert_eq!(rx.try_recv(),Err(TryRecvError::Malformed));
+        assert_eq!(rx.recv(),Ok(true));
+    }
+}
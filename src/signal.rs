@@ -0,0 +1,170 @@
+use core::fmt::{self,Debug};
+#[cfg(feature = "std")]
+use core::cell::UnsafeCell;
+#[cfg(feature = "std")]
+use std::thread::{self,Thread};
+#[cfg(feature = "std")]
+use std::time::{Duration,Instant};
+
+use crate::sync::{Arc,AtomicU8,Ordering};
+
+const NOTIFIED: u8 = 1;
+const CLOSED: u8 = 2;
+#[cfg(feature = "std")]
+const WAITING: u8 = 4;
+#[cfg(feature = "std")]
+const DONE: u8 = NOTIFIED | CLOSED;
+
+// a oneshot of () needs no value and no waker list: one state byte and the thread
+// parked on it
+struct Inner {
+    state: AtomicU8,
+    // written by the receiver before it sets WAITING, taken by the notifier after
+    #[cfg(feature = "std")]
+    thread: UnsafeCell<Option<Thread>>,
+}
+unsafe impl Send for Inner {}
+unsafe impl Sync for Inner {}
+impl Inner {
+    fn complete(&self, bit: u8) {
+        #[cfg(feature = "std")]
+        if self.state.fetch_or(bit,Ordering::AcqRel) & WAITING != 0 {
+            if let Some(thread) = unsafe { (*self.thread.get()).take() } {
+                thread.unpark();
+            }
+        }
+        #[cfg(not(feature = "std"))]
+        self.state.fetch_or(bit,Ordering::Release);
+    }
+    fn done(&self) -> Option<bool> {
+        match self.state.load(Ordering::Acquire) {
+            s if s & NOTIFIED != 0 => Some(true),
+            s if s & CLOSED != 0 => Some(false),
+            _ => None,
+        }
+    }
+    // false once the deadline passed with the notifier still out
+    #[cfg(feature = "std")]
+    fn park(&self, deadline: Option<Instant>) -> bool {
+        if self.done().is_some() {
+            return true;
+        }
+        unsafe { *self.thread.get() = Some(thread::current()) };
+        if self.state.fetch_or(WAITING,Ordering::AcqRel) & DONE != 0 {
+            return true;
+        }
+        while self.done().is_none() {
+            match deadline {
+                Some(deadline) => match deadline.checked_duration_since(Instant::now()) {
+                    Some(left) => thread::park_timeout(left),
+                    // taking WAITING back makes the thread slot the receiver's again
+                    None => return self.state.compare_exchange(WAITING,0,Ordering::Acquire,Ordering::Acquire).is_err(),
+                },
+                None => thread::park(),
+            }
+        }
+        true
+    }
+}
+
+pub struct SignalGet(Arc<Inner>);
+impl SignalGet {
+    pub fn is_notified(&self) -> bool {
+        self.0.done() == Some(true)
+    }
+    // false if the notifier was dropped without notifying
+    #[cfg(not(poll_only))]
+    pub fn wait(self) -> bool {
+        #[cfg(feature = "std")]
+        self.0.park(None);
+        #[cfg(not(feature = "std"))]
+        while self.0.done().is_none() {
+            core::hint::spin_loop();
+        }
+        self.is_notified()
+    }
+    #[cfg(feature = "std")]
+    pub fn wait_timeout(self, timeout: Duration) -> Result<bool,SignalGet> {
+        match self.0.park(Instant::now().checked_add(timeout)) {
+            true => Ok(self.is_notified()),
+            false => Err(self),
+        }
+    }
+}
+impl Debug for SignalGet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SignalGet")
+    }
+}
+
+pub struct SignalSet(Arc<Inner>,bool);
+impl SignalSet {
+    pub fn is_needed(&self) -> bool {
+        Arc::strong_count(&self.0) > 1
+    }
+    pub fn notify(mut self) {
+        self.1 = true;
+        self.0.complete(NOTIFIED);
+    }
+}
+impl Debug for SignalSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SignalSet")
+    }
+}
+impl Drop for SignalSet {
+    fn drop(&mut self) {
+        if !self.1 {
+            self.0.complete(CLOSED);
+        }
+    }
+}
+
+// oneshot_in over a Slot<()> is the variant that needs no allocation at all
+pub fn signal() -> (SignalSet,SignalGet) {
+    let r = Arc::new(Inner {
+        state: AtomicU8::new(0),
+        #[cfg(feature = "std")]
+        thread: UnsafeCell::new(None),
+    });
+    (SignalSet(r.clone(),false),SignalGet(r))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::signal;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_notify() {
+        let (tx,rx) = signal();
+        let h = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            tx.notify();
+        });
+        assert!(rx.wait());
+        h.join().unwrap();
+        let (tx,rx) = signal();
+        assert!(!rx.is_notified());
+        assert!(tx.is_needed());
+        tx.notify();
+        assert!(rx.is_notified());
+    }
+
+    #[test]
+    fn test_dropped_notifier() {
+        let (tx,rx) = signal();
+        let rx = rx.wait_timeout(Duration::from_millis(20)).unwrap_err();
+        let h = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            drop(tx);
+        });
+        assert!(!rx.wait_timeout(Duration::from_secs(5)).unwrap());
+        h.join().unwrap();
+        let (tx,rx) = signal();
+        drop(rx);
+        assert!(!tx.is_needed());
+    }
+}
@@ -0,0 +1,131 @@
+use std::fmt::{self,Debug};
+use std::thread::{self,JoinHandle};
+use std::time::Duration;
+
+use crate::sync::Arc;
+use crate::{oneshot,Event,OneGet,RecvError};
+
+// a panic in f drops the setter while unwinding, recv reports RecvError::SenderPanicked
+pub fn spawn_transmit<T,F>(f: F) -> OneGet<T>
+where T: Send + 'static, F: FnOnce() -> T + Send + 'static
+{
+    let (tx,rx) = oneshot();
+    thread::spawn(move || {
+        let _ = tx.set(f());
+    });
+    rx
+}
+
+// handed to the worker loop to notice stop requests
+pub struct WorkerStop(Arc<Event>);
+impl WorkerStop {
+    pub fn is_stopped(&self) -> bool {
+        self.0.is_set()
+    }
+    // sleeps until a stop request or the timeout, true if stopped
+    pub fn wait_timeout(&self, timeout: Duration) -> bool {
+        self.0.wait_timeout(timeout)
+    }
+}
+impl Debug for WorkerStop {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "WorkerStop({})", self.is_stopped())
+    }
+}
+
+// asks the loop to stop and joins the thread on drop
+pub struct Worker<T> {
+    stop: Arc<Event>,
+    result: Option<OneGet<T>>,
+    handle: Option<JoinHandle<()>>,
+}
+impl<T: Send + 'static> Worker<T> {
+    pub fn spawn<F>(f: F) -> Worker<T>
+    where F: FnOnce(&WorkerStop) -> T + Send + 'static
+    {
+        let stop = Arc::new(Event::new(false));
+        let worker_stop = WorkerStop(stop.clone());
+        let (tx,rx) = oneshot();
+        let handle = thread::spawn(move || {
+            let _ = tx.set(f(&worker_stop));
+        });
+        Worker { stop, result: Some(rx), handle: Some(handle) }
+    }
+}
+impl<T> Worker<T> {
+    pub fn stop(&self) {
+        self.stop.set();
+    }
+    pub fn is_finished(&self) -> bool {
+        self.handle.as_ref().is_none_or(|h| h.is_finished())
+    }
+    // the final value, taken once
+    pub fn result(&mut self) -> Option<OneGet<T>> {
+        self.result.take()
+    }
+    // waits for the loop to return on its own, without asking it to stop
+    pub fn join(mut self) -> Result<T,RecvError> {
+        let result = self.result.take();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+        match result {
+            Some(rx) => rx.recv(),
+            None => Err(RecvError::Disconnected),
+        }
+    }
+}
+impl<T> Debug for Worker<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Worker")
+    }
+}
+impl<T> Drop for Worker<T> {
+    fn drop(&mut self) {
+        self.stop();
+        if let Some(handle) = self.handle.take() {
+            // a panic in the loop already went to the result as SenderPanicked
+            let _ = handle.join();
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{spawn_transmit,Worker};
+    use crate::RecvError;
+    use std::time::Duration;
+
+    #[test]
+    fn test_spawn_transmit() {
+        assert_eq!(spawn_transmit(|| 6 * 7).recv(),Ok(42));
+        let rx = spawn_transmit(|| -> u64 { panic!("worker failed") });
+        assert_eq!(rx.recv(),Err(RecvError::SenderPanicked));
+    }
+
+    #[test]
+    fn test_worker_stop() {
+        let mut worker = Worker::spawn(|stop| {
+            let mut rounds = 0u64;
+            while !stop.wait_timeout(Duration::from_millis(5)) {
+                rounds += 1;
+            }
+            rounds
+        });
+        let result = worker.result().unwrap();
+        assert!(worker.result().is_none());
+        std::thread::sleep(Duration::from_millis(30));
+        assert!(!worker.is_finished());
+        drop(worker);
+        assert!(result.recv().unwrap() > 0);
+    }
+
+    #[test]
+    fn test_worker_join() {
+        let worker = Worker::spawn(|_| 5);
+        assert_eq!(worker.join(),Ok(5));
+        let worker = Worker::spawn(|_| -> u8 { panic!("loop failed") });
+        assert_eq!(worker.join(),Err(RecvError::SenderPanicked));
+    }
+}
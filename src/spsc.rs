@@ -0,0 +1,279 @@
+use alloc::boxed::Box;
+use core::cell::{Cell,UnsafeCell};
+use core::fmt::{self,Debug};
+use core::mem::MaybeUninit;
+
+use crate::error::{RecvError,TryRecvError,TrySendError};
+use crate::iter::impl_receiver;
+#[cfg(feature = "std")]
+use crate::strategy::{self,WaitStrategy};
+use crate::sync::{fence,Arc,AtomicBool,AtomicUsize,CachePadded,Condvar,Mutex,Ordering};
+use crate::transmit::Transmit;
+
+struct Inner<T> {
+    // head is written by the receiver only, tail by the sender only
+    head: CachePadded<AtomicUsize>,
+    tail: CachePadded<AtomicUsize>,
+    buffer: Box<[UnsafeCell<MaybeUninit<T>>]>,
+    sender: AtomicBool,
+    receiver: AtomicBool,
+    sleepers: AtomicUsize,
+    lock: Mutex<()>,
+    cond: Condvar,
+}
+unsafe impl<T: Send> Send for Inner<T> {}
+unsafe impl<T: Send> Sync for Inner<T> {}
+impl<T> Inner<T> {
+    fn new(capacity: usize) -> Inner<T> {
+        assert!(capacity > 0, "spsc capacity must be positive");
+        Inner {
+            head: CachePadded(AtomicUsize::new(0)),
+            tail: CachePadded(AtomicUsize::new(0)),
+            buffer: (0 .. capacity).map(|_| UnsafeCell::new(MaybeUninit::uninit())).collect(),
+            sender: AtomicBool::new(true),
+            receiver: AtomicBool::new(true),
+            sleepers: AtomicUsize::new(0),
+            lock: Mutex::new(()),
+            cond: Condvar::new(),
+        }
+    }
+    fn slot(&self, idx: usize) -> *mut MaybeUninit<T> {
+        self.buffer[idx % self.buffer.len()].get()
+    }
+    fn wake(&self) {
+        fence(Ordering::SeqCst);
+        if self.sleepers.load(Ordering::Relaxed) > 0 {
+            let _lock = self.lock.lock();
+            self.cond.notify_all();
+        }
+    }
+    fn sleep_until(&self, ready: impl Fn() -> bool) {
+        let mut lock = self.lock.lock();
+        self.sleepers.fetch_add(1,Ordering::Relaxed);
+        fence(Ordering::SeqCst);
+        while !ready() {
+            lock = self.cond.wait(lock);
+        }
+        self.sleepers.fetch_sub(1,Ordering::Relaxed);
+    }
+    fn is_full(&self) -> bool {
+        self.tail.load(Ordering::Acquire).wrapping_sub(self.head.load(Ordering::Acquire)) == self.buffer.len()
+    }
+    fn is_empty(&self) -> bool {
+        self.tail.load(Ordering::Acquire) == self.head.load(Ordering::Acquire)
+    }
+}
+impl<T> Drop for Inner<T> {
+    fn drop(&mut self) {
+        let tail = self.tail.load(Ordering::Relaxed);
+        let mut head = self.head.load(Ordering::Relaxed);
+        while head != tail {
+            unsafe { (*self.slot(head)).assume_init_drop() };
+            head = head.wrapping_add(1);
+        }
+    }
+}
+
+// the Cell keeps the last head seen, the receiver's line is only read again when the
+// buffer looks full; it also keeps the handle !Sync
+pub struct Sender<T>(Arc<Inner<T>>,Cell<usize>);
+impl<T> Sender<T> {
+    pub fn try_send(&self, t: T) -> Result<(),TrySendError<T>> {
+        let inner = &*self.0;
+        if !inner.receiver.load(Ordering::Acquire) {
+            return Err(TrySendError::Disconnected(t));
+        }
+        let tail = inner.tail.load(Ordering::Relaxed);
+        if tail.wrapping_sub(self.1.get()) == inner.buffer.len() {
+            self.1.set(inner.head.load(Ordering::Acquire));
+            if tail.wrapping_sub(self.1.get()) == inner.buffer.len() {
+                return Err(TrySendError::Full(t));
+            }
+        }
+        // the slot is free and only this sender writes to it
+        unsafe { (*inner.slot(tail)).write(t) };
+        inner.tail.store(tail.wrapping_add(1),Ordering::Release);
+        inner.wake();
+        Ok(())
+    }
+    pub fn send(&self, mut t: T) -> Result<(),T> {
+        let inner = &*self.0;
+        loop {
+            match self.try_send(t) {
+                Ok(()) => return Ok(()),
+                Err(TrySendError::Disconnected(v) | TrySendError::TooLarge(v)) => return Err(v),
+                Err(TrySendError::Full(v)) => t = v,
+            }
+            inner.sleep_until(|| !inner.is_full() || !inner.receiver.load(Ordering::Acquire));
+        }
+    }
+    pub fn capacity(&self) -> usize {
+        self.0.buffer.len()
+    }
+}
+impl<T> Transmit for Sender<T> {
+    type Item = T;
+    fn send(&self, t: T) -> Result<(),T> {
+        Sender::send(self,t)
+    }
+}
+impl<T> Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Sender")
+    }
+}
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        self.0.sender.store(false,Ordering::Release);
+        self.0.wake();
+    }
+}
+
+// caches the last tail seen, like the Sender does with head
+pub struct Receiver<T>(Arc<Inner<T>>,Cell<usize>);
+impl<T> Receiver<T> {
+    pub fn try_recv(&self) -> Result<T,TryRecvError> {
+        let inner = &*self.0;
+        let head = inner.head.load(Ordering::Relaxed);
+        if head == self.1.get() {
+            self.1.set(inner.tail.load(Ordering::Acquire));
+        }
+        if head == self.1.get() {
+            // sender could push right before dropping, so check the tail again
+            if !inner.sender.load(Ordering::Acquire) && head == inner.tail.load(Ordering::Acquire) {
+                return Err(TryRecvError::Disconnected);
+            }
+            return Err(TryRecvError::Empty);
+        }
+        // the slot was published by the sender's release store of tail
+        let t = unsafe { (*inner.slot(head)).assume_init_read() };
+        inner.head.store(head.wrapping_add(1),Ordering::Release);
+        inner.wake();
+        Ok(t)
+    }
+    pub fn recv(&self) -> Result<T,RecvError> {
+        #[cfg(feature = "std")]
+        if let Some(res) = strategy::spin_recv(strategy::get(),|| self.try_recv()) {
+            return res;
+        }
+        self.block()
+    }
+    // the strategy replaces the process-wide one for this receive
+    #[cfg(feature = "std")]
+    pub fn recv_with(&self, strategy: &dyn WaitStrategy) -> Result<T,RecvError> {
+        strategy::spin_recv(Some(strategy),|| self.try_recv()).unwrap_or_else(|| self.block())
+    }
+    fn block(&self) -> Result<T,RecvError> {
+        let inner = &*self.0;
+        loop {
+            match self.try_recv() {
+                Ok(t) => return Ok(t),
+                Err(TryRecvError::Disconnected) => return Err(RecvError::Disconnected),
+                Err(TryRecvError::Malformed) => return Err(RecvError::Malformed),
+                Err(TryRecvError::Empty) => {},
+            }
+            inner.sleep_until(|| !inner.is_empty() || !inner.sender.load(Ordering::Acquire));
+        }
+    }
+    pub fn capacity(&self) -> usize {
+        self.0.buffer.len()
+    }
+}
+impl_receiver!(Receiver<T>);
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.0.receiver.store(false,Ordering::Release);
+        self.0.wake();
+    }
+}
+
+pub fn bounded<T>(capacity: usize) -> (Sender<T>,Receiver<T>) {
+    let r = Arc::new(Inner::new(capacity));
+    (Sender(r.clone(),Cell::new(0)),Receiver(r,Cell::new(0)))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::bounded;
+    use crate::error::{RecvError,TryRecvError,TrySendError};
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn test_send_recv() {
+        let (tx,rx) = bounded(4);
+        let h = thread::spawn(move || {
+            for i in 0 .. 1000 {
+                tx.send(i).unwrap();
+            }
+        });
+        for i in 0 .. 1000 {
+            assert_eq!(rx.recv(),Ok(i));
+        }
+        assert_eq!(rx.recv(),Err(RecvError::Disconnected));
+        h.join().unwrap();
+    }
+
+    #[test]
+    fn test_try_send_full() {
+        let (tx,rx) = bounded(2);
+        tx.try_send(1).unwrap();
+        tx.try_send(2).unwrap();
+        assert_eq!(tx.try_send(3),Err(TrySendError::Full(3)));
+        assert_eq!(rx.try_recv(),Ok(1));
+        tx.try_send(3).unwrap();
+        drop(rx);
+        assert_eq!(tx.try_send(4),Err(TrySendError::Disconnected(4)));
+    }
+
+    #[test]
+    fn test_drop_unreceived() {
+        let item = Arc::new(());
+        let (tx,rx) = bounded(3);
+        tx.send(item.clone()).unwrap();
+        tx.send(item.clone()).unwrap();
+        drop(tx);
+        assert!(rx.try_recv().is_ok());
+        drop(rx);
+        assert_eq!(Arc::strong_count(&item),1);
+    }
+
+    #[test]
+    fn test_disconnect_after_send() {
+        let (tx,rx) = bounded(1);
+        tx.send(1).unwrap();
+        drop(tx);
+        assert_eq!(rx.try_recv(),Ok(1));
+        assert_eq!(rx.try_recv(),Err(TryRecvError::Disconnected));
+    }
+
+    #[test]
+    fn test_iter() {
+        let (tx,rx) = bounded(2);
+        let h = thread::spawn(move || {
+            for i in 0 .. 10 {
+                tx.send(i).unwrap();
+            }
+        });
+        let mut got = Vec::new();
+        for v in &rx {
+            got.push(v);
+        }
+        assert_eq!(got,(0 .. 10).collect::<Vec<_>>());
+        assert_eq!(rx.try_iter().count(),0);
+        h.join().unwrap();
+    }
+
+    #[test]
+    fn test_padding() {
+        assert!(core::mem::align_of::<super::Inner<u8>>() >= 64);
+        let (tx,rx) = bounded(1);
+        for i in 0 .. 100 {
+            tx.try_send(i).unwrap();
+            assert_eq!(tx.try_send(i),Err(TrySendError::Full(i)));
+            assert_eq!(rx.try_recv(),Ok(i));
+            assert_eq!(rx.try_recv(),Err(TryRecvError::Empty));
+        }
+    }
+}
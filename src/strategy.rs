@@ -0,0 +1,125 @@
+use std::sync::OnceLock;
+use std::thread;
+
+use crate::error::{RecvError,TryRecvError};
+
+// decides what a oneshot waiter does before it parks; spin is called with the number
+// of rounds so far and returns false to park instead
+pub trait WaitStrategy: Send + Sync {
+    fn spin(&self, round: u32) -> bool;
+}
+
+// never parks, timed waits still stop at their deadline
+#[derive(Debug,Clone,Copy)]
+pub struct Spin;
+impl WaitStrategy for Spin {
+    fn spin(&self, _round: u32) -> bool {
+        core::hint::spin_loop();
+        true
+    }
+}
+
+// spins for the given rounds, then yields the time slice without parking
+#[derive(Debug,Clone,Copy)]
+pub struct SpinThenYield(pub u32);
+impl WaitStrategy for SpinThenYield {
+    fn spin(&self, round: u32) -> bool {
+        match round < self.0 {
+            true => core::hint::spin_loop(),
+            false => thread::yield_now(),
+        }
+        true
+    }
+}
+
+#[derive(Debug,Clone,Copy)]
+pub struct SpinThenPark(pub u32);
+impl WaitStrategy for SpinThenPark {
+    fn spin(&self, round: u32) -> bool {
+        core::hint::spin_loop();
+        round < self.0
+    }
+}
+
+// parks right away, what waits do when no strategy is installed
+#[derive(Debug,Clone,Copy)]
+pub struct Block;
+impl WaitStrategy for Block {
+    fn spin(&self, _round: u32) -> bool {
+        false
+    }
+}
+
+static STRATEGY: OnceLock<Box<dyn WaitStrategy>> = OnceLock::new();
+
+// can be installed once per process, false if there already was one
+pub fn set_wait_strategy(strategy: impl WaitStrategy + 'static) -> bool {
+    STRATEGY.set(Box::new(strategy)).is_ok()
+}
+
+pub(crate) fn get() -> Option<&'static dyn WaitStrategy> {
+    STRATEGY.get().map(|s| &**s)
+}
+
+// the queue receivers poll try_recv while the strategy spins; None hands the wait on
+// to their Condvar
+pub(crate) fn spin_recv<T>(strategy: Option<&dyn WaitStrategy>, try_recv: impl Fn() -> Result<T,TryRecvError>) -> Option<Result<T,RecvError>> {
+    let strategy = strategy?;
+    let mut round = 0;
+    loop {
+        match try_recv() {
+            Ok(t) => return Some(Ok(t)),
+            Err(TryRecvError::Disconnected) => return Some(Err(RecvError::Disconnected)),
+            Err(TryRecvError::Malformed) => return Some(Err(RecvError::Malformed)),
+            Err(TryRecvError::Empty) => {},
+        }
+        if !strategy.spin(round) {
+            return None;
+        }
+        round = round.saturating_add(1);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{Block,Spin,SpinThenPark,SpinThenYield};
+    use crate::oneshot;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_wait_with() {
+        for strategy in [&Spin as &dyn super::WaitStrategy,&SpinThenYield(10),&SpinThenPark(10),&Block] {
+            let (tx,rx) = oneshot();
+            let h = thread::spawn(move || {
+                thread::sleep(Duration::from_millis(10));
+                tx.set(1).unwrap();
+            });
+            assert_eq!(rx.wait_with(strategy),Some(1));
+            h.join().unwrap();
+        }
+        let (_tx,rx) = oneshot::<u8>();
+        assert!(rx.wait_timeout_with(Duration::from_millis(10),&Spin).is_err());
+    }
+
+    #[test]
+    fn test_queue_recv_with() {
+        for strategy in [&Spin as &dyn super::WaitStrategy,&SpinThenYield(10),&SpinThenPark(10),&Block] {
+            let (tx,rx) = crate::mpsc::channel();
+            let (spsc_tx,spsc_rx) = crate::spsc::bounded(1);
+            let (mpmc_tx,mpmc_rx) = crate::mpmc::bounded(1);
+            let h = thread::spawn(move || {
+                thread::sleep(Duration::from_millis(10));
+                tx.send(1).unwrap();
+                spsc_tx.send(2).unwrap();
+                mpmc_tx.send(3).unwrap();
+            });
+            assert_eq!(rx.recv_with(strategy),Ok(1));
+            assert_eq!(spsc_rx.recv_with(strategy),Ok(2));
+            assert_eq!(mpmc_rx.recv_with(strategy),Ok(3));
+            h.join().unwrap();
+            assert_eq!(rx.recv_with(strategy),Err(crate::RecvError::Disconnected));
+        }
+    }
+}
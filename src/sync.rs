@@ -0,0 +1,221 @@
+// the channel state machines take their atomics from here, the one place a model
+// checker's replacements would be swapped in
+// loom's Arc has no Weak, the reference counts stay std's under the model checker
+#[cfg(feature = "alloc")]
+pub(crate) use alloc::sync::Arc;
+#[cfg(not(all(test, loom)))]
+pub(crate) use core::sync::atomic::{AtomicBool,AtomicU8,Ordering};
+#[cfg(all(feature = "alloc", not(all(test, loom))))]
+pub(crate) use core::sync::atomic::{fence,AtomicUsize};
+#[cfg(all(test, loom))]
+pub(crate) use loom::sync::atomic::{fence,AtomicBool,AtomicU8,AtomicUsize,Ordering};
+
+// loom's atomics have no const constructors, under the model checker such a fn is a plain one
+macro_rules! const_fn {
+    ($(#[$m:meta])* $vis:vis const fn $($item:tt)*) => {
+        #[cfg(not(all(test, loom)))]
+        $(#[$m])* $vis const fn $($item)*
+        #[cfg(all(test, loom))]
+        $(#[$m])* $vis fn $($item)*
+    };
+}
+pub(crate) use const_fn;
+
+// keeps fields written by different threads on separate cache lines
+#[cfg(feature = "alloc")]
+#[repr(align(64))]
+pub(crate) struct CachePadded<T>(pub(crate) T);
+#[cfg(feature = "alloc")]
+impl<T> core::ops::Deref for CachePadded<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+#[cfg(all(feature = "std", not(feature = "parking_lot")))]
+pub(crate) use self::std_imp::{Condvar,Mutex,MutexGuard};
+#[cfg(feature = "parking_lot")]
+pub(crate) use self::parking_lot_imp::{Condvar,Mutex,MutexGuard};
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+pub(crate) use self::spin_imp::{Condvar,Mutex,MutexGuard};
+
+#[cfg(all(feature = "std", not(feature = "parking_lot")))]
+mod std_imp {
+    use std::sync::{self,PoisonError};
+    use std::time::{Duration,Instant};
+
+    pub(crate) use std::sync::MutexGuard;
+
+    pub(crate) struct Mutex<T>(sync::Mutex<T>);
+    impl<T> Mutex<T> {
+        pub(crate) const fn new(t: T) -> Mutex<T> {
+            Mutex(sync::Mutex::new(t))
+        }
+        // the guarded state is kept consistent at every unlock, so a panic
+        // in another thread doesn't make it unusable
+        pub(crate) fn lock(&self) -> MutexGuard<'_,T> {
+            self.0.lock().unwrap_or_else(PoisonError::into_inner)
+        }
+    }
+
+    pub(crate) struct Condvar(sync::Condvar);
+    impl Condvar {
+        pub(crate) const fn new() -> Condvar {
+            Condvar(sync::Condvar::new())
+        }
+        pub(crate) fn notify_one(&self) {
+            self.0.notify_one()
+        }
+        pub(crate) fn notify_all(&self) {
+            self.0.notify_all()
+        }
+        pub(crate) fn wait<'t,T>(&self, guard: MutexGuard<'t,T>) -> MutexGuard<'t,T> {
+            self.0.wait(guard).unwrap_or_else(PoisonError::into_inner)
+        }
+        pub(crate) fn wait_timeout<'t,T>(&self, guard: MutexGuard<'t,T>, timeout: Duration) -> MutexGuard<'t,T> {
+            self.0.wait_timeout(guard,timeout).unwrap_or_else(PoisonError::into_inner).0
+        }
+        // true once the deadline has passed, a missing deadline never does
+        pub(crate) fn wait_deadline<'t,T>(&self, guard: MutexGuard<'t,T>, deadline: Option<Instant>) -> (MutexGuard<'t,T>,bool) {
+            match deadline {
+                Some(deadline) => match deadline.checked_duration_since(Instant::now()) {
+                    Some(left) if !left.is_zero() => (self.wait_timeout(guard,left),false),
+                    _ => (guard,true),
+                },
+                None => (self.wait(guard),false),
+            }
+        }
+    }
+}
+
+// no poisoning to handle, the Condvar waits on the guard in place instead of taking it
+#[cfg(feature = "parking_lot")]
+mod parking_lot_imp {
+    use std::time::{Duration,Instant};
+
+    pub(crate) use parking_lot::{Mutex,MutexGuard};
+
+    pub(crate) struct Condvar(parking_lot::Condvar);
+    impl Condvar {
+        pub(crate) const fn new() -> Condvar {
+            Condvar(parking_lot::Condvar::new())
+        }
+        pub(crate) fn notify_one(&self) {
+            self.0.notify_one();
+        }
+        pub(crate) fn notify_all(&self) {
+            self.0.notify_all();
+        }
+        pub(crate) fn wait<'t,T>(&self, mut guard: MutexGuard<'t,T>) -> MutexGuard<'t,T> {
+            self.0.wait(&mut guard);
+            guard
+        }
+        pub(crate) fn wait_timeout<'t,T>(&self, mut guard: MutexGuard<'t,T>, timeout: Duration) -> MutexGuard<'t,T> {
+            self.0.wait_for(&mut guard,timeout);
+            guard
+        }
+        // true once the deadline has passed, a missing deadline never does
+        pub(crate) fn wait_deadline<'t,T>(&self, mut guard: MutexGuard<'t,T>, deadline: Option<Instant>) -> (MutexGuard<'t,T>,bool) {
+            match deadline {
+                Some(deadline) if Instant::now() >= deadline => (guard,true),
+                Some(deadline) => {
+                    self.0.wait_until(&mut guard,deadline);
+                    (guard,false)
+                },
+                None => (self.wait(guard),false),
+            }
+        }
+    }
+}
+
+#[cfg(all(feature = "alloc", not(feature = "std")))]
+mod spin_imp {
+    use core::cell::UnsafeCell;
+    use core::hint;
+    use core::ops::{Deref,DerefMut};
+    use core::sync::atomic::{AtomicBool,Ordering};
+
+    pub(crate) struct Mutex<T> {
+        locked: AtomicBool,
+        value: UnsafeCell<T>,
+    }
+    unsafe impl<T: Send> Send for Mutex<T> {}
+    unsafe impl<T: Send> Sync for Mutex<T> {}
+    impl<T> Mutex<T> {
+        pub(crate) const fn new(t: T) -> Mutex<T> {
+            Mutex {
+                locked: AtomicBool::new(false),
+                value: UnsafeCell::new(t),
+            }
+        }
+        pub(crate) fn lock(&self) -> MutexGuard<'_,T> {
+            while self.locked.compare_exchange_weak(false,true,Ordering::Acquire,Ordering::Relaxed).is_err() {
+                while self.locked.load(Ordering::Relaxed) {
+                    hint::spin_loop();
+                }
+            }
+            MutexGuard(self)
+        }
+    }
+
+    pub(crate) struct MutexGuard<'t,T>(&'t Mutex<T>);
+    impl<'t,T> MutexGuard<'t,T> {
+        fn mutex(&self) -> &'t Mutex<T> {
+            self.0
+        }
+    }
+    impl<'t,T> Deref for MutexGuard<'t,T> {
+        type Target = T;
+        fn deref(&self) -> &T {
+            // the lock is held for the lifetime of the guard
+            unsafe { &*self.0.value.get() }
+        }
+    }
+    impl<'t,T> DerefMut for MutexGuard<'t,T> {
+        fn deref_mut(&mut self) -> &mut T {
+            unsafe { &mut *self.0.value.get() }
+        }
+    }
+    impl<'t,T> Drop for MutexGuard<'t,T> {
+        fn drop(&mut self) {
+            self.0.locked.store(false,Ordering::Release);
+        }
+    }
+
+    // without an OS to park on, waiting is a spin: release the lock and retake it
+    pub(crate) struct Condvar;
+    impl Condvar {
+        pub(crate) const fn new() -> Condvar {
+            Condvar
+        }
+        pub(crate) fn notify_one(&self) {}
+        pub(crate) fn notify_all(&self) {}
+        pub(crate) fn wait<'t,T>(&self, guard: MutexGuard<'t,T>) -> MutexGuard<'t,T> {
+            let mutex = guard.mutex();
+            drop(guard);
+            hint::spin_loop();
+            mutex.lock()
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{Arc,Mutex};
+    use std::thread;
+
+    #[test]
+    fn test_poisoned_lock() {
+        let m = Arc::new(Mutex::new(1));
+        let m2 = m.clone();
+        let h = thread::spawn(move || {
+            let _guard = m2.lock();
+            panic!("holding the lock");
+        });
+        assert!(h.join().is_err());
+        *m.lock() += 1;
+        assert_eq!(*m.lock(),2);
+    }
+}
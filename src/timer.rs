@@ -0,0 +1,364 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap,VecDeque};
+use std::fmt::{self,Debug};
+use std::panic::{self,AssertUnwindSafe};
+use std::sync::{OnceLock,Weak};
+use std::thread;
+use std::time::{Duration,Instant};
+
+use crate::sync::{Arc,Condvar,Mutex};
+use crate::{oneshot,OneGet,OneSet};
+
+type Task = Box<dyn FnOnce() + Send>;
+
+struct Entry {
+    deadline: Instant,
+    seq: u64,
+    task: Task,
+}
+// the heap pops the earliest deadline first, in scheduling order on ties
+impl Ord for Entry {
+    fn cmp(&self, other: &Entry) -> Ordering {
+        other.deadline.cmp(&self.deadline).then(other.seq.cmp(&self.seq))
+    }
+}
+impl PartialOrd for Entry {
+    fn partial_cmp(&self, other: &Entry) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl PartialEq for Entry {
+    fn eq(&self, other: &Entry) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+impl Eq for Entry {}
+
+struct Queue {
+    entries: BinaryHeap<Entry>,
+    seq: u64,
+    // setters of delays too large to represent, kept open until their receiver leaves
+    never: Vec<OneSet<Instant>>,
+}
+
+struct Timer {
+    queue: Mutex<Queue>,
+    cond: Condvar,
+}
+impl Timer {
+    fn run(&self) {
+        let mut queue = self.queue.lock();
+        loop {
+            let now = Instant::now();
+            match queue.entries.peek() {
+                Some(entry) if entry.deadline <= now => {
+                    let entry = queue.entries.pop().unwrap();
+                    // tasks may schedule again; a panicking one must not take down
+                    // every other timer with the thread
+                    drop(queue);
+                    let _ = panic::catch_unwind(AssertUnwindSafe(entry.task));
+                    queue = self.queue.lock();
+                },
+                Some(entry) => {
+                    let left = entry.deadline - now;
+                    queue = self.cond.wait_timeout(queue,left);
+                },
+                None => queue = self.cond.wait(queue),
+            }
+        }
+    }
+}
+
+fn timer() -> &'static Timer {
+    static TIMER: OnceLock<Timer> = OnceLock::new();
+    let mut spawn = false;
+    let timer = TIMER.get_or_init(|| {
+        spawn = true;
+        Timer {
+            queue: Mutex::new(Queue { entries: BinaryHeap::new(), seq: 0, never: Vec::new() }),
+            cond: Condvar::new(),
+        }
+    });
+    if spawn {
+        thread::Builder::new()
+            .name("transmitter-timer".to_string())
+            .spawn(move || timer.run())
+            .expect("failed to spawn the timer thread");
+    }
+    timer
+}
+
+// runs the task on the shared timer thread once the deadline has passed
+pub(crate) fn schedule(deadline: Instant, task: Task) {
+    let timer = timer();
+    let mut queue = timer.queue.lock();
+    let seq = queue.seq;
+    queue.seq += 1;
+    let first = queue.entries.peek().is_none_or(|e| deadline < e.deadline);
+    queue.entries.push(Entry { deadline, seq, task });
+    if first {
+        timer.cond.notify_one();
+    }
+}
+
+// fires with the time it was delivered at, usable in select next to real channels;
+// callbacks chained on the result (on_ready, map, forward and the like) run on the
+// timer thread, so anything slow there delays every other timer
+pub fn after(delay: Duration) -> OneGet<Instant> {
+    let (tx,rx) = oneshot();
+    // a delay too large to represent never fires
+    if let Some(deadline) = Instant::now().checked_add(delay) {
+        schedule(deadline,Box::new(move || {
+            let _ = tx.set(Instant::now());
+        }));
+    } else {
+        park_forever(tx);
+    }
+    rx
+}
+
+fn park_forever(tx: OneSet<Instant>) {
+    let mut queue = timer().queue.lock();
+    let mut gone = Vec::new();
+    let mut i = 0;
+    while i < queue.never.len() {
+        match queue.never[i].is_needed() {
+            true => i += 1,
+            false => gone.push(queue.never.swap_remove(i)),
+        }
+    }
+    queue.never.push(tx);
+    drop(queue);
+    drop(gone);
+}
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum MissedTicks {
+    // every tick is delivered, late ones back to back; at most 1024 wait unclaimed
+    Burst,
+    // ticks missed by a slow receiver collapse into the latest one
+    Skip,
+}
+
+const MAX_BACKLOG: usize = 1024;
+
+struct Ticks {
+    due: VecDeque<Instant>,
+    waiter: Option<OneSet<Instant>>,
+}
+
+struct TickInner {
+    ticks: Mutex<Ticks>,
+    period: Duration,
+    missed: MissedTicks,
+}
+impl TickInner {
+    fn fire(&self, mut at: Instant) {
+        loop {
+            let mut ticks = self.ticks.lock();
+            match ticks.waiter.take() {
+                Some(tx) => {
+                    drop(ticks);
+                    match tx.set(at) {
+                        Ok(()) => return,
+                        // the receiver lost interest, keep the tick for the next call
+                        Err(back) => at = back,
+                    }
+                },
+                None => {
+                    // a receiver that stopped asking keeps at most MAX_BACKLOG ticks, the
+                    // oldest go first
+                    if self.missed == MissedTicks::Skip || ticks.due.len() >= MAX_BACKLOG {
+                        ticks.due.pop_front();
+                    }
+                    ticks.due.push_back(at);
+                    return;
+                },
+            }
+        }
+    }
+}
+
+fn schedule_tick(inner: Weak<TickInner>, deadline: Instant) {
+    schedule(deadline,Box::new(move || {
+        let Some(ticker) = inner.upgrade() else { return };
+        ticker.fire(deadline);
+        let mut step = ticker.period;
+        let now = Instant::now();
+        if ticker.missed == MissedTicks::Skip && deadline.checked_add(step).is_some_and(|d| d <= now) {
+            let behind = (now - deadline).as_nanos() / ticker.period.as_nanos();
+            let behind = u32::try_from(behind).unwrap_or(u32::MAX);
+            step = ticker.period.saturating_mul(behind.saturating_add(1));
+        }
+        // like after, a tick too far out to represent never comes
+        if let Some(next) = deadline.checked_add(step) {
+            schedule_tick(inner,next);
+        }
+    }));
+}
+
+pub struct Ticker(Arc<TickInner>);
+impl Ticker {
+    // the next tick; a receiver dropped before it fires leaves the tick queued,
+    // one still pending from an earlier call is closed
+    pub fn next_tick(&mut self) -> OneGet<Instant> {
+        let (tx,rx) = oneshot();
+        let mut ticks = self.0.ticks.lock();
+        match ticks.due.pop_front() {
+            Some(at) => {
+                drop(ticks);
+                let _ = tx.set(at);
+            },
+            None => {
+                // the closed one may carry callbacks, they don't run under the lock
+                let old = ticks.waiter.replace(tx);
+                drop(ticks);
+                drop(old);
+            },
+        }
+        rx
+    }
+    pub fn recv(&mut self) -> Instant {
+        self.next_tick().wait().unwrap()
+    }
+    pub fn try_recv(&mut self) -> Option<Instant> {
+        self.0.ticks.lock().due.pop_front()
+    }
+    pub fn period(&self) -> Duration {
+        self.0.period
+    }
+}
+impl Debug for Ticker {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Ticker({:?})", self.0.period)
+    }
+}
+
+pub fn tick(period: Duration) -> Ticker {
+    tick_with(period,MissedTicks::Burst)
+}
+
+pub fn tick_with(period: Duration, missed: MissedTicks) -> Ticker {
+    assert!(!period.is_zero(), "tick with a zero period");
+    let inner = Arc::new(TickInner {
+        ticks: Mutex::new(Ticks { due: VecDeque::new(), waiter: None }),
+        period,
+        missed,
+    });
+    if let Some(first) = Instant::now().checked_add(period) {
+        schedule_tick(Arc::downgrade(&inner),first);
+    }
+    Ticker(inner)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{after,schedule,tick,tick_with,MissedTicks,MAX_BACKLOG};
+    use crate::{oneshot,select};
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::{Duration,Instant};
+
+    #[test]
+    fn test_order() {
+        let (tx,rx) = mpsc::channel();
+        let now = Instant::now();
+        for i in [3u64,1,2,0] {
+            let tx = tx.clone();
+            schedule(now + Duration::from_millis(i * 30),Box::new(move || tx.send(i).unwrap()));
+        }
+        let fired = (0 .. 4).map(|_| rx.recv().unwrap()).collect::<Vec<_>>();
+        assert_eq!(fired,vec![0,1,2,3]);
+        assert!(now.elapsed() >= Duration::from_millis(90));
+    }
+
+    #[test]
+    fn test_after() {
+        let start = Instant::now();
+        let fired = after(Duration::from_millis(50)).wait().unwrap();
+        assert!(fired >= start + Duration::from_millis(50));
+        let (_tx,rx) = oneshot();
+        let (i,_) = select(vec![rx,after(Duration::from_millis(20))]);
+        assert_eq!(i,1);
+    }
+
+    #[test]
+    fn test_never() {
+        let rx = after(Duration::MAX);
+        assert!(rx.wait_timeout(Duration::from_millis(20)).is_err());
+        for _ in 0 .. 3 {
+            drop(after(Duration::MAX));
+        }
+        let _kept = after(Duration::MAX);
+        assert!(super::timer().queue.lock().never.len() <= 2);
+    }
+
+    #[test]
+    fn test_panicking_task() {
+        schedule(Instant::now(),Box::new(|| panic!("task panicked")));
+        let (tx,rx) = oneshot::<()>();
+        rx.on_ready(|_| panic!("callback panicked"));
+        schedule(Instant::now(),Box::new(move || { let _ = tx.set(()); }));
+        assert!(after(Duration::from_millis(20)).wait().is_some());
+    }
+
+    #[test]
+    fn test_tick() {
+        let mut ticker = tick(Duration::from_millis(20));
+        let first = ticker.recv();
+        let second = ticker.recv();
+        assert!(second >= first + Duration::from_millis(20));
+        thread::sleep(Duration::from_millis(90));
+        let mut burst = 0;
+        while ticker.try_recv().is_some() {
+            burst += 1;
+        }
+        assert!(burst >= 3);
+        let (_tx,rx) = oneshot::<Instant>();
+        let (i,_) = select(vec![rx,ticker.next_tick()]);
+        assert_eq!(i,1);
+    }
+
+    #[test]
+    fn test_tick_skip() {
+        let mut ticker = tick_with(Duration::from_millis(20),MissedTicks::Skip);
+        ticker.recv();
+        thread::sleep(Duration::from_millis(90));
+        assert!(ticker.try_recv().is_some());
+        assert!(ticker.try_recv().is_none());
+    }
+
+    #[test]
+    fn test_replaced_waiter() {
+        let mut ticker = tick(Duration::from_secs(3600));
+        let inner = ticker.0.clone();
+        let (tx,rx) = std::sync::mpsc::channel();
+        ticker.next_tick().on_ready(move |res| {
+            // takes the ticks lock, which the replacing call must not be holding
+            let due = inner.ticks.lock().due.len();
+            tx.send((res,due)).unwrap();
+        });
+        let _next = ticker.next_tick();
+        assert_eq!(rx.recv_timeout(Duration::from_secs(1)),Ok((None,0)));
+    }
+
+    #[test]
+    fn test_tick_bounds() {
+        let ticker = tick_with(Duration::MAX,MissedTicks::Skip);
+        let now = Instant::now();
+        for i in 0 .. 2 * MAX_BACKLOG as u32 {
+            ticker.0.fire(now + Duration::from_nanos(i as u64));
+        }
+        let ticks = ticker.0.ticks.lock();
+        assert_eq!(ticks.due.len(),1);
+        drop(ticks);
+        let burst = tick(Duration::from_secs(3600));
+        for i in 0 .. 2 * MAX_BACKLOG as u32 {
+            burst.0.fire(now + Duration::from_nanos(i as u64));
+        }
+        let ticks = burst.0.ticks.lock();
+        assert_eq!(ticks.due.len(),MAX_BACKLOG);
+        assert_eq!(ticks.due.front(),Some(&(now + Duration::from_nanos(MAX_BACKLOG as u64))));
+    }
+}
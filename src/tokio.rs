@@ -0,0 +1,86 @@
+use ::tokio::sync::oneshot;
+
+use crate::relay::Relay;
+use crate::{OneGet,OneSet};
+
+impl<T: Send + 'static> OneGet<T> {
+    // the value arrives on a tokio receiver, a dropped OneSet is its RecvError
+    pub fn into_tokio(self) -> oneshot::Receiver<T> {
+        let (tx,rx) = oneshot::channel();
+        self.on_ready(move |res| if let Some(t) = res {
+            let _ = tx.send(t);
+        });
+        rx
+    }
+}
+impl<T: Send + 'static> From<OneGet<T>> for oneshot::Receiver<T> {
+    fn from(rx: OneGet<T>) -> oneshot::Receiver<T> {
+        rx.into_tokio()
+    }
+}
+impl<T: Send + 'static> From<oneshot::Receiver<T>> for OneGet<T> {
+    fn from(rx: oneshot::Receiver<T>) -> OneGet<T> {
+        let (tx,get) = crate::oneshot();
+        Relay::start(rx,tx);
+        get
+    }
+}
+impl<T: Send + 'static> From<oneshot::Sender<T>> for OneSet<T> {
+    fn from(tx: oneshot::Sender<T>) -> OneSet<T> {
+        let (set,rx) = crate::oneshot();
+        rx.on_ready(move |res| if let Some(t) = res {
+            let _ = tx.send(t);
+        });
+        set
+    }
+}
+impl<T: Send + 'static> From<OneSet<T>> for oneshot::Sender<T> {
+    fn from(set: OneSet<T>) -> oneshot::Sender<T> {
+        let (tx,rx) = oneshot::channel();
+        Relay::start(rx,set);
+        tx
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::{oneshot,OneGet,OneSet};
+    use ::tokio::sync::oneshot as tokio_oneshot;
+    use std::thread;
+
+    #[test]
+    fn test_into_tokio() {
+        let (tx,rx) = oneshot();
+        let rx = rx.into_tokio();
+        thread::spawn(move || tx.set(1).unwrap());
+        assert_eq!(rx.blocking_recv(),Ok(1));
+        let (tx,rx) = oneshot::<u8>();
+        drop(tx);
+        assert!(tokio_oneshot::Receiver::from(rx).blocking_recv().is_err());
+    }
+
+    #[test]
+    fn test_from_tokio() {
+        let (tx,rx) = tokio_oneshot::channel();
+        let rx = OneGet::from(rx);
+        let h = thread::spawn(move || tx.send(2).unwrap());
+        assert_eq!(rx.wait(),Some(2));
+        h.join().unwrap();
+        let (tx,rx) = tokio_oneshot::channel::<u8>();
+        let rx = OneGet::from(rx);
+        drop(tx);
+        assert_eq!(rx.wait(),None);
+    }
+
+    #[test]
+    fn test_senders() {
+        let (tx,rx) = tokio_oneshot::channel();
+        OneSet::from(tx).set(3).unwrap();
+        assert_eq!(rx.blocking_recv(),Ok(3));
+        let (tx,rx) = oneshot();
+        let tx = tokio_oneshot::Sender::from(tx);
+        thread::spawn(move || tx.send(4).unwrap());
+        assert_eq!(rx.wait(),Some(4));
+    }
+}
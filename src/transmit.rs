@@ -0,0 +1,74 @@
+#[cfg(feature = "std")]
+use std::sync::mpsc;
+
+#[cfg(feature = "std")]
+use crate::error::{RecvError,TryRecvError};
+#[cfg(feature = "std")]
+use crate::iter::Receive;
+
+// multi-value senders, the value comes back if nobody will receive it
+pub trait Transmit {
+    type Item;
+    fn send(&self, t: Self::Item) -> Result<(),Self::Item>;
+}
+
+#[cfg(feature = "std")]
+impl<T> Transmit for mpsc::Sender<T> {
+    type Item = T;
+    fn send(&self, t: T) -> Result<(),T> {
+        mpsc::Sender::send(self,t).map_err(|e| e.0)
+    }
+}
+#[cfg(feature = "std")]
+impl<T> Transmit for mpsc::SyncSender<T> {
+    type Item = T;
+    fn send(&self, t: T) -> Result<(),T> {
+        mpsc::SyncSender::send(self,t).map_err(|e| e.0)
+    }
+}
+#[cfg(feature = "std")]
+impl<T> Receive for mpsc::Receiver<T> {
+    type Item = T;
+    fn recv(&self) -> Result<T,RecvError> {
+        mpsc::Receiver::recv(self).map_err(|_| RecvError::Disconnected)
+    }
+    fn try_recv(&self) -> Result<T,TryRecvError> {
+        mpsc::Receiver::try_recv(self).map_err(|e| match e {
+            mpsc::TryRecvError::Empty => TryRecvError::Empty,
+            mpsc::TryRecvError::Disconnected => TryRecvError::Disconnected,
+        })
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::Transmit;
+    use crate::iter::{IntoIter,Receive};
+    use crate::{mpmc,mpsc};
+    use std::thread;
+
+    fn pump<R: Receive,S: Transmit<Item = R::Item>>(rx: R, tx: S) {
+        for t in IntoIter(rx) {
+            if tx.send(t).is_err() {
+                break;
+            }
+        }
+    }
+
+    #[test]
+    fn test_mixed_endpoints() {
+        let (std_tx,std_rx) = std::sync::mpsc::channel();
+        let (tx,rx) = mpsc::channel();
+        let (out_tx,out_rx) = mpmc::bounded(2);
+        let h1 = thread::spawn(move || pump(std_rx,tx));
+        let h2 = thread::spawn(move || pump(rx,out_tx));
+        for i in 0 .. 5 {
+            Transmit::send(&std_tx,i).unwrap();
+        }
+        drop(std_tx);
+        assert_eq!(out_rx.iter().collect::<Vec<_>>(),vec![0,1,2,3,4]);
+        h1.join().unwrap();
+        h2.join().unwrap();
+    }
+}
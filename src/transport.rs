@@ -0,0 +1,410 @@
+use std::fmt::{self,Debug};
+use std::io::{self,Read,Write};
+use std::marker::PhantomData;
+use std::net::{Shutdown,TcpStream};
+#[cfg(unix)]
+use std::os::unix::net::{UnixDatagram,UnixStream};
+use std::thread;
+use std::time::Duration;
+
+use crate::error::{RecvError,TryRecvError};
+use crate::iter::{impl_receiver,Watcher};
+use crate::mpsc;
+use crate::wire::Wire;
+use crate::sync::{Arc,Mutex};
+use crate::transmit::Transmit;
+
+// unblocks a read_frame running on another thread, which then returns None or an error
+pub type Closer = Box<dyn FnOnce() + Send>;
+
+// moves whole frames, the remote endpoints are generic over it
+pub trait Transport: Send + 'static {
+    fn write_frame(&mut self, frame: &[u8]) -> io::Result<()>;
+    // None once the peer has shut down its side
+    fn read_frame(&mut self) -> io::Result<Option<Vec<u8>>>;
+    fn shutdown(&mut self) -> io::Result<()>;
+    // None for a transport whose reads can't be interrupted, its reader thread then
+    // ends at the next frame
+    fn closer(&self) -> Option<Closer> {
+        None
+    }
+}
+
+// the longest frame the bare stream sockets accept from their peer
+pub const DEFAULT_MAX_FRAME_LEN: usize = 16 << 20;
+// and the bare UnixDatagram, which needs a buffer of that size for every read
+pub const DEFAULT_MAX_DATAGRAM_LEN: usize = 64 << 10;
+
+// byte streams carry a big-endian u32 length before each frame
+fn write_stream<W: Write>(w: &mut W, frame: &[u8]) -> io::Result<()> {
+    let len = u32::try_from(frame.len()).map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "frame too long"))?;
+    w.write_all(&len.to_be_bytes())?;
+    w.write_all(frame)?;
+    w.flush()
+}
+
+// a length prefix over max fails the read with InvalidData
+fn read_stream<R: Read>(r: &mut R, max: usize) -> io::Result<Option<Vec<u8>>> {
+    let mut len = [0; 4];
+    match r.read_exact(&mut len) {
+        Ok(()) => {},
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    }
+    let len = u32::from_be_bytes(len) as usize;
+    if len > max {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "frame over the maximum length"));
+    }
+    // grown as the bytes arrive rather than sized up front on the peer's word
+    let mut buf = Vec::new();
+    r.take(len as u64).read_to_end(&mut buf)?;
+    if buf.len() < len {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(Some(buf))
+}
+
+// a socket with its own cap on the frames it reads, the bare socket types use the defaults
+pub struct Framed<S> {
+    inner: S,
+    max_frame_len: usize,
+    // kept across datagram reads
+    buf: Vec<u8>,
+}
+impl<S> Framed<S> {
+    pub fn new(inner: S, max_frame_len: usize) -> Framed<S> {
+        Framed { inner, max_frame_len, buf: Vec::new() }
+    }
+    pub fn max_frame_len(&self) -> usize {
+        self.max_frame_len
+    }
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+impl<S> Debug for Framed<S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Framed")
+    }
+}
+
+// shutting down a clone of the socket wakes the read blocked on the original
+macro_rules! stream_transport {
+    ($t:ty) => {
+        impl Transport for $t {
+            fn write_frame(&mut self, frame: &[u8]) -> io::Result<()> {
+                write_stream(self,frame)
+            }
+            fn read_frame(&mut self) -> io::Result<Option<Vec<u8>>> {
+                read_stream(self,DEFAULT_MAX_FRAME_LEN)
+            }
+            fn shutdown(&mut self) -> io::Result<()> {
+                <$t>::shutdown(self,Shutdown::Both)
+            }
+            fn closer(&self) -> Option<Closer> {
+                let socket = self.try_clone().ok()?;
+                Some(Box::new(move || { let _ = socket.shutdown(Shutdown::Both); }))
+            }
+        }
+        impl Transport for Framed<$t> {
+            fn write_frame(&mut self, frame: &[u8]) -> io::Result<()> {
+                write_stream(&mut self.inner,frame)
+            }
+            fn read_frame(&mut self) -> io::Result<Option<Vec<u8>>> {
+                read_stream(&mut self.inner,self.max_frame_len)
+            }
+            fn shutdown(&mut self) -> io::Result<()> {
+                self.inner.shutdown(Shutdown::Both)
+            }
+            fn closer(&self) -> Option<Closer> {
+                self.inner.closer()
+            }
+        }
+    };
+}
+stream_transport!(TcpStream);
+#[cfg(unix)]
+stream_transport!(UnixStream);
+
+// a connected socket, one datagram per frame behind a tag byte, so an empty frame is
+// a datagram of its own; a shut down peer doesn't wake the reader, it sends END first
+#[cfg(unix)]
+const FRAME: u8 = 0;
+#[cfg(unix)]
+const END: u8 = 1;
+
+#[cfg(unix)]
+fn write_datagram(socket: &UnixDatagram, frame: &[u8]) -> io::Result<()> {
+    let mut datagram = Vec::with_capacity(frame.len() + 1);
+    datagram.push(FRAME);
+    datagram.extend_from_slice(frame);
+    socket.send(&datagram).map(|_| ())
+}
+
+#[cfg(unix)]
+fn read_datagram(socket: &UnixDatagram, buf: &mut Vec<u8>, max: usize) -> io::Result<Option<Vec<u8>>> {
+    // the tag, the frame and a byte more to tell a truncated datagram from a full one
+    buf.resize(max.saturating_add(2),0);
+    let n = socket.recv(buf)?;
+    match &buf[.. n] {
+        // shut down on this side, by a closer
+        [] => Ok(None),
+        _ if n > max + 1 => Err(io::Error::new(io::ErrorKind::InvalidData, "datagram over the maximum length, truncated")),
+        [FRAME,frame @ ..] => Ok(Some(frame.to_vec())),
+        [END] => Ok(None),
+        _ => Err(io::Error::new(io::ErrorKind::InvalidData, "unknown datagram tag")),
+    }
+}
+
+#[cfg(unix)]
+fn shutdown_datagram(socket: &UnixDatagram) -> io::Result<()> {
+    let _ = socket.send(&[END]);
+    socket.shutdown(Shutdown::Both)
+}
+
+#[cfg(unix)]
+fn datagram_closer(socket: &UnixDatagram) -> Option<Closer> {
+    let socket = socket.try_clone().ok()?;
+    Some(Box::new(move || { let _ = socket.shutdown(Shutdown::Both); }))
+}
+
+#[cfg(unix)]
+impl Transport for UnixDatagram {
+    fn write_frame(&mut self, frame: &[u8]) -> io::Result<()> {
+        write_datagram(self,frame)
+    }
+    fn read_frame(&mut self) -> io::Result<Option<Vec<u8>>> {
+        read_datagram(self,&mut Vec::new(),DEFAULT_MAX_DATAGRAM_LEN)
+    }
+    fn shutdown(&mut self) -> io::Result<()> {
+        shutdown_datagram(self)
+    }
+    fn closer(&self) -> Option<Closer> {
+        datagram_closer(self)
+    }
+}
+
+#[cfg(unix)]
+impl Transport for Framed<UnixDatagram> {
+    fn write_frame(&mut self, frame: &[u8]) -> io::Result<()> {
+        write_datagram(&self.inner,frame)
+    }
+    fn read_frame(&mut self) -> io::Result<Option<Vec<u8>>> {
+        read_datagram(&self.inner,&mut self.buf,self.max_frame_len)
+    }
+    fn shutdown(&mut self) -> io::Result<()> {
+        shutdown_datagram(&self.inner)
+    }
+    fn closer(&self) -> Option<Closer> {
+        datagram_closer(&self.inner)
+    }
+}
+
+// in-process transport for tests; None on a channel is the end of the frames, sent by
+// a shutdown, a drop or a closer
+pub struct MemoryTransport {
+    tx: Option<mpsc::Sender<Option<Vec<u8>>>>,
+    rx: mpsc::Receiver<Option<Vec<u8>>>,
+    // feeds rx, for the closer
+    wake: mpsc::Sender<Option<Vec<u8>>>,
+    ended: bool,
+}
+impl Transport for MemoryTransport {
+    fn write_frame(&mut self, frame: &[u8]) -> io::Result<()> {
+        match self.tx.as_ref().map(|tx| tx.send(Some(frame.to_vec()))) {
+            Some(Ok(())) => Ok(()),
+            _ => Err(io::ErrorKind::BrokenPipe.into()),
+        }
+    }
+    fn read_frame(&mut self) -> io::Result<Option<Vec<u8>>> {
+        if self.ended {
+            return Ok(None);
+        }
+        let frame = self.rx.recv().ok().flatten();
+        self.ended = frame.is_none();
+        Ok(frame)
+    }
+    fn shutdown(&mut self) -> io::Result<()> {
+        if let Some(tx) = self.tx.take() {
+            let _ = tx.send(None);
+        }
+        Ok(())
+    }
+    fn closer(&self) -> Option<Closer> {
+        let wake = self.wake.clone();
+        Some(Box::new(move || { let _ = wake.send(None); }))
+    }
+}
+impl Debug for MemoryTransport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "MemoryTransport")
+    }
+}
+impl Drop for MemoryTransport {
+    fn drop(&mut self) {
+        let _ = self.shutdown();
+    }
+}
+
+pub fn memory() -> (MemoryTransport,MemoryTransport) {
+    let (atx,arx) = mpsc::channel();
+    let (btx,brx) = mpsc::channel();
+    let a = MemoryTransport { tx: Some(atx.clone()), rx: brx, wake: btx.clone(), ended: false };
+    (a,MemoryTransport { tx: Some(btx), rx: arx, wake: atx, ended: false })
+}
+
+// writes each value as a frame; a failed write means the remote receiver is gone
+pub struct Sender<T,R: Transport>(Mutex<R>,PhantomData<fn(T)>);
+impl<T: Wire, R: Transport> Sender<T,R> {
+    pub fn new(transport: R) -> Sender<T,R> {
+        Sender(Mutex::new(transport),PhantomData)
+    }
+    pub fn send(&self, t: T) -> Result<(),T> {
+        let mut frame = Vec::new();
+        t.encode(&mut frame);
+        match self.0.lock().write_frame(&frame) {
+            Ok(()) => Ok(()),
+            Err(_) => Err(t),
+        }
+    }
+}
+impl<T: Wire, R: Transport> Transmit for Sender<T,R> {
+    type Item = T;
+    fn send(&self, t: T) -> Result<(),T> {
+        Sender::send(self,t)
+    }
+}
+impl<T,R: Transport> Debug for Sender<T,R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Sender")
+    }
+}
+impl<T,R: Transport> Drop for Sender<T,R> {
+    fn drop(&mut self) {
+        // the remote reader sees the shutdown as every sender dropped
+        let _ = self.0.lock().shutdown();
+    }
+}
+
+// the local end of a remote stream of values, fed by a reader thread through an mpsc
+// channel, so recv, recv_timeout and disconnects behave as for any in-process receiver;
+// dropping it closes the transport and with that ends the thread
+pub struct Receiver<T> {
+    rx: mpsc::Receiver<T>,
+    closer: Option<Closer>,
+}
+impl<T> Receiver<T> {
+    pub fn recv(&self) -> Result<T,RecvError> {
+        self.rx.recv()
+    }
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<T,RecvError> {
+        self.rx.recv_timeout(timeout)
+    }
+    pub fn try_recv(&self) -> Result<T,TryRecvError> {
+        self.rx.try_recv()
+    }
+}
+impl_receiver!(Receiver<T>,
+    fn watch(&self, watcher: &Arc<Watcher>) -> bool {
+        self.rx.watch(watcher);
+        true
+    }
+);
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        if let Some(close) = self.closer.take() {
+            close();
+        }
+    }
+}
+
+// a frame that doesn't decode ends the channel
+pub fn receiver<T: Wire + Send + 'static>(mut transport: impl Transport) -> io::Result<Receiver<T>> {
+    let (tx,rx) = mpsc::channel();
+    let closer = transport.closer();
+    thread::Builder::new()
+        .name("transmitter-transport".to_string())
+        .spawn(move || {
+            while let Ok(Some(frame)) = transport.read_frame() {
+                let t = match T::decode(&frame) {
+                    Some(t) => t,
+                    None => break,
+                };
+                if tx.send(t).is_err() {
+                    break;
+                }
+            }
+            let _ = transport.shutdown();
+        })?;
+    Ok(Receiver { rx, closer })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{memory,read_stream,receiver,Framed,Sender,Transport};
+    use crate::RecvError;
+    use std::io::ErrorKind;
+    use std::time::Duration;
+
+    #[test]
+    fn test_memory_transport() {
+        let (a,b) = memory();
+        let tx = Sender::<String,_>::new(a);
+        let rx = receiver::<String>(b).unwrap();
+        tx.send(String::from("in memory")).unwrap();
+        assert_eq!(rx.recv().as_deref(),Ok("in memory"));
+        assert_eq!(rx.recv_timeout(Duration::from_millis(10)),Err(RecvError::Timeout));
+        drop(tx);
+        assert_eq!(rx.recv(),Err(RecvError::Disconnected));
+    }
+
+    #[test]
+    fn test_receiver_dropped() {
+        let (mut a,b) = memory();
+        drop(receiver::<u8>(b).unwrap());
+        // the reader thread was closed out of its wait and shut its side down
+        assert_eq!(a.read_frame().unwrap(),None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_datagram() {
+        let (a,b) = std::os::unix::net::UnixDatagram::pair().unwrap();
+        let tx = Sender::<Vec<u8>,_>::new(a);
+        let rx = receiver::<Vec<u8>>(b).unwrap();
+        for v in [vec![1],vec![],vec![2,3]] {
+            tx.send(v).unwrap();
+        }
+        drop(tx);
+        assert_eq!(rx.iter().collect::<Vec<_>>(),vec![vec![1],vec![],vec![2,3]]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_datagram_truncated() {
+        let (a,b) = std::os::unix::net::UnixDatagram::pair().unwrap();
+        let (mut a,mut b) = (Framed::new(a,8),Framed::new(b,4));
+        a.write_frame(&[1,2,3,4]).unwrap();
+        a.write_frame(&[1,2,3,4,5]).unwrap();
+        assert_eq!(b.read_frame().unwrap(),Some(vec![1,2,3,4]));
+        assert_eq!(b.read_frame().unwrap_err().kind(),ErrorKind::InvalidData);
+        a.shutdown().unwrap();
+        assert_eq!(b.read_frame().unwrap(),None);
+    }
+
+    #[test]
+    fn test_frame_len() {
+        let mut hostile = &[0xff,0xff,0xff,0xff,1,2][..];
+        assert_eq!(read_stream(&mut hostile,super::DEFAULT_MAX_FRAME_LEN).unwrap_err().kind(),ErrorKind::InvalidData);
+        let mut short = &[0,0,0,4,1,2][..];
+        assert_eq!(read_stream(&mut short,16).unwrap_err().kind(),ErrorKind::UnexpectedEof);
+        let mut ok = &[0,0,0,2,1,2][..];
+        assert_eq!(read_stream(&mut ok,2).unwrap(),Some(vec![1,2]));
+        let mut long = &[0,0,0,2,1,2][..];
+        assert_eq!(read_stream(&mut long,1).unwrap_err().kind(),ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,74 @@
+use std::io;
+use std::os::unix::net::{UnixDatagram,UnixStream};
+
+use crate::wire::Wire;
+use crate::transport;
+
+pub type Sender<T> = transport::Sender<T,UnixStream>;
+pub type DatagramSender<T> = transport::Sender<T,UnixDatagram>;
+
+pub fn sender<T: Wire>(stream: UnixStream) -> Sender<T> {
+    transport::Sender::new(stream)
+}
+
+// dropping the Receiver shuts the stream down, which fails the remote sender's next send
+pub fn receiver<T: Wire + Send + 'static>(stream: UnixStream) -> io::Result<transport::Receiver<T>> {
+    transport::receiver(stream)
+}
+
+// the socket must be connected; a value is one datagram, up to
+// transport::DEFAULT_MAX_DATAGRAM_LEN bytes unless the socket goes through transport::Framed
+pub fn datagram_sender<T: Wire>(socket: UnixDatagram) -> DatagramSender<T> {
+    transport::Sender::new(socket)
+}
+
+pub fn datagram_receiver<T: Wire + Send + 'static>(socket: UnixDatagram) -> io::Result<transport::Receiver<T>> {
+    transport::receiver(socket)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{datagram_receiver,datagram_sender,receiver,sender};
+    use crate::RecvError;
+    use std::os::unix::net::{UnixDatagram,UnixStream};
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_stream_pipeline() {
+        let (a,b) = UnixStream::pair().unwrap();
+        let tx = sender::<u32>(a);
+        let rx = receiver::<u32>(b).unwrap();
+        assert_eq!(rx.recv_timeout(Duration::from_millis(10)),Err(RecvError::Timeout));
+        let h = thread::spawn(move || {
+            for i in 0 .. 100 {
+                tx.send(i).unwrap();
+            }
+        });
+        assert_eq!(rx.iter().collect::<Vec<_>>(),(0 .. 100).collect::<Vec<_>>());
+        h.join().unwrap();
+    }
+
+    #[test]
+    fn test_receiver_dropped() {
+        let (a,b) = UnixStream::pair().unwrap();
+        let tx = sender::<String>(a);
+        drop(receiver::<String>(b).unwrap());
+        // shut down by the drop, not by the reader thread's next frame
+        assert!(tx.send(String::from("lost")).is_err());
+    }
+
+    #[test]
+    fn test_datagrams() {
+        let (a,b) = UnixDatagram::pair().unwrap();
+        let tx = datagram_sender::<String>(a);
+        let rx = datagram_receiver::<String>(b).unwrap();
+        tx.send(String::new()).unwrap();
+        tx.send(String::from("datagram")).unwrap();
+        assert_eq!(rx.recv().as_deref(),Ok(""));
+        assert_eq!(rx.recv().as_deref(),Ok("datagram"));
+        drop(rx);
+        // the closed socket refuses the next datagram
+        assert!(tx.send(String::from("lost")).is_err());
+    }
+}
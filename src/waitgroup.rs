@@ -0,0 +1,120 @@
+use core::fmt::{self,Debug};
+#[cfg(feature = "std")]
+use std::time::{Duration,Instant};
+
+use crate::sync::{Arc,Condvar,Mutex};
+
+struct Inner {
+    count: Mutex<usize>,
+    cond: Condvar,
+}
+
+pub struct WaitGroup(Arc<Inner>);
+impl WaitGroup {
+    pub fn new() -> WaitGroup {
+        WaitGroup(Arc::new(Inner {
+            count: Mutex::new(0),
+            cond: Condvar::new(),
+        }))
+    }
+    pub fn add(&self) -> WaitGuard {
+        *self.0.count.lock() += 1;
+        WaitGuard(self.0.clone())
+    }
+    pub fn count(&self) -> usize {
+        *self.0.count.lock()
+    }
+    pub fn wait(&self) {
+        let mut count = self.0.count.lock();
+        while *count > 0 {
+            count = self.0.cond.wait(count);
+        }
+    }
+    // false if workers are still running at the timeout
+    #[cfg(feature = "std")]
+    pub fn wait_timeout(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now().checked_add(timeout);
+        let mut count = self.0.count.lock();
+        while *count > 0 {
+            let (guard,timed_out) = self.0.cond.wait_deadline(count,deadline);
+            if timed_out {
+                return false;
+            }
+            count = guard;
+        }
+        true
+    }
+}
+impl Clone for WaitGroup {
+    fn clone(&self) -> WaitGroup {
+        WaitGroup(self.0.clone())
+    }
+}
+impl Default for WaitGroup {
+    fn default() -> WaitGroup {
+        WaitGroup::new()
+    }
+}
+impl Debug for WaitGroup {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "WaitGroup({})", self.count())
+    }
+}
+
+pub struct WaitGuard(Arc<Inner>);
+impl Debug for WaitGuard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "WaitGuard")
+    }
+}
+impl Drop for WaitGuard {
+    fn drop(&mut self) {
+        let mut count = self.0.count.lock();
+        *count -= 1;
+        if *count == 0 {
+            self.0.cond.notify_all();
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::WaitGroup;
+    use std::sync::atomic::{AtomicUsize,Ordering};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_wait() {
+        let wg = WaitGroup::new();
+        let done = Arc::new(AtomicUsize::new(0));
+        for i in 0 .. 4 {
+            let guard = wg.add();
+            let done = done.clone();
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(i * 50));
+                done.fetch_add(1,Ordering::SeqCst);
+                drop(guard);
+            });
+        }
+        wg.wait();
+        assert_eq!(done.load(Ordering::SeqCst),4);
+        assert_eq!(wg.count(),0);
+        wg.wait();
+    }
+
+    #[test]
+    fn test_wait_timeout() {
+        let wg = WaitGroup::new();
+        let guard = wg.add();
+        assert!(!wg.wait_timeout(Duration::from_millis(50)));
+        let h = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            drop(guard);
+        });
+        assert!(wg.wait_timeout(Duration::from_secs(5)));
+        h.join().unwrap();
+    }
+}
@@ -0,0 +1,89 @@
+use alloc::boxed::Box;
+use core::fmt::{self,Debug};
+
+use crate::sync::{Arc,AtomicBool,Mutex,Ordering};
+
+type Hook = Box<dyn FnOnce() + Send>;
+
+struct Inner {
+    woken: AtomicBool,
+    hook: Mutex<Option<Hook>>,
+}
+
+// handed out by OneGet::poll_get when the value isn't there yet; the channel wakes it
+// once it completes, for an event loop to poll again
+pub struct WakeHandle(Arc<Inner>);
+impl WakeHandle {
+    pub(crate) fn new() -> WakeHandle {
+        WakeHandle(Arc::new(Inner { woken: AtomicBool::new(false), hook: Mutex::new(None) }))
+    }
+    pub(crate) fn wake(&self) {
+        self.0.woken.store(true,Ordering::SeqCst);
+        let hook = self.0.hook.lock().take();
+        if let Some(hook) = hook {
+            hook();
+        }
+    }
+    pub fn is_woken(&self) -> bool {
+        self.0.woken.load(Ordering::SeqCst)
+    }
+    // runs on the completing thread, or right away if that already happened; a later
+    // hook replaces an earlier one
+    pub fn on_wake<F: FnOnce() + Send + 'static>(&self, f: F) {
+        let mut hook = self.0.hook.lock();
+        if !self.is_woken() {
+            *hook = Some(Box::new(f));
+            return;
+        }
+        drop(hook);
+        f();
+    }
+}
+impl Clone for WakeHandle {
+    fn clone(&self) -> WakeHandle {
+        WakeHandle(self.0.clone())
+    }
+}
+impl Debug for WakeHandle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "WakeHandle({})", self.is_woken())
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::oneshot;
+    use std::sync::mpsc;
+    use std::task::Poll;
+    use std::thread;
+
+    #[test]
+    fn test_poll_get() {
+        let (tx,mut rx) = oneshot::<u8>();
+        let mut handle = None;
+        assert_eq!(rx.poll_get(|h| handle = Some(h)),Poll::Pending);
+        let handle = handle.unwrap();
+        let (wtx,wrx) = mpsc::channel();
+        handle.on_wake(move || wtx.send(()).unwrap());
+        assert!(!handle.is_woken());
+        let h = thread::spawn(move || tx.set(5).unwrap());
+        wrx.recv().unwrap();
+        assert!(handle.is_woken());
+        assert_eq!(rx.poll_get(|_| panic!("already complete")),Poll::Ready(Some(5)));
+        h.join().unwrap();
+    }
+
+    #[test]
+    fn test_woken_before_hook() {
+        let (tx,mut rx) = oneshot::<u8>();
+        let mut handle = None;
+        assert_eq!(rx.poll_get(|h| handle = Some(h)),Poll::Pending);
+        drop(tx);
+        let handle = handle.unwrap();
+        let (wtx,wrx) = mpsc::channel();
+        handle.on_wake(move || wtx.send(()).unwrap());
+        assert_eq!(wrx.try_recv(),Ok(()));
+        assert_eq!(rx.poll_get(|_| {}),Poll::Ready(None));
+    }
+}
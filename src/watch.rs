@@ -0,0 +1,260 @@
+use core::fmt::{self,Debug};
+use core::ops::Deref;
+
+use crate::error::{RecvError,TryRecvError};
+use crate::iter::Receive;
+use crate::sync::{Arc,AtomicUsize,Condvar,Mutex,MutexGuard,Ordering};
+
+struct State<T> {
+    value: T,
+    // wraps, a receiver would have to miss exactly usize::MAX + 1 sends to be fooled
+    version: usize,
+    sender: bool,
+    receivers: usize,
+}
+
+struct Inner<T> {
+    state: Mutex<State<T>>,
+    cond: Condvar,
+}
+
+pub struct Ref<'t,T>(MutexGuard<'t,State<T>>);
+impl<'t,T> Deref for Ref<'t,T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.0.value
+    }
+}
+impl<'t,T: Debug> Debug for Ref<'t,T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.value.fmt(f)
+    }
+}
+
+pub struct Sender<T>(Arc<Inner<T>>);
+impl<T> Sender<T> {
+    pub fn send(&self, t: T) -> Result<(),T> {
+        let mut lock = self.0.state.lock();
+        if lock.receivers == 0 {
+            return Err(t);
+        }
+        let old = core::mem::replace(&mut lock.value,t);
+        lock.version = lock.version.wrapping_add(1);
+        drop(lock);
+        self.0.cond.notify_all();
+        drop(old);
+        Ok(())
+    }
+    pub fn borrow(&self) -> Ref<'_,T> {
+        Ref(self.0.state.lock())
+    }
+    pub fn subscribe(&self) -> Receiver<T> {
+        let mut lock = self.0.state.lock();
+        lock.receivers += 1;
+        Receiver {
+            inner: self.0.clone(),
+            seen: AtomicUsize::new(lock.version),
+        }
+    }
+}
+impl<T> Debug for Sender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Sender")
+    }
+}
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        self.0.state.lock().sender = false;
+        self.0.cond.notify_all();
+    }
+}
+
+// seen is atomic so the Receive impl can mark values through &self
+pub struct Receiver<T> {
+    inner: Arc<Inner<T>>,
+    seen: AtomicUsize,
+}
+impl<T> Receiver<T> {
+    fn seen(&self) -> usize {
+        self.seen.load(Ordering::Relaxed)
+    }
+    fn mark(&self, lock: &MutexGuard<'_,State<T>>) {
+        self.seen.store(lock.version,Ordering::Relaxed);
+    }
+    fn wait_changed(&self) -> Result<MutexGuard<'_,State<T>>,RecvError> {
+        let mut lock = self.inner.state.lock();
+        loop {
+            if lock.version != self.seen() {
+                self.mark(&lock);
+                return Ok(lock);
+            }
+            if !lock.sender {
+                return Err(RecvError::Disconnected);
+            }
+            lock = self.inner.cond.wait(lock);
+        }
+    }
+    pub fn borrow(&self) -> Ref<'_,T> {
+        Ref(self.inner.state.lock())
+    }
+    pub fn borrow_and_update(&mut self) -> Ref<'_,T> {
+        let lock = self.inner.state.lock();
+        self.mark(&lock);
+        Ref(lock)
+    }
+    pub fn has_changed(&self) -> Result<bool,RecvError> {
+        let lock = self.inner.state.lock();
+        match lock.version != self.seen() {
+            true => Ok(true),
+            false if !lock.sender => Err(RecvError::Disconnected),
+            false => Ok(false),
+        }
+    }
+    pub fn changed(&mut self) -> Result<(),RecvError> {
+        self.wait_changed().map(drop)
+    }
+    pub fn wait_for(&mut self, mut predicate: impl FnMut(&T) -> bool) -> Result<Ref<'_,T>,RecvError> {
+        let mut lock = self.inner.state.lock();
+        loop {
+            self.mark(&lock);
+            if predicate(&lock.value) {
+                return Ok(Ref(lock));
+            }
+            if !lock.sender {
+                return Err(RecvError::Disconnected);
+            }
+            while lock.version == self.seen() && lock.sender {
+                lock = self.inner.cond.wait(lock);
+            }
+        }
+    }
+}
+impl<T> Clone for Receiver<T> {
+    fn clone(&self) -> Receiver<T> {
+        self.inner.state.lock().receivers += 1;
+        Receiver {
+            inner: self.inner.clone(),
+            seen: AtomicUsize::new(self.seen()),
+        }
+    }
+}
+// each recv is the value current at a change, the ones it replaced are skipped;
+// debounce and throttle go on top of this
+impl<T: Clone> Receive for Receiver<T> {
+    type Item = T;
+    fn recv(&self) -> Result<T,RecvError> {
+        self.wait_changed().map(|lock| lock.value.clone())
+    }
+    fn try_recv(&self) -> Result<T,TryRecvError> {
+        let lock = self.inner.state.lock();
+        match lock.version != self.seen() {
+            true => {
+                self.mark(&lock);
+                Ok(lock.value.clone())
+            },
+            false if !lock.sender => Err(TryRecvError::Disconnected),
+            false => Err(TryRecvError::Empty),
+        }
+    }
+}
+impl<T> Debug for Receiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Receiver")
+    }
+}
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.inner.state.lock().receivers -= 1;
+    }
+}
+
+pub fn channel<T>(initial: T) -> (Sender<T>,Receiver<T>) {
+    let r = Arc::new(Inner {
+        state: Mutex::new(State {
+            value: initial,
+            version: 0,
+            sender: true,
+            receivers: 1,
+        }),
+        cond: Condvar::new(),
+    });
+    let rx = Receiver {
+        inner: r.clone(),
+        seen: AtomicUsize::new(0),
+    };
+    (Sender(r),rx)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::channel;
+    use crate::error::{RecvError,TryRecvError};
+    use crate::Receive;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_changed() {
+        let (tx,mut rx) = channel(0);
+        assert_eq!(*rx.borrow(),0);
+        assert_eq!(rx.has_changed(),Ok(false));
+        let h = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(100));
+            tx.send(1).unwrap();
+        });
+        assert_eq!(rx.changed(),Ok(()));
+        assert_eq!(*rx.borrow(),1);
+        h.join().unwrap();
+        assert_eq!(rx.changed(),Err(RecvError::Disconnected));
+    }
+
+    #[test]
+    fn test_latest_value_wins() {
+        let (tx,mut rx) = channel(0);
+        for i in 1 .. 5 {
+            tx.send(i).unwrap();
+        }
+        assert_eq!(rx.changed(),Ok(()));
+        assert_eq!(*rx.borrow(),4);
+        assert_eq!(rx.has_changed(),Ok(false));
+    }
+
+    #[test]
+    fn test_wait_for() {
+        let (tx,mut rx) = channel(0);
+        let h = thread::spawn(move || {
+            for i in 1 .. 10 {
+                tx.send(i).unwrap();
+                thread::sleep(Duration::from_millis(10));
+            }
+        });
+        assert!(*rx.wait_for(|v| *v >= 5).unwrap() >= 5);
+        h.join().unwrap();
+        assert!(rx.wait_for(|v| *v > 100).is_err());
+    }
+
+    #[test]
+    fn test_debounce_throttle() {
+        let (tx,rx) = channel(0);
+        let h = thread::spawn(move || {
+            for i in 1 .. 6 {
+                tx.send(i).unwrap();
+                thread::sleep(Duration::from_millis(5));
+            }
+            tx
+        });
+        let rx = rx.debounce(Duration::from_millis(40));
+        assert_eq!(rx.recv(),Ok(5));
+        let tx = h.join().unwrap();
+        let rx = rx.into_inner().throttle(Duration::from_millis(30));
+        tx.send(6).unwrap();
+        assert_eq!(rx.recv(),Ok(6));
+        tx.send(7).unwrap();
+        tx.send(8).unwrap();
+        assert_eq!(rx.recv(),Ok(8));
+        assert_eq!(rx.try_recv(),Err(TryRecvError::Empty));
+        drop(tx);
+        assert_eq!(rx.into_inner().try_recv(),Err(TryRecvError::Disconnected));
+    }
+}
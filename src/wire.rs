@@ -0,0 +1,113 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+#[cfg(feature = "serde")]
+use serde::{de::DeserializeOwned,Serialize};
+
+// the byte form of a value sent over a transport or shared memory; the serde feature adds
+// Bincode for any serde type
+pub trait Wire: Sized {
+    fn encode(&self, buf: &mut Vec<u8>);
+    fn decode(bytes: &[u8]) -> Option<Self>;
+}
+
+macro_rules! wire_int {
+    ($($t:ty),*) => {$(
+        impl Wire for $t {
+            fn encode(&self, buf: &mut Vec<u8>) {
+                buf.extend_from_slice(&self.to_be_bytes());
+            }
+            fn decode(bytes: &[u8]) -> Option<$t> {
+                Some(<$t>::from_be_bytes(bytes.try_into().ok()?))
+            }
+        }
+    )*};
+}
+wire_int!(u8,u16,u32,u64,i8,i16,i32,i64);
+
+impl Wire for bool {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        buf.push(*self as u8);
+    }
+    fn decode(bytes: &[u8]) -> Option<bool> {
+        match bytes {
+            [0] => Some(false),
+            [1] => Some(true),
+            _ => None,
+        }
+    }
+}
+impl Wire for () {
+    fn encode(&self, _buf: &mut Vec<u8>) {}
+    fn decode(bytes: &[u8]) -> Option<()> {
+        bytes.is_empty().then_some(())
+    }
+}
+impl Wire for Vec<u8> {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(self);
+    }
+    fn decode(bytes: &[u8]) -> Option<Vec<u8>> {
+        Some(bytes.to_vec())
+    }
+}
+impl Wire for String {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(self.as_bytes());
+    }
+    fn decode(bytes: &[u8]) -> Option<String> {
+        String::from_utf8(bytes.to_vec()).ok()
+    }
+}
+
+#[cfg(feature = "serde")]
+pub(crate) fn bincode_encode<T: Serialize>(t: &T, buf: &mut Vec<u8>) {
+    let start = buf.len();
+    // a value whose Serialize impl fails goes out as a frame that doesn't decode
+    if bincode::serialize_into(&mut *buf,t).is_err() {
+        buf.truncate(start);
+    }
+}
+
+#[cfg(feature = "serde")]
+pub(crate) fn bincode_decode<T: DeserializeOwned>(bytes: &[u8]) -> Option<T> {
+    bincode::deserialize(bytes).ok()
+}
+
+// a serde value in bincode's encoding
+#[cfg(feature = "serde")]
+#[derive(Clone,Debug,PartialEq)]
+pub struct Bincode<T>(pub T);
+#[cfg(feature = "serde")]
+impl<T: Serialize + DeserializeOwned> Wire for Bincode<T> {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        bincode_encode(&self.0,buf);
+    }
+    fn decode(bytes: &[u8]) -> Option<Bincode<T>> {
+        bincode_decode(bytes).map(Bincode)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::Wire;
+
+    #[test]
+    fn test_wire() {
+        let mut buf = Vec::new();
+        0x0102u16.encode(&mut buf);
+        assert_eq!(buf,vec![1,2]);
+        assert_eq!(u16::decode(&buf),Some(0x0102));
+        assert_eq!(u32::decode(&buf),None);
+        assert_eq!(bool::decode(&[2]),None);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_bincode() {
+        let mut buf = Vec::new();
+        super::Bincode((1u8,String::from("pair"))).encode(&mut buf);
+        assert_eq!(super::Bincode::decode(&buf),Some(super::Bincode((1u8,String::from("pair")))));
+        assert_eq!(super::Bincode::<(u8,String)>::decode(&buf[.. 3]),None);
+    }
+}
@@ -0,0 +1,80 @@
+use crate::sync::{Arc,Mutex};
+use crate::{oneshot,OneGet,OneSet};
+
+struct Zip<T,U> {
+    left: Option<T>,
+    right: Option<U>,
+    tx: Option<OneSet<(T,U)>>,
+}
+impl<T,U> Zip<T,U> {
+    // f stores an input's value, false if the input closed without one
+    fn arrive(zip: &Mutex<Zip<T,U>>, f: impl FnOnce(&mut Zip<T,U>) -> bool) {
+        let ready = {
+            let mut zip = zip.lock();
+            match f(&mut zip) {
+                false => zip.tx.take().map(|tx| (tx,None)),
+                true if zip.left.is_some() && zip.right.is_some() => {
+                    let pair = (zip.left.take().unwrap(),zip.right.take().unwrap());
+                    zip.tx.take().map(|tx| (tx,Some(pair)))
+                },
+                true => None,
+            }
+        };
+        // a closed input drops the setter here, outside of the lock
+        if let Some((tx,Some(pair))) = ready {
+            let _ = tx.set(pair);
+        }
+    }
+}
+
+impl<T: Send + 'static> OneGet<T> {
+    // completes on the thread delivering the second value, closes as soon as either input does
+    pub fn zip<U: Send + 'static>(self, other: OneGet<U>) -> OneGet<(T,U)> {
+        let (tx,rx) = oneshot();
+        let zip = Arc::new(Mutex::new(Zip { left: None, right: None, tx: Some(tx) }));
+        let left = zip.clone();
+        self.on_ready(move |res| Zip::arrive(&left,|zip| {
+            zip.left = res;
+            zip.left.is_some()
+        }));
+        other.on_ready(move |res| Zip::arrive(&zip,|zip| {
+            zip.right = res;
+            zip.right.is_some()
+        }));
+        rx
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::oneshot;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_zip() {
+        let (atx,arx) = oneshot();
+        let (btx,brx) = oneshot();
+        let rx = arx.zip(brx);
+        let h = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(10));
+            btx.set("cache").unwrap();
+            thread::sleep(Duration::from_millis(10));
+            atx.set(1).unwrap();
+        });
+        assert_eq!(rx.wait(),Some((1,"cache")));
+        h.join().unwrap();
+    }
+
+    #[test]
+    fn test_zip_closed() {
+        let (atx,arx) = oneshot::<u8>();
+        let (btx,brx) = oneshot::<u8>();
+        let rx = arx.zip(brx);
+        drop(btx);
+        assert!(rx.is_closed());
+        assert_eq!(atx.set(1),Ok(()));
+        assert_eq!(rx.wait(),None);
+    }
+}